@@ -0,0 +1,125 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+
+static OLDEST_HIT_SECS: AtomicU64 = AtomicU64::new(0);
+
+/// Api responses stored as json files, one per request.
+#[derive(Clone)]
+pub struct Cache {
+    dir: PathBuf,
+    ttl: Option<Duration>,
+}
+
+/// Age of the oldest response served from any cache in this process.
+pub fn oldest_hit() -> Option<Duration> {
+    match OLDEST_HIT_SECS.load(Ordering::Relaxed) {
+        0 => None,
+        secs => Some(Duration::from_secs(secs)),
+    }
+}
+
+impl Cache {
+    /// Entries older than the ttl are ignored, without a ttl they never expire.
+    pub fn new(dir: PathBuf, ttl: Option<Duration>) -> Cache {
+        Cache { dir, ttl }
+    }
+
+    /// The stored value, none when missing, expired or no longer decodable.
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let path = self.path(key);
+        let age = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| SystemTime::now().duration_since(modified).ok())?;
+        if self.ttl.is_some_and(|ttl| age >= ttl) {
+            return None;
+        }
+        let content = std::fs::read_to_string(path).ok()?;
+        let value = serde_json::from_str(&content).ok()?;
+        OLDEST_HIT_SECS.fetch_max(age.as_secs().max(1), Ordering::Relaxed);
+        Some(value)
+    }
+
+    /// Stores the value, failures only cost a later request.
+    pub fn put<T: Serialize>(&self, key: &str, value: &T) {
+        let path = self.path(key);
+        let Ok(content) = serde_json::to_string(value) else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let _ = std::fs::write(path, content);
+    }
+
+    // keys hold crate names, search queries and repositories, so every segment
+    // is encoded to stay a single file or directory name inside the cache
+    fn path(&self, key: &str) -> PathBuf {
+        let mut path = self.dir.clone();
+        path.extend(key.split('/').map(encode_segment));
+        path.as_mut_os_string().push(".json");
+        path
+    }
+}
+
+fn encode_segment(segment: &str) -> String {
+    if segment.chars().all(|c| c == '.') {
+        return segment.replace('.', "%2E");
+    }
+    segment
+        .bytes()
+        .map(|byte| match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' => {
+                (byte as char).to_string()
+            }
+            byte => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Component;
+
+    #[test]
+    fn keys_stay_inside_the_cache_dir() {
+        let cache = Cache::new(PathBuf::from("/cache"), None);
+        for key in [
+            "crates.io/crates/q=../../../../escape&sort=relevance",
+            "crates.io/../../escape",
+            "github/../..",
+            "crates.io/a\\..\\..\\b",
+        ] {
+            let path = cache.path(key);
+            assert!(path.starts_with("/cache"), "{}", path.display());
+            assert!(
+                path.components()
+                    .all(|component| !matches!(component, Component::ParentDir)),
+                "{}",
+                path.display()
+            );
+            assert_eq!(path.components().count(), key.split('/').count() + 2);
+        }
+    }
+
+    #[test]
+    fn plain_keys_keep_their_layout() {
+        let cache = Cache::new(PathBuf::from("/cache"), None);
+        assert_eq!(
+            cache.path("crates.io/crates/serde"),
+            PathBuf::from("/cache/crates.io/crates/serde.json")
+        );
+        assert_eq!(
+            cache.path("crates.io/dependencies/serde-1.0.0"),
+            PathBuf::from("/cache/crates.io/dependencies/serde-1.0.0.json")
+        );
+        assert_eq!(
+            cache.path("crates.io/crates/q=a b"),
+            PathBuf::from("/cache/crates.io/crates/q%3Da%20b.json")
+        );
+    }
+}
@@ -0,0 +1,545 @@
+use crate::cache::Cache;
+use crate::error::{Context, Error, Result};
+use crates_io_api::{
+    ApiError, ApiErrors, Crate, CrateDownloads, CrateResponse, CratesPage, Dependencies,
+    Dependency, Meta, Owners, ReverseDependencies, ReverseDependency, User, Version,
+};
+use reqwest::header::HeaderValue;
+use reqwest::{StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// The user agent sent when none is configured, crates.io asks for one naming the tool.
+pub const USER_AGENT: &str = "crabst stats agent";
+pub const API_URL: &str = "https://crates.io/api/v1";
+const BACKOFF_BASE: Duration = Duration::from_millis(500);
+const BACKOFF_MAX: Duration = Duration::from_secs(30);
+const SLOWDOWN_MIN: Duration = Duration::from_secs(1);
+
+/// Results of single api requests, kept apart so callers can tell missing crates and users.
+pub type ApiResult<T> = std::result::Result<T, crates_io_api::Error>;
+
+/// A crates.io team, like `github:rust-lang:libs`.
+#[derive(Serialize, Deserialize)]
+pub struct Team {
+    pub id: u64,
+    pub login: String,
+    pub name: Option<String>,
+    pub avatar: Option<String>,
+    pub url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TeamResponse {
+    team: Team,
+}
+
+#[derive(Serialize, Deserialize)]
+struct UserResponse {
+    user: User,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ReverseDependenciesResponse {
+    dependencies: Vec<Dependency>,
+    versions: Vec<Version>,
+    meta: Meta,
+}
+
+/// The user an api token belongs to and the crates it owns directly.
+#[derive(Deserialize)]
+pub struct Me {
+    pub user: User,
+    pub owned_crates: Vec<OwnedCrate>,
+}
+
+#[derive(Deserialize)]
+pub struct OwnedCrate {
+    pub name: String,
+}
+
+/// Settings of a [`Client`], the defaults talk to crates.io without a cache.
+#[derive(Clone)]
+pub struct ClientOptions {
+    pub registry_url: String,
+    pub user_agent: String,
+    pub proxy: Option<String>,
+    /// minimum time between two requests of the client and its clones
+    pub request_interval: Duration,
+    /// retries of rate limited, failed and timed out requests
+    pub retries: u32,
+    pub cache: Option<Cache>,
+    /// answer from the cache only and fail on misses
+    pub offline: bool,
+}
+
+impl Default for ClientOptions {
+    fn default() -> ClientOptions {
+        ClientOptions {
+            registry_url: API_URL.to_string(),
+            user_agent: USER_AGENT.to_string(),
+            proxy: None,
+            request_interval: Duration::from_millis(100),
+            retries: 3,
+            cache: None,
+            offline: false,
+        }
+    }
+}
+
+/// A crates.io api client that throttles, retries and caches its requests.
+/// Clones share the throttle, so concurrent requests keep to the interval.
+#[derive(Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: Url,
+    cache_prefix: String,
+    request_interval: Duration,
+    next_request: Arc<Mutex<Instant>>,
+    slowdown_ms: Arc<AtomicU64>,
+    retries: u32,
+    cache: Option<Cache>,
+    offline: bool,
+}
+
+impl Client {
+    /// Fails on registry urls that can not be a base and on invalid user agents or proxies.
+    pub fn new(options: &ClientOptions) -> Result<Client> {
+        let http = http_client(&options.user_agent, options.proxy.as_deref())?;
+        let registry_url = &options.registry_url;
+        let base_url = Url::parse(registry_url.trim_end_matches('/'))
+            .ok()
+            .filter(|url| url.has_host() && !url.cannot_be_a_base())
+            .ok_or_else(|| Error::Usage(format!("invalid registry url {}", registry_url)))?;
+        let cache_prefix = match base_url.as_str() {
+            API_URL => "crates.io".to_string(),
+            url => url
+                .split_once("://")
+                .map_or(url, |(_, rest)| rest)
+                .replace(['/', ':'], "_"),
+        };
+        Ok(Client {
+            http,
+            base_url,
+            cache_prefix,
+            request_interval: options.request_interval,
+            next_request: Arc::new(Mutex::new(Instant::now())),
+            slowdown_ms: Arc::new(AtomicU64::new(0)),
+            retries: options.retries,
+            cache: options.cache.clone(),
+            offline: options.offline,
+        })
+    }
+
+    /// The crate with its versions, keywords and categories.
+    pub async fn get_crate(&self, crate_name: &str) -> ApiResult<CrateResponse> {
+        self.get_json(
+            format!("crates/{}", crate_name),
+            &["crates", crate_name],
+            &[],
+        )
+        .await
+    }
+
+    /// Daily downloads of the last 90 days, per version and for older versions as extra downloads.
+    pub async fn crate_downloads(&self, crate_name: &str) -> ApiResult<CrateDownloads> {
+        self.get_json(
+            format!("downloads/{}", crate_name),
+            &["crates", crate_name, "downloads"],
+            &[],
+        )
+        .await
+    }
+
+    /// User and team owners of the crate.
+    pub async fn crate_owners(&self, crate_name: &str) -> ApiResult<Vec<User>> {
+        self.get_json::<Owners>(
+            format!("owners/{}", crate_name),
+            &["crates", crate_name, "owners"],
+            &[],
+        )
+        .await
+        .map(|owners| owners.users)
+    }
+
+    /// A page of 100 crates depending on the crate, pages start at 1.
+    pub async fn crate_reverse_dependencies_page(
+        &self,
+        crate_name: &str,
+        page: u64,
+    ) -> ApiResult<ReverseDependencies> {
+        let response = self
+            .get_json::<ReverseDependenciesResponse>(
+                format!("dependents/{}-{}", crate_name, page),
+                &["crates", crate_name, "reverse_dependencies"],
+                &[
+                    ("page", page.max(1).to_string()),
+                    ("per_page", "100".to_string()),
+                ],
+            )
+            .await?;
+        let dependencies = response
+            .dependencies
+            .into_iter()
+            .filter_map(|dependency| {
+                let version = response
+                    .versions
+                    .iter()
+                    .find(|version| version.id == dependency.version_id)?;
+                Some(ReverseDependency {
+                    crate_version: version.clone(),
+                    dependency,
+                })
+            })
+            .collect();
+        Ok(ReverseDependencies {
+            dependencies,
+            meta: response.meta,
+        })
+    }
+
+    /// Number of crates depending on the crate.
+    pub async fn crate_reverse_dependency_count(&self, crate_name: &str) -> ApiResult<u64> {
+        self.crate_reverse_dependencies_page(crate_name, 1)
+            .await
+            .map(|page| page.meta.total)
+    }
+
+    /// Dependencies of a published version of the crate.
+    pub async fn crate_dependencies(
+        &self,
+        crate_name: &str,
+        version: &str,
+    ) -> ApiResult<Vec<Dependency>> {
+        self.get_json::<Dependencies>(
+            format!("dependencies/{}-{}", crate_name, version),
+            &["crates", crate_name, version, "dependencies"],
+            &[],
+        )
+        .await
+        .map(|dependencies| dependencies.dependencies)
+    }
+
+    /// A page of 100 crates owned by the user, sorted by name.
+    pub async fn user_crates(&self, user_id: u64, page: u64) -> ApiResult<CratesPage> {
+        self.get_json(
+            format!("user_crates/{}-{}", user_id, page),
+            &["crates"],
+            &[
+                ("user_id", user_id.to_string()),
+                ("page", page.to_string()),
+                ("per_page", "100".to_string()),
+                ("sort", "alpha".to_string()),
+            ],
+        )
+        .await
+    }
+
+    /// The 20 most downloaded crates matching the query.
+    pub async fn search_crates(&self, query: &str) -> ApiResult<CratesPage> {
+        self.get_json(
+            format!("search/{}", query),
+            &["crates"],
+            &[
+                ("q", query.to_string()),
+                ("per_page", "20".to_string()),
+                ("sort", "downloads".to_string()),
+            ],
+        )
+        .await
+    }
+
+    /// A page of the crates listing with the given query parameters, like `sort` and `category`.
+    pub async fn crates_page(&self, query: &[(&str, String)]) -> ApiResult<CratesPage> {
+        let key = query
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("&");
+        self.get_json(format!("crates/{}", key), &["crates"], query)
+            .await
+    }
+
+    /// A user by github login.
+    pub async fn user(&self, user_name: &str) -> ApiResult<User> {
+        self.get_json::<UserResponse>(format!("users/{}", user_name), &["users", user_name], &[])
+            .await
+            .map(|response| response.user)
+    }
+
+    /// A team by login, like `github:org:team`.
+    pub async fn team(&self, login: &str) -> Result<Team> {
+        match self
+            .get_json::<TeamResponse>(format!("teams/{}", login), &["teams", login], &[])
+            .await
+        {
+            Err(err) if is_status(&err, &[StatusCode::NOT_FOUND]) => {
+                Err(Error::TeamNotFound(login.to_string()))
+            }
+            response => response
+                .map(|response| response.team)
+                .context(format!("can not get team {} from crates.io", login)),
+        }
+    }
+
+    /// Every crate owned by the team.
+    pub async fn team_crates(&self, team: &Team) -> Result<Vec<Crate>> {
+        let mut crates = Vec::new();
+        for page in 1.. {
+            let crates_page = self
+                .get_json::<CratesPage>(
+                    format!("team_crates/{}-{}", team.id, page),
+                    &["crates"],
+                    &[
+                        ("team_id", team.id.to_string()),
+                        ("page", page.to_string()),
+                        ("per_page", "100".to_string()),
+                        ("sort", "alpha".to_string()),
+                    ],
+                )
+                .await
+                .context(format!("can not get crates of team {}", team.login))?;
+            let page_len = crates_page.crates.len();
+            crates.extend(crates_page.crates);
+            if page_len < 100 || crates.len() as u64 >= crates_page.meta.total {
+                break;
+            }
+        }
+        Ok(crates)
+    }
+
+    /// The user of an api token, never cached.
+    pub async fn me(&self, token: &str) -> Result<Me> {
+        if self.offline {
+            return Err(not_cached()).context("can not get the token's user from crates.io");
+        }
+        let url = self.url(&["me"], &[]);
+        let request = || async {
+            self.throttle().await;
+            let request = self
+                .http
+                .get(url.clone())
+                .header(reqwest::header::AUTHORIZATION, token);
+            let response = self.send(request, &url).await?.error_for_status()?;
+            decode(&response.text().await?)
+        };
+        match self.retry(request).await {
+            Err(err) if is_status(&err, &[StatusCode::UNAUTHORIZED, StatusCode::FORBIDDEN]) => {
+                Err(Error::Usage("crates.io rejected the api token".to_string()))
+            }
+            me => me.context("can not get the token's user from crates.io"),
+        }
+    }
+
+    async fn get_json<T>(
+        &self,
+        key: String,
+        segments: &[&str],
+        query: &[(&str, String)],
+    ) -> ApiResult<T>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        let key = format!("{}/{}", self.cache_prefix, key);
+        if let Some(value) = self.cache.as_ref().and_then(|cache| cache.get(&key)) {
+            tracing::info!("cache hit {}", key);
+            return Ok(value);
+        }
+        if self.offline {
+            return Err(not_cached());
+        }
+        let url = self.url(segments, query);
+        let value = self
+            .retry(|| async {
+                self.throttle().await;
+                let response = self
+                    .send(self.http.get(url.clone()), &url)
+                    .await?
+                    .error_for_status()?;
+                decode(&response.text().await?)
+            })
+            .await?;
+        if let Some(cache) = &self.cache {
+            cache.put(&key, &value);
+        }
+        Ok(value)
+    }
+
+    fn url(&self, segments: &[&str], query: &[(&str, String)]) -> Url {
+        let mut url = self.base_url.clone();
+        if let Ok(mut path) = url.path_segments_mut() {
+            path.pop_if_empty().extend(segments);
+        }
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+        url
+    }
+
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+        url: &Url,
+    ) -> reqwest::Result<reqwest::Response> {
+        let started = Instant::now();
+        let response = request.send().await;
+        let elapsed = started.elapsed().as_millis();
+        match &response {
+            Ok(response) => tracing::info!(
+                "GET {} {} in {}ms",
+                url,
+                response.status().as_u16(),
+                elapsed
+            ),
+            Err(err) => tracing::info!("GET {} failed after {}ms: {}", url, elapsed, err),
+        }
+        response
+    }
+
+    async fn throttle(&self) {
+        let mut next_request = self.next_request.lock().await;
+        let wait = next_request.saturating_duration_since(Instant::now());
+        if !wait.is_zero() {
+            tracing::debug!("waiting {}ms for the request interval", wait.as_millis());
+        }
+        tokio::time::sleep_until(*next_request).await;
+        *next_request = Instant::now() + self.request_interval;
+    }
+
+    async fn retry<T, F, Fut>(&self, mut request: F) -> ApiResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = ApiResult<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            let slowdown = self.slowdown_ms.load(Ordering::Relaxed);
+            if slowdown > 0 {
+                tracing::debug!("slowing down by {}ms after rate limiting", slowdown);
+                tokio::time::sleep(Duration::from_millis(slowdown)).await;
+            }
+            let result = request().await;
+            self.adapt(
+                result
+                    .as_ref()
+                    .is_err_and(|err| is_status(err, &[StatusCode::TOO_MANY_REQUESTS])),
+            );
+            match result {
+                Err(err) if attempt < self.retries && is_transient(&err) => {
+                    let delay = backoff(attempt);
+                    tracing::info!(
+                        "retrying in {}ms after {} ({} of {})",
+                        delay.as_millis(),
+                        err,
+                        attempt + 1,
+                        self.retries
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn adapt(&self, rate_limited: bool) {
+        let _ = self
+            .slowdown_ms
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |slowdown| {
+                Some(match (rate_limited, slowdown) {
+                    (true, slowdown) => (slowdown * 2)
+                        .max(SLOWDOWN_MIN.as_millis() as u64)
+                        .min(BACKOFF_MAX.as_millis() as u64),
+                    (false, 0..=100) => 0,
+                    (false, slowdown) => slowdown * 3 / 4,
+                })
+            });
+    }
+}
+
+/// A plain http client with the user agent and proxy, for the services next to crates.io.
+pub fn http_client(user_agent: &str, proxy: Option<&str>) -> Result<reqwest::Client> {
+    let user_agent = HeaderValue::from_str(user_agent)?;
+    let mut builder = reqwest::Client::builder().user_agent(user_agent);
+    if let Some(proxy) = proxy {
+        let proxy = reqwest::Proxy::all(proxy)
+            .map_err(|_| Error::Usage(format!("invalid proxy url {}", proxy)))?;
+        builder = builder.proxy(proxy.no_proxy(reqwest::NoProxy::from_env()));
+    }
+    builder.build().context("can not build http client")
+}
+
+fn decode<T: DeserializeOwned>(body: &str) -> ApiResult<T> {
+    if let Ok(errors) = serde_json::from_str::<ApiErrors>(body) {
+        return Err(crates_io_api::Error::Api(errors));
+    }
+    serde_json::from_str(body).map_err(|err| api_error(format!("can not decode response: {}", err)))
+}
+
+/// Whether a request failed with one of the http statuses.
+pub fn is_status(err: &crates_io_api::Error, statuses: &[StatusCode]) -> bool {
+    matches!(err, crates_io_api::Error::Http(err) if err.status().is_some_and(|status| statuses.contains(&status)))
+}
+
+fn is_transient(err: &crates_io_api::Error) -> bool {
+    let crates_io_api::Error::Http(err) = err else {
+        return false;
+    };
+    match err.status() {
+        Some(status) => status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error(),
+        None => err.is_timeout() || err.is_connect(),
+    }
+}
+
+fn api_error(detail: String) -> crates_io_api::Error {
+    crates_io_api::Error::Api(ApiErrors {
+        errors: vec![ApiError {
+            detail: Some(detail),
+        }],
+    })
+}
+
+fn not_cached() -> crates_io_api::Error {
+    api_error("not in the local cache, run once without --offline".to_string())
+}
+
+fn backoff(attempt: u32) -> Duration {
+    let delay = BACKOFF_BASE
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(BACKOFF_MAX);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.subsec_nanos());
+    delay / 2 + delay.mul_f64(f64::from(nanos % 1000) / 2000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn clones_share_one_request_interval() {
+        let client = Client::new(&ClientOptions {
+            request_interval: Duration::from_millis(50),
+            ..ClientOptions::default()
+        })
+        .unwrap();
+        let started = Instant::now();
+        let jobs = (0..4)
+            .map(|_| {
+                let client = client.clone();
+                tokio::spawn(async move { client.throttle().await })
+            })
+            .collect::<Vec<_>>();
+        for job in jobs {
+            job.await.unwrap();
+        }
+        assert!(started.elapsed() >= Duration::from_millis(150));
+    }
+}
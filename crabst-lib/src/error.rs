@@ -0,0 +1,78 @@
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("crate `{0}` was not found on crates.io")]
+    CrateNotFound(String),
+    #[error("user `{0}` was not found on crates.io")]
+    UserNotFound(String),
+    #[error("team `{0}` was not found on crates.io")]
+    TeamNotFound(String),
+    #[error("{context}: {source}")]
+    Api {
+        context: String,
+        #[source]
+        source: crates_io_api::Error,
+    },
+    #[error("{context}: {source}")]
+    Http {
+        context: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("invalid user agent: {0}")]
+    UserAgent(#[from] reqwest::header::InvalidHeaderValue),
+    #[error("{0}")]
+    Usage(String),
+}
+
+/// Wraps lower level errors with what was being done.
+pub trait Context<T> {
+    fn context(self, context: impl Into<String>) -> Result<T>;
+}
+
+impl<T> Context<T> for std::result::Result<T, crates_io_api::Error> {
+    fn context(self, context: impl Into<String>) -> Result<T> {
+        self.map_err(|source| Error::Api {
+            context: context.into(),
+            source,
+        })
+    }
+}
+
+impl<T> Context<T> for std::result::Result<T, reqwest::Error> {
+    fn context(self, context: impl Into<String>) -> Result<T> {
+        self.map_err(|source| Error::Http {
+            context: context.into(),
+            source,
+        })
+    }
+}
+
+fn is_not_found(source: &crates_io_api::Error) -> bool {
+    matches!(source, crates_io_api::Error::NotFound(_))
+        || crate::client::is_status(source, &[reqwest::StatusCode::NOT_FOUND])
+        || matches!(source, crates_io_api::Error::Api(errors) if errors.errors.iter().any(|error| error.detail.as_deref() == Some("Not Found")))
+}
+
+/// Maps a failed crate request to [`Error::CrateNotFound`] when crates.io has no such crate.
+pub fn crate_not_found(crate_name: &str) -> impl Fn(crates_io_api::Error) -> Error + '_ {
+    move |source| match source {
+        source if is_not_found(&source) => Error::CrateNotFound(crate_name.to_string()),
+        source => Error::Api {
+            context: format!("can not get crate {} from crates.io", crate_name),
+            source,
+        },
+    }
+}
+
+/// Maps a failed user request to [`Error::UserNotFound`] when crates.io has no such user.
+pub fn user_not_found(user_name: &str) -> impl Fn(crates_io_api::Error) -> Error + '_ {
+    move |source| match source {
+        source if is_not_found(&source) => Error::UserNotFound(user_name.to_string()),
+        source => Error::Api {
+            context: format!("can not get user {} from crates.io", user_name),
+            source,
+        },
+    }
+}
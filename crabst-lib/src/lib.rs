@@ -0,0 +1,36 @@
+//! The crates.io client and download statistics behind the `crabst` cli.
+//!
+//! [`Client`] fetches crates, downloads, owners and dependents from crates.io
+//! or a compatible registry, throttled, retried and optionally cached. [`stats`]
+//! turns the raw downloads into daily counts, trends and anomalies, and
+//! [`render`] prints rows as tables, csv, markdown or ndjson. The statistics
+//! take any [`StatsProvider`], [`MemoryProvider`] answers from memory.
+//!
+//! ```no_run
+//! use crabst_lib::stats::{daily_downloads, trend};
+//! use crabst_lib::{Client, ClientOptions};
+//!
+//! # async fn run() -> crabst_lib::Result<()> {
+//! let client = Client::new(&ClientOptions::default())?;
+//! let downloads = client
+//!     .crate_downloads("serde")
+//!     .await
+//!     .map_err(crabst_lib::error::crate_not_found("serde"))?;
+//! let daily = daily_downloads(&downloads).into_values().collect::<Vec<_>>();
+//! let (previous, current) = daily.split_at(daily.len() / 2);
+//! println!("{}", trend(current.iter().sum(), previous.iter().sum()));
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod cache;
+pub mod client;
+pub mod error;
+pub mod provider;
+pub mod render;
+pub mod semver;
+pub mod stats;
+
+pub use client::{Client, ClientOptions};
+pub use error::{Error, Result};
+pub use provider::{MemoryProvider, StatsProvider};
@@ -0,0 +1,300 @@
+use crate::client::{ApiResult, Client};
+use chrono::NaiveDate;
+use crates_io_api::{
+    ApiError, ApiErrors, Crate, CrateDownloads, CrateDownloadsMeta, CrateResponse, CratesPage,
+    Dependency, Meta, ReverseDependencies, ReverseDependency, User, VersionDownloads,
+};
+use std::collections::HashMap;
+use std::future::Future;
+
+/// The crates.io calls download statistics are built from, so they can be
+/// computed from another registry or from memory.
+pub trait StatsProvider: Sync {
+    /// The crate with its versions, keywords and categories.
+    fn get_crate(&self, crate_name: &str) -> impl Future<Output = ApiResult<CrateResponse>> + Send;
+
+    /// Daily downloads of the last 90 days.
+    fn crate_downloads(
+        &self,
+        crate_name: &str,
+    ) -> impl Future<Output = ApiResult<CrateDownloads>> + Send;
+
+    /// User and team owners of the crate.
+    fn crate_owners(&self, crate_name: &str) -> impl Future<Output = ApiResult<Vec<User>>> + Send;
+
+    /// A page of 100 crates depending on the crate, pages start at 1.
+    fn crate_reverse_dependencies_page(
+        &self,
+        crate_name: &str,
+        page: u64,
+    ) -> impl Future<Output = ApiResult<ReverseDependencies>> + Send;
+
+    /// Dependencies of a published version of the crate.
+    fn crate_dependencies(
+        &self,
+        crate_name: &str,
+        version: &str,
+    ) -> impl Future<Output = ApiResult<Vec<Dependency>>> + Send;
+
+    /// The user with the login.
+    fn user(&self, user_name: &str) -> impl Future<Output = ApiResult<User>> + Send;
+
+    /// A page of 100 crates owned by the user, sorted by name.
+    fn user_crates(
+        &self,
+        user_id: u64,
+        page: u64,
+    ) -> impl Future<Output = ApiResult<CratesPage>> + Send;
+}
+
+impl StatsProvider for Client {
+    async fn get_crate(&self, crate_name: &str) -> ApiResult<CrateResponse> {
+        Client::get_crate(self, crate_name).await
+    }
+
+    async fn crate_downloads(&self, crate_name: &str) -> ApiResult<CrateDownloads> {
+        Client::crate_downloads(self, crate_name).await
+    }
+
+    async fn crate_owners(&self, crate_name: &str) -> ApiResult<Vec<User>> {
+        Client::crate_owners(self, crate_name).await
+    }
+
+    async fn crate_reverse_dependencies_page(
+        &self,
+        crate_name: &str,
+        page: u64,
+    ) -> ApiResult<ReverseDependencies> {
+        Client::crate_reverse_dependencies_page(self, crate_name, page).await
+    }
+
+    async fn crate_dependencies(
+        &self,
+        crate_name: &str,
+        version: &str,
+    ) -> ApiResult<Vec<Dependency>> {
+        Client::crate_dependencies(self, crate_name, version).await
+    }
+
+    async fn user(&self, user_name: &str) -> ApiResult<User> {
+        Client::user(self, user_name).await
+    }
+
+    async fn user_crates(&self, user_id: u64, page: u64) -> ApiResult<CratesPage> {
+        Client::user_crates(self, user_id, page).await
+    }
+}
+
+/// Canned responses kept in memory, for tests and examples without network
+/// access. Anything not added is answered the way crates.io answers missing
+/// resources.
+#[derive(Clone, Default)]
+pub struct MemoryProvider {
+    crates: HashMap<String, CrateResponse>,
+    downloads: HashMap<String, CrateDownloads>,
+    owners: HashMap<String, Vec<User>>,
+    dependents: HashMap<String, Vec<ReverseDependency>>,
+    dependencies: HashMap<(String, String), Vec<Dependency>>,
+    users: HashMap<String, User>,
+    user_crates: HashMap<u64, Vec<Crate>>,
+}
+
+impl MemoryProvider {
+    pub fn new() -> MemoryProvider {
+        MemoryProvider::default()
+    }
+
+    pub fn with_crate(mut self, api_crate: CrateResponse) -> MemoryProvider {
+        self.crates
+            .insert(api_crate.crate_data.name.clone(), api_crate);
+        self
+    }
+
+    pub fn with_downloads(mut self, crate_name: &str, downloads: CrateDownloads) -> MemoryProvider {
+        self.downloads.insert(crate_name.to_string(), downloads);
+        self
+    }
+
+    /// Downloads of a single version on each of the days.
+    pub fn with_daily_downloads(
+        self,
+        crate_name: &str,
+        daily: &[(NaiveDate, u64)],
+    ) -> MemoryProvider {
+        let downloads = CrateDownloads {
+            version_downloads: daily
+                .iter()
+                .map(|(date, downloads)| VersionDownloads {
+                    date: *date,
+                    downloads: *downloads,
+                    version: 1,
+                })
+                .collect(),
+            meta: CrateDownloadsMeta {
+                extra_downloads: Vec::new(),
+            },
+        };
+        self.with_downloads(crate_name, downloads)
+    }
+
+    pub fn with_owners(mut self, crate_name: &str, owners: Vec<User>) -> MemoryProvider {
+        self.owners.insert(crate_name.to_string(), owners);
+        self
+    }
+
+    pub fn with_dependents(
+        mut self,
+        crate_name: &str,
+        dependents: Vec<ReverseDependency>,
+    ) -> MemoryProvider {
+        self.dependents.insert(crate_name.to_string(), dependents);
+        self
+    }
+
+    pub fn with_dependencies(
+        mut self,
+        crate_name: &str,
+        version: &str,
+        dependencies: Vec<Dependency>,
+    ) -> MemoryProvider {
+        self.dependencies
+            .insert((crate_name.to_string(), version.to_string()), dependencies);
+        self
+    }
+
+    /// The user and the crates they own.
+    pub fn with_user(mut self, user: User, crates: Vec<Crate>) -> MemoryProvider {
+        self.user_crates.insert(user.id, crates);
+        self.users.insert(user.login.clone(), user);
+        self
+    }
+}
+
+impl StatsProvider for MemoryProvider {
+    async fn get_crate(&self, crate_name: &str) -> ApiResult<CrateResponse> {
+        self.crates.get(crate_name).cloned().ok_or_else(not_found)
+    }
+
+    async fn crate_downloads(&self, crate_name: &str) -> ApiResult<CrateDownloads> {
+        self.downloads
+            .get(crate_name)
+            .cloned()
+            .ok_or_else(not_found)
+    }
+
+    async fn crate_owners(&self, crate_name: &str) -> ApiResult<Vec<User>> {
+        self.owners.get(crate_name).cloned().ok_or_else(not_found)
+    }
+
+    async fn crate_reverse_dependencies_page(
+        &self,
+        crate_name: &str,
+        page: u64,
+    ) -> ApiResult<ReverseDependencies> {
+        let dependents = self.dependents.get(crate_name).ok_or_else(not_found)?;
+        Ok(ReverseDependencies {
+            dependencies: paginate(dependents, page),
+            meta: Meta {
+                total: dependents.len() as u64,
+            },
+        })
+    }
+
+    async fn crate_dependencies(
+        &self,
+        crate_name: &str,
+        version: &str,
+    ) -> ApiResult<Vec<Dependency>> {
+        self.dependencies
+            .get(&(crate_name.to_string(), version.to_string()))
+            .cloned()
+            .ok_or_else(not_found)
+    }
+
+    async fn user(&self, user_name: &str) -> ApiResult<User> {
+        self.users.get(user_name).cloned().ok_or_else(not_found)
+    }
+
+    async fn user_crates(&self, user_id: u64, page: u64) -> ApiResult<CratesPage> {
+        let mut crates = self
+            .user_crates
+            .get(&user_id)
+            .ok_or_else(not_found)?
+            .clone();
+        crates.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(CratesPage {
+            crates: paginate(&crates, page),
+            versions: Vec::new(),
+            keywords: Vec::new(),
+            categories: Vec::new(),
+            meta: Meta {
+                total: crates.len() as u64,
+            },
+        })
+    }
+}
+
+fn paginate<T: Clone>(items: &[T], page: u64) -> Vec<T> {
+    items
+        .iter()
+        .skip((page.max(1) as usize - 1) * 100)
+        .take(100)
+        .cloned()
+        .collect()
+}
+
+// the body crates.io sends with a 404
+fn not_found() -> crates_io_api::Error {
+    crates_io_api::Error::Api(ApiErrors {
+        errors: vec![ApiError {
+            detail: Some("Not Found".to_string()),
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{crate_not_found, Error};
+
+    #[tokio::test]
+    async fn missing_crates_are_not_found() {
+        let err = MemoryProvider::new()
+            .crate_downloads("foo")
+            .await
+            .map_err(crate_not_found("foo"))
+            .unwrap_err();
+        assert!(matches!(err, Error::CrateNotFound(name) if name == "foo"));
+    }
+
+    #[tokio::test]
+    async fn reverse_dependencies_are_paged_by_100() {
+        let dependent: ReverseDependency = serde_json::from_value(serde_json::json!({
+            "crate_version": {
+                "crate": "bar", "created_at": "2024-03-01T00:00:00Z",
+                "updated_at": "2024-03-01T00:00:00Z", "dl_path": "", "downloads": 0,
+                "features": {}, "id": 1, "num": "1.0.0", "yanked": false, "license": null,
+                "readme_path": null, "links": {"dependencies": "", "version_downloads": ""},
+                "crate_size": null, "published_by": null, "rust_version": null
+            },
+            "dependency": {
+                "crate_id": "foo", "default_features": true, "downloads": 0, "features": [],
+                "id": 1, "kind": "normal", "optional": false, "req": "^1", "target": null,
+                "version_id": 1
+            }
+        }))
+        .unwrap();
+        let provider = MemoryProvider::new().with_dependents("foo", vec![dependent; 150]);
+        let first = provider
+            .crate_reverse_dependencies_page("foo", 1)
+            .await
+            .unwrap();
+        let second = provider
+            .crate_reverse_dependencies_page("foo", 2)
+            .await
+            .unwrap();
+        assert_eq!(first.dependencies.len(), 100);
+        assert_eq!(second.dependencies.len(), 50);
+        assert_eq!(second.meta.total, 150);
+    }
+}
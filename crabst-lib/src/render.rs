@@ -0,0 +1,224 @@
+use comfy_table::modifiers::UTF8_ROUND_CORNERS;
+use comfy_table::presets::UTF8_FULL;
+use comfy_table::{Cell, CellAlignment, Color, Row, Table};
+use itertools::Itertools;
+use serde_json::json;
+
+/// Casing of table headers.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum HeaderCase {
+    AsIs,
+    Title,
+    Upper,
+    Lower,
+}
+
+/// Alignment of numeric columns, auto right aligns them.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Align {
+    Auto,
+    Left,
+    Right,
+}
+
+pub fn format_header(header: &str, header_case: HeaderCase) -> String {
+    match header_case {
+        HeaderCase::Upper => header.to_uppercase(),
+        HeaderCase::Lower => header.to_lowercase(),
+        HeaderCase::Title => header
+            .split(' ')
+            .map(|word| {
+                let mut chars = word.chars();
+                match chars.next() {
+                    Some(first) => first
+                        .to_uppercase()
+                        .chain(chars.flat_map(char::to_lowercase))
+                        .collect(),
+                    None => String::new(),
+                }
+            })
+            .join(" "),
+        HeaderCase::AsIs => header.to_string(),
+    }
+}
+
+/// A rounded utf-8 table ending with a newline.
+pub fn render_table(headers: &[String], rows: &[Vec<String>], align: Align) -> String {
+    render_highlighted_table(headers, rows, align, |_| false)
+}
+
+/// A table with the rows matching `highlight` in red.
+pub fn render_highlighted_table(
+    headers: &[String],
+    rows: &[Vec<String>],
+    align: Align,
+    highlight: impl Fn(&[String]) -> bool,
+) -> String {
+    let numeric_alignment = match align {
+        Align::Auto | Align::Right => CellAlignment::Right,
+        Align::Left => CellAlignment::Left,
+    };
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .apply_modifier(UTF8_ROUND_CORNERS)
+        .set_header(headers);
+    for row in rows {
+        let highlighted = highlight(row);
+        table.add_row(Row::from(row.iter().map(|value| {
+            let mut cell = Cell::new(value);
+            if is_numeric(value) {
+                cell = cell.set_alignment(numeric_alignment);
+            }
+            if highlighted {
+                cell = cell.fg(Color::Red);
+            }
+            cell
+        })));
+    }
+    format!("{}\n", table)
+}
+
+/// Csv with crlf line ends, quoting fields where needed.
+pub fn render_csv(headers: &[String], rows: &[Vec<String>]) -> String {
+    let escape = |field: &String| {
+        if field.contains([',', '"', '\r', '\n']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.clone()
+        }
+    };
+    std::iter::once(headers)
+        .chain(rows.iter().map(|row| row.as_slice()))
+        .map(|row| row.iter().map(escape).join(",") + "\r\n")
+        .collect()
+}
+
+/// One json object per row keyed by header, a line each.
+pub fn render_ndjson(headers: &[String], rows: &[Vec<String>]) -> String {
+    row_objects(headers, rows)
+        .iter()
+        .map(|object| format!("{}\n", object))
+        .collect()
+}
+
+/// Rows as json objects keyed by header.
+pub fn row_objects(headers: &[String], rows: &[Vec<String>]) -> Vec<serde_json::Value> {
+    rows.iter()
+        .map(|row| {
+            let object = headers
+                .iter()
+                .cloned()
+                .zip(row.iter().map(|field| json!(field)))
+                .collect::<serde_json::Map<_, _>>();
+            serde_json::Value::Object(object)
+        })
+        .collect()
+}
+
+/// A github flavored markdown table, numeric columns are right aligned unless `align` is left.
+pub fn render_markdown(headers: &[String], rows: &[Vec<String>], align: Align) -> String {
+    let escape = |field: &String| field.replace('|', "\\|");
+    let alignments = (0..headers.len()).map(|column| {
+        let numeric = !rows.is_empty()
+            && rows
+                .iter()
+                .all(|row| row.get(column).is_some_and(|v| is_numeric(v)));
+        match (numeric, align) {
+            (true, Align::Auto | Align::Right) => "---:",
+            _ => "---",
+        }
+    });
+    let mut markdown = format!("| {} |\n", headers.iter().map(escape).join(" | "));
+    markdown.push_str(&format!(
+        "| {} |\n",
+        alignments.collect::<Vec<_>>().join(" | ")
+    ));
+    for row in rows {
+        markdown.push_str(&format!("| {} |\n", row.iter().map(escape).join(" | ")));
+    }
+    markdown
+}
+
+// signed digits with separators, names like nan or inf and dates are not numbers here
+fn is_numeric(value: &str) -> bool {
+    let digits = value.strip_prefix(['+', '-']).unwrap_or(value);
+    digits.chars().any(|c| c.is_ascii_digit())
+        && digits
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '.' | ','))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers() -> Vec<String> {
+        vec!["Crate".to_string(), "Downloads".to_string()]
+    }
+
+    fn rows() -> Vec<Vec<String>> {
+        vec![
+            vec!["foo".to_string(), "12".to_string()],
+            vec!["a|b, \"c\"".to_string(), "3".to_string()],
+        ]
+    }
+
+    #[test]
+    fn header_cases() {
+        assert_eq!(
+            format_header("total DOWNLOADS", HeaderCase::Title),
+            "Total Downloads"
+        );
+        assert_eq!(format_header("Crate", HeaderCase::Upper), "CRATE");
+        assert_eq!(format_header("Crate", HeaderCase::Lower), "crate");
+        assert_eq!(format_header("Crate", HeaderCase::AsIs), "Crate");
+    }
+
+    #[test]
+    fn csv_quotes_special_fields() {
+        assert_eq!(
+            render_csv(&headers(), &rows()),
+            "Crate,Downloads\r\nfoo,12\r\n\"a|b, \"\"c\"\"\",3\r\n"
+        );
+    }
+
+    #[test]
+    fn markdown_right_aligns_numeric_columns() {
+        assert_eq!(
+            render_markdown(&headers(), &rows(), Align::Auto),
+            "| Crate | Downloads |\n| --- | ---: |\n| foo | 12 |\n| a\\|b, \"c\" | 3 |\n"
+        );
+        assert!(render_markdown(&headers(), &rows(), Align::Left).contains("| --- | --- |"));
+    }
+
+    #[test]
+    fn float_names_are_not_numeric() {
+        let rows = ["nan", "inf", "infinity"]
+            .iter()
+            .map(|name| vec![name.to_string(), "1,024".to_string()])
+            .collect::<Vec<_>>();
+        assert!(render_markdown(&headers(), &rows, Align::Auto).contains("| --- | ---: |"));
+        assert!(!is_numeric("nan") && !is_numeric("-") && !is_numeric("2024-02-01"));
+        assert!(is_numeric("-1.5") && is_numeric("+12"));
+    }
+
+    #[test]
+    fn ndjson_keys_rows_by_header() {
+        assert_eq!(
+            render_ndjson(&headers(), &rows()[..1]),
+            "{\"Crate\":\"foo\",\"Downloads\":\"12\"}\n"
+        );
+    }
+
+    #[test]
+    fn table_lists_every_cell() {
+        let table = render_table(&headers(), &rows(), Align::Auto);
+        assert!(table.ends_with('\n'));
+        for cell in headers().iter().chain(rows().iter().flatten()) {
+            assert!(table.contains(cell.as_str()));
+        }
+    }
+}
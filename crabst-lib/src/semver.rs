@@ -0,0 +1,308 @@
+use std::cmp::Ordering;
+
+/// A semver version as published on crates.io, build metadata is dropped.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre: String,
+}
+
+impl Version {
+    pub fn parse(text: &str) -> Option<Version> {
+        let text = text.trim();
+        let text = text.split_once('+').map_or(text, |(version, _)| version);
+        let (core, pre) = text.split_once('-').unwrap_or((text, ""));
+        let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+        let version = Version {
+            major: parts.next()??,
+            minor: parts.next()??,
+            patch: parts.next()??,
+            pre: pre.to_string(),
+        };
+        parts.next().is_none().then_some(version)
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Version) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => compare_pre(&self.pre, &other.pre),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Version) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn compare_pre(a: &str, b: &str) -> Ordering {
+    let mut a = a.split('.');
+    let mut b = b.split('.');
+    loop {
+        let ordering = match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => match (a.parse::<u64>(), b.parse::<u64>()) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => a.cmp(b),
+            },
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Op {
+    Exact,
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Tilde,
+    Caret,
+    Any,
+}
+
+struct Comparator {
+    op: Op,
+    major: u64,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    pre: String,
+}
+
+/// A cargo version requirement such as `^1.2`, `~0.3.1` or `>=1, <3`.
+pub struct VersionReq {
+    comparators: Vec<Comparator>,
+}
+
+impl VersionReq {
+    pub fn parse(text: &str) -> Option<VersionReq> {
+        let comparators = text
+            .split(',')
+            .map(Comparator::parse)
+            .collect::<Option<Vec<_>>>()?;
+        Some(VersionReq { comparators })
+    }
+
+    pub fn matches(&self, version: &Version) -> bool {
+        let all = self
+            .comparators
+            .iter()
+            .all(|comparator| comparator.matches(version));
+        // cargo only lets pre-releases match requirements that name one of the same version
+        all && (version.pre.is_empty()
+            || self.comparators.iter().any(|comparator| {
+                !comparator.pre.is_empty()
+                    && comparator.major == version.major
+                    && comparator.minor == Some(version.minor)
+                    && comparator.patch == Some(version.patch)
+            }))
+    }
+}
+
+impl Comparator {
+    fn parse(text: &str) -> Option<Comparator> {
+        let text = text.trim();
+        let (op, rest) = [
+            (">=", Op::GreaterEq),
+            ("<=", Op::LessEq),
+            (">", Op::Greater),
+            ("<", Op::Less),
+            ("=", Op::Exact),
+            ("~", Op::Tilde),
+            ("^", Op::Caret),
+        ]
+        .into_iter()
+        .find_map(|(prefix, op)| text.strip_prefix(prefix).map(|rest| (op, rest.trim())))
+        .unwrap_or((Op::Caret, text));
+        let rest = rest.split_once('+').map_or(rest, |(version, _)| version);
+        let (core, pre) = rest.split_once('-').unwrap_or((rest, ""));
+        let mut parts = core.split('.');
+        let mut part = || match parts.next() {
+            None | Some("*" | "x" | "X") => Ok(None),
+            Some(part) => part.parse::<u64>().map(Some).map_err(|_| ()),
+        };
+        let (major, minor, patch) = (part().ok()?, part().ok()?, part().ok()?);
+        if parts.next().is_some() {
+            return None;
+        }
+        let Some(major) = major else {
+            return Some(Comparator {
+                op: Op::Any,
+                major: 0,
+                minor: None,
+                patch: None,
+                pre: String::new(),
+            });
+        };
+        let wildcard = core.contains(['*', 'x', 'X']);
+        if (minor.is_none() && patch.is_some()) || (!pre.is_empty() && patch.is_none()) {
+            return None;
+        }
+        let op = match op {
+            Op::Caret | Op::Tilde if wildcard => Op::Exact,
+            op => op,
+        };
+        Some(Comparator {
+            op,
+            major,
+            minor,
+            patch,
+            pre: pre.to_string(),
+        })
+    }
+
+    fn matches(&self, version: &Version) -> bool {
+        let lower = Version {
+            major: self.major,
+            minor: self.minor.unwrap_or(0),
+            patch: self.patch.unwrap_or(0),
+            pre: self.pre.clone(),
+        };
+        let same_major = version.major == self.major;
+        let same_minor = same_major && Some(version.minor) == self.minor;
+        match (self.op, self.minor, self.patch) {
+            (Op::Any, _, _) => true,
+            (Op::Exact, Some(_), Some(_)) => *version == lower,
+            (Op::Exact, Some(_), None) => same_minor,
+            (Op::Exact, None, _) => same_major,
+            (Op::Greater, Some(_), Some(_)) => *version > lower,
+            (Op::Greater, Some(minor), None) => {
+                version.major > self.major || (same_major && version.minor > minor)
+            }
+            (Op::Greater, None, _) => version.major > self.major,
+            (Op::GreaterEq, _, _) => *version >= lower,
+            (Op::Less, _, _) => *version < lower,
+            (Op::LessEq, Some(_), Some(_)) => *version <= lower,
+            (Op::LessEq, Some(minor), None) => {
+                version.major < self.major || (same_major && version.minor <= minor)
+            }
+            (Op::LessEq, None, _) => version.major <= self.major,
+            (Op::Tilde, Some(_), _) => same_minor && *version >= lower,
+            (Op::Tilde, None, _) => same_major,
+            (Op::Caret, Some(_), Some(patch)) => {
+                *version >= lower
+                    && match (self.major, self.minor) {
+                        (0, Some(0)) => same_minor && version.patch == patch,
+                        (0, _) => same_minor,
+                        _ => same_major,
+                    }
+            }
+            (Op::Caret, Some(_), None) if self.major == 0 => same_minor,
+            (Op::Caret, Some(_), None) => same_major && *version >= lower,
+            (Op::Caret, None, _) => same_major,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matching(req: &str, versions: &[&str]) -> Vec<String> {
+        let req = VersionReq::parse(req).unwrap();
+        versions
+            .iter()
+            .filter(|version| req.matches(&Version::parse(version).unwrap()))
+            .map(|version| version.to_string())
+            .collect()
+    }
+
+    const VERSIONS: [&str; 12] = [
+        "0.0.3", "0.0.4", "0.2.0", "0.2.5", "0.3.0", "1.0.0", "1.2.0", "1.2.9", "1.3.0", "2.0.0",
+        "2.9.9", "3.0.0",
+    ];
+
+    #[test]
+    fn caret_requirements() {
+        assert_eq!(matching("^0.0.3", &VERSIONS), ["0.0.3"]);
+        assert_eq!(matching("^0.2", &VERSIONS), ["0.2.0", "0.2.5"]);
+        assert_eq!(
+            matching("^0", &VERSIONS),
+            ["0.0.3", "0.0.4", "0.2.0", "0.2.5", "0.3.0"]
+        );
+        assert_eq!(matching("1.2", &VERSIONS), ["1.2.0", "1.2.9", "1.3.0"]);
+    }
+
+    #[test]
+    fn tilde_requirements() {
+        assert_eq!(matching("~1.2", &VERSIONS), ["1.2.0", "1.2.9"]);
+        assert_eq!(
+            matching("~1", &VERSIONS),
+            ["1.0.0", "1.2.0", "1.2.9", "1.3.0"]
+        );
+    }
+
+    #[test]
+    fn wildcards_and_ranges() {
+        assert_eq!(
+            matching("1.*", &VERSIONS),
+            ["1.0.0", "1.2.0", "1.2.9", "1.3.0"]
+        );
+        assert_eq!(matching("*", &VERSIONS).len(), VERSIONS.len());
+        assert_eq!(
+            matching(">=1, <3", &VERSIONS),
+            ["1.0.0", "1.2.0", "1.2.9", "1.3.0", "2.0.0", "2.9.9"]
+        );
+    }
+
+    #[test]
+    fn exact_pre_release() {
+        let versions = ["1.2.3-alpha", "1.2.3-beta", "1.2.3"];
+        assert_eq!(matching("=1.2.3-alpha", &versions), ["1.2.3-alpha"]);
+        assert_eq!(matching("^1.2.3-alpha", &versions), versions);
+    }
+
+    #[test]
+    fn pre_releases_do_not_match_plain_requirements() {
+        let versions = ["1.3.0-rc.1", "2.0.0-alpha", "1.3.0"];
+        assert_eq!(matching("^1", &versions), ["1.3.0"]);
+        assert_eq!(matching(">=1, <3", &versions), ["1.3.0"]);
+        assert_eq!(matching("*", &versions), ["1.3.0"]);
+    }
+
+    #[test]
+    fn pre_release_ordering() {
+        let ordered = [
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+        ];
+        let versions = ordered
+            .iter()
+            .map(|version| Version::parse(version).unwrap())
+            .collect::<Vec<_>>();
+        assert!(versions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(compare_pre("1", "alpha"), Ordering::Less);
+        assert_eq!(compare_pre("rc.1", "rc.1"), Ordering::Equal);
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert!(Version::parse("1.2").is_none());
+        assert!(Version::parse("1.2.3.4").is_none());
+        assert!(VersionReq::parse("^1.x.3").is_none());
+        assert!(VersionReq::parse("1.2-alpha").is_none());
+        assert!(VersionReq::parse("nope").is_none());
+    }
+}
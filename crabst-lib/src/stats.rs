@@ -0,0 +1,194 @@
+use crate::client::ApiResult;
+use crate::provider::StatsProvider;
+use chrono::NaiveDate;
+use crates_io_api::CrateDownloads;
+use std::collections::{BTreeMap, HashMap};
+
+/// Downloads per day summed over all versions, including the downloads crates.io
+/// only reports as a daily total for older versions.
+pub fn daily_downloads(downloads: &CrateDownloads) -> BTreeMap<NaiveDate, u64> {
+    let mut daily = BTreeMap::new();
+    for vd in &downloads.version_downloads {
+        *daily.entry(vd.date).or_insert(0) += vd.downloads;
+    }
+    for extra in &downloads.meta.extra_downloads {
+        *daily.entry(extra.date).or_insert(0) += extra.downloads;
+    }
+    daily
+}
+
+/// Downloads of a crate on each of the dates, zero for days without data.
+pub async fn get_crate_downloads_multi(
+    provider: &impl StatsProvider,
+    crate_name: &str,
+    dates: &[NaiveDate],
+) -> ApiResult<HashMap<NaiveDate, u64>> {
+    let daily = daily_downloads(&provider.crate_downloads(crate_name).await?);
+    Ok(dates
+        .iter()
+        .map(|d| (*d, daily.get(d).copied().unwrap_or(0)))
+        .collect())
+}
+
+/// Change of a window against the previous one, like `▲ 12%`, `new` when the
+/// previous window had no downloads.
+pub fn trend(current: u64, previous: u64) -> String {
+    if previous == 0 {
+        return if current == 0 { "±0%" } else { "new" }.to_string();
+    }
+    let percent = (current as f64 - previous as f64) * 100.0 / previous as f64;
+    match percent {
+        p if p > 0.0 => format!("▲ {:.0}%", p),
+        p if p < 0.0 => format!("▼ {:.0}%", -p),
+        _ => "±0%".to_string(),
+    }
+}
+
+/// Mean of each point and the ones before it, over at most `window` points.
+pub fn moving_average(counts: &[f64], window: usize) -> Vec<f64> {
+    (0..counts.len())
+        .map(|i| {
+            let points = &counts[(i + 1).saturating_sub(window)..=i];
+            points.iter().sum::<f64>() / points.len() as f64
+        })
+        .collect()
+}
+
+/// Median of values sorted in ascending order.
+pub fn median(sorted: &[f64]) -> Option<f64> {
+    match sorted.len() {
+        0 => None,
+        len if len % 2 == 0 => Some((sorted[len / 2 - 1] + sorted[len / 2]) / 2.0),
+        len => Some(sorted[len / 2]),
+    }
+}
+
+/// Points more than `sigma` standard deviations from the mean of the 14 points
+/// before them, with their score. The first week has too little history.
+/// `counts` needs a point for every day, zero on days without downloads.
+pub fn find_anomalies(counts: &[f64], sigma: f64) -> Vec<(usize, f64)> {
+    const TRAILING: usize = 14;
+    const MIN_TRAILING: usize = 7;
+    counts
+        .iter()
+        .enumerate()
+        .skip(MIN_TRAILING)
+        .filter_map(|(i, count)| {
+            let trailing = &counts[i.saturating_sub(TRAILING)..i];
+            let mean = trailing.iter().sum::<f64>() / trailing.len() as f64;
+            let variance = trailing
+                .iter()
+                .map(|value| (value - mean).powi(2))
+                .sum::<f64>()
+                / trailing.len() as f64;
+            let score = (count - mean) / variance.sqrt().max(1.0);
+            (score.abs() > sigma).then_some((i, score))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::MemoryProvider;
+    use crates_io_api::{CrateDownloadsMeta, ExtraDownloads, VersionDownloads};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    #[test]
+    fn daily_downloads_sums_versions_and_extra_downloads() {
+        let downloads = CrateDownloads {
+            version_downloads: vec![
+                VersionDownloads {
+                    date: date(1),
+                    downloads: 10,
+                    version: 1,
+                },
+                VersionDownloads {
+                    date: date(1),
+                    downloads: 5,
+                    version: 2,
+                },
+                VersionDownloads {
+                    date: date(2),
+                    downloads: 7,
+                    version: 2,
+                },
+            ],
+            meta: CrateDownloadsMeta {
+                extra_downloads: vec![ExtraDownloads {
+                    date: date(2),
+                    downloads: 3,
+                }],
+            },
+        };
+        let daily = daily_downloads(&downloads);
+        assert_eq!(daily, BTreeMap::from([(date(1), 15), (date(2), 10)]));
+    }
+
+    #[tokio::test]
+    async fn downloads_multi_fills_missing_days_with_zero() {
+        let provider = MemoryProvider::new().with_daily_downloads("foo", &[(date(1), 4)]);
+        let downloads = get_crate_downloads_multi(&provider, "foo", &[date(1), date(2)])
+            .await
+            .unwrap();
+        assert_eq!(downloads, HashMap::from([(date(1), 4), (date(2), 0)]));
+    }
+
+    #[tokio::test]
+    async fn downloads_multi_of_a_missing_crate_fails() {
+        let downloads = get_crate_downloads_multi(&MemoryProvider::new(), "foo", &[date(1)]).await;
+        assert!(downloads.is_err());
+    }
+
+    #[test]
+    fn trend_against_previous_window() {
+        assert_eq!(trend(112, 100), "▲ 12%");
+        assert_eq!(trend(50, 100), "▼ 50%");
+        assert_eq!(trend(100, 100), "±0%");
+        assert_eq!(trend(5, 0), "new");
+        assert_eq!(trend(0, 0), "±0%");
+    }
+
+    #[test]
+    fn moving_average_over_available_points() {
+        assert_eq!(
+            moving_average(&[1.0, 3.0, 5.0, 7.0], 2),
+            vec![1.0, 2.0, 4.0, 6.0]
+        );
+    }
+
+    #[test]
+    fn median_of_odd_and_even_lengths() {
+        assert_eq!(median(&[]), None);
+        assert_eq!(median(&[1.0, 2.0, 9.0]), Some(2.0));
+        assert_eq!(median(&[1.0, 2.0, 4.0, 9.0]), Some(3.0));
+    }
+
+    #[test]
+    fn anomalies_after_the_first_week() {
+        let mut counts = vec![100.0; 20];
+        counts[3] = 1000.0;
+        counts[15] = 1000.0;
+        let anomalies = find_anomalies(&counts, 3.0);
+        assert_eq!(
+            anomalies.iter().map(|(i, _)| *i).collect::<Vec<_>>(),
+            vec![15]
+        );
+        assert!(anomalies[0].1 > 0.0);
+    }
+
+    #[test]
+    fn anomalies_include_dips_to_zero() {
+        let mut counts = vec![100.0; 20];
+        counts[15] = 0.0;
+        let anomalies = find_anomalies(&counts, 3.0);
+        assert_eq!(
+            anomalies.iter().map(|(i, _)| *i).collect::<Vec<_>>(),
+            vec![15]
+        );
+        assert!(anomalies[0].1 < 0.0);
+    }
+}
@@ -0,0 +1,195 @@
+use crate::api::http_client;
+use crate::cache::Cache;
+use crate::config;
+use crate::error::{Context, Error, Result};
+use crate::semver::Version;
+use chrono::{DateTime, Utc};
+use reqwest::header::CONTENT_TYPE;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+pub const OSV_URL: &str = "https://api.osv.dev/v1";
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Advisory {
+    pub id: String,
+    pub aliases: Vec<String>,
+    pub summary: String,
+    pub kind: String,
+    pub published: Option<DateTime<Utc>>,
+    pub withdrawn: Option<DateTime<Utc>>,
+    pub patched: Vec<String>,
+    pub url: Option<String>,
+    ranges: Vec<Vec<Event>>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Event {
+    Introduced(String),
+    Fixed(String),
+    LastAffected(String),
+}
+
+impl Advisory {
+    pub fn affects(&self, version: &Version) -> bool {
+        self.withdrawn.is_none()
+            && self.ranges.iter().any(|events| {
+                // events are ordered by version, so the last one passed decides
+                events.iter().fold(false, |affected, event| match event {
+                    Event::Introduced(introduced) => {
+                        affected || Version::parse(introduced).is_none_or(|at| *version >= at)
+                    }
+                    Event::Fixed(fixed) => {
+                        affected && Version::parse(fixed).is_some_and(|at| *version < at)
+                    }
+                    Event::LastAffected(last) => {
+                        affected && Version::parse(last).is_some_and(|at| *version <= at)
+                    }
+                })
+            })
+    }
+}
+
+/// Advisories of the RustSec database, served through the OSV api.
+#[derive(Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    cache: Option<Cache>,
+    offline: bool,
+}
+
+impl Client {
+    pub fn new() -> Result<Client> {
+        Ok(Client {
+            http: http_client()?,
+            base_url: config::osv_url(),
+            cache: config::cache(),
+            offline: config::offline(),
+        })
+    }
+
+    pub async fn advisories(&self, crate_name: &str) -> Result<Vec<Advisory>> {
+        let key = format!("osv/{}", crate_name);
+        if let Some(advisories) = self.cache.as_ref().and_then(|cache| cache.get(&key)) {
+            tracing::info!("cache hit {}", key);
+            return Ok(advisories);
+        }
+        let context = || format!("can not get advisories of crate {}", crate_name);
+        if self.offline {
+            return Err(Error::Usage(format!("{}: not cached", context())));
+        }
+        let query = json!({"package": {"name": crate_name, "ecosystem": "crates.io"}});
+        tracing::info!("POST {}/query for {}", self.base_url, crate_name);
+        let body = self
+            .http
+            .post(format!("{}/query", self.base_url.trim_end_matches('/')))
+            .header(CONTENT_TYPE, "application/json")
+            .body(query.to_string())
+            .send()
+            .await
+            .context(context())?
+            .error_for_status()
+            .context(context())?
+            .text()
+            .await
+            .context(context())?;
+        let response: Value = serde_json::from_str(&body).context(context())?;
+        let advisories = response["vulns"]
+            .as_array()
+            .map(|vulns| {
+                vulns
+                    .iter()
+                    .map(|vuln| parse_advisory(crate_name, vuln))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        if let Some(cache) = &self.cache {
+            cache.put(&key, &advisories);
+        }
+        Ok(advisories)
+    }
+}
+
+fn parse_advisory(crate_name: &str, vuln: &Value) -> Advisory {
+    let date = |value: &Value| {
+        value
+            .as_str()
+            .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+            .map(|date| date.with_timezone(&Utc))
+    };
+    let strings = |value: &Value| {
+        value
+            .as_array()
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|value| value.as_str().map(str::to_string))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    };
+    let affected = vuln["affected"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter(|affected| {
+            affected["package"]["ecosystem"] == "crates.io"
+                && affected["package"]["name"] == crate_name
+        })
+        .collect::<Vec<_>>();
+    let ranges = affected
+        .iter()
+        .flat_map(|affected| affected["ranges"].as_array().cloned().unwrap_or_default())
+        .map(|range| {
+            range["events"]
+                .as_array()
+                .map(Vec::as_slice)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|event| {
+                    let version = |key: &str| event[key].as_str().map(str::to_string);
+                    version("introduced")
+                        .map(Event::Introduced)
+                        .or_else(|| version("fixed").map(Event::Fixed))
+                        .or_else(|| version("last_affected").map(Event::LastAffected))
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let patched = ranges
+        .iter()
+        .flatten()
+        .filter_map(|event| match event {
+            Event::Fixed(version) => Some(version.clone()),
+            _ => None,
+        })
+        .collect();
+    let kind = affected
+        .iter()
+        .find_map(|affected| affected["database_specific"]["informational"].as_str())
+        .unwrap_or("vulnerability")
+        .to_string();
+    let url = vuln["references"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .find(|reference| reference["type"] == "ADVISORY")
+        .or_else(|| vuln["references"].get(0))
+        .and_then(|reference| reference["url"].as_str())
+        .map(str::to_string);
+    Advisory {
+        id: vuln["id"].as_str().unwrap_or_default().to_string(),
+        aliases: strings(&vuln["aliases"]),
+        summary: vuln["summary"].as_str().unwrap_or_default().to_string(),
+        kind,
+        published: date(&vuln["published"]),
+        withdrawn: date(&vuln["withdrawn"]),
+        patched,
+        url,
+        ranges,
+    }
+}
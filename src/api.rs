@@ -0,0 +1,25 @@
+use crate::config;
+use crate::error::Result;
+use crabst_lib::client::ClientOptions;
+pub use crabst_lib::client::{Client, API_URL, USER_AGENT};
+
+/// A crates.io client set up from the config file and network flags.
+pub fn client() -> Result<Client> {
+    let options = ClientOptions {
+        registry_url: config::registry_url(),
+        user_agent: config::user_agent(),
+        proxy: config::proxy(),
+        request_interval: config::request_interval(),
+        retries: config::retries(),
+        cache: config::cache(),
+        offline: config::offline(),
+    };
+    Ok(Client::new(&options)?)
+}
+
+pub fn http_client() -> Result<reqwest::Client> {
+    Ok(crabst_lib::client::http_client(
+        &config::user_agent(),
+        config::proxy().as_deref(),
+    )?)
+}
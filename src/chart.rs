@@ -0,0 +1,141 @@
+use crate::escape_xml;
+use std::fmt::{Display, Write};
+
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 400.0;
+const LEFT: f64 = 64.0;
+const RIGHT: f64 = 24.0;
+const TOP: f64 = 48.0;
+const BOTTOM: f64 = 48.0;
+const X_LABELS: usize = 6;
+const COLORS: [&str; 8] = [
+    "#dea584", "#4e79a7", "#59a14f", "#e15759", "#b07aa1", "#76b7b2", "#edc948", "#ff9da7",
+];
+
+/// Plots the series against the x axis labels, dates or group-by buckets.
+pub fn render(labels: &[impl Display], series: &[(&str, &[f64])], title: &str) -> String {
+    let plot_width = WIDTH - LEFT - RIGHT;
+    let plot_height = HEIGHT - TOP - BOTTOM;
+    let max_count = series
+        .iter()
+        .flat_map(|(_, counts)| counts.iter().copied())
+        .fold(0.0, f64::max);
+    let step = tick_step(max_count);
+    let y_max = ((max_count / step).ceil() * step).max(step);
+    let x = |i: usize| match labels.len() {
+        0 | 1 => LEFT + plot_width / 2.0,
+        n => LEFT + i as f64 * plot_width / (n - 1) as f64,
+    };
+    let y = |count: f64| TOP + plot_height - count / y_max * plot_height;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\">",
+        w = WIDTH,
+        h = HEIGHT
+    );
+    let _ = writeln!(
+        svg,
+        "  <rect width=\"{}\" height=\"{}\" fill=\"#fff\"/>",
+        WIDTH, HEIGHT
+    );
+    let _ = writeln!(
+        svg,
+        "  <text x=\"{}\" y=\"24\" font-size=\"16\" text-anchor=\"middle\">{}</text>",
+        WIDTH / 2.0,
+        escape_xml(title)
+    );
+
+    let mut tick = 0.0;
+    while tick <= y_max {
+        let _ = writeln!(
+            svg,
+            "  <line x1=\"{l}\" y1=\"{y:.1}\" x2=\"{r}\" y2=\"{y:.1}\" stroke=\"{c}\"/>",
+            l = LEFT,
+            r = WIDTH - RIGHT,
+            y = y(tick),
+            c = if tick == 0.0 { "#999" } else { "#eee" }
+        );
+        let _ = writeln!(
+            svg,
+            "  <text x=\"{}\" y=\"{:.1}\" font-size=\"11\" text-anchor=\"end\">{}</text>",
+            LEFT - 6.0,
+            y(tick) + 4.0,
+            short_number(tick)
+        );
+        tick += step;
+    }
+
+    let label_count = X_LABELS.min(labels.len());
+    let mut label_indexes = (0..label_count)
+        .map(|i| i * (labels.len() - 1) / (label_count - 1).max(1))
+        .collect::<Vec<_>>();
+    label_indexes.dedup();
+    for i in label_indexes {
+        let _ = writeln!(
+            svg,
+            "  <text x=\"{:.1}\" y=\"{}\" font-size=\"11\" text-anchor=\"middle\">{}</text>",
+            x(i),
+            HEIGHT - BOTTOM + 18.0,
+            escape_xml(&labels[i].to_string())
+        );
+    }
+
+    for (index, (name, counts)) in series.iter().enumerate() {
+        let color = COLORS[index % COLORS.len()];
+        let points = counts
+            .iter()
+            .enumerate()
+            .map(|(i, count)| format!("{:.1},{:.1}", x(i), y(*count)))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let _ = writeln!(
+            svg,
+            "  <polyline fill=\"none\" stroke=\"{}\" stroke-width=\"2\" points=\"{}\"/>",
+            color, points
+        );
+        if series.len() > 1 {
+            let legend_x = LEFT + index as f64 * 120.0;
+            let _ = writeln!(
+                svg,
+                "  <rect x=\"{:.1}\" y=\"{}\" width=\"12\" height=\"12\" fill=\"{}\"/>",
+                legend_x,
+                HEIGHT - 20.0,
+                color
+            );
+            let _ = writeln!(
+                svg,
+                "  <text x=\"{:.1}\" y=\"{}\" font-size=\"11\">{}</text>",
+                legend_x + 16.0,
+                HEIGHT - 10.0,
+                escape_xml(name)
+            );
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn tick_step(max_count: f64) -> f64 {
+    if max_count <= 5.0 {
+        return 1.0;
+    }
+    let raw = max_count / 5.0;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let step = match raw / magnitude {
+        n if n <= 1.0 => 1.0,
+        n if n <= 2.0 => 2.0,
+        n if n <= 5.0 => 5.0,
+        _ => 10.0,
+    };
+    step * magnitude
+}
+
+fn short_number(value: f64) -> String {
+    match value {
+        v if v >= 1_000_000.0 => format!("{}M", v / 1_000_000.0),
+        v if v >= 1_000.0 => format!("{}k", v / 1_000.0),
+        v => format!("{}", v),
+    }
+}
@@ -0,0 +1,937 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+pub use crabst_lib::render::{Align, HeaderCase};
+use std::path::PathBuf;
+
+/// crate download statistics on your cli
+#[derive(Parser)]
+#[command(name = "crabst", version, arg_required_else_help = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+
+    #[command(flatten)]
+    pub output: OutputArgs,
+
+    #[command(flatten)]
+    pub network: NetworkArgs,
+
+    #[command(flatten)]
+    pub logging: LoggingArgs,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// get crate download statistics, several crates are compared side by side
+    #[command(short_flag = 'c')]
+    Crate(CrateArgs),
+    /// get user download statistics
+    User(UserArgs),
+    /// get download statistics of the crates owned by the api token's user and their teams
+    Me(MeArgs),
+    /// get download statistics of a watchlist from the config file
+    List(ListArgs),
+    /// get download statistics of every publishable crate in a cargo workspace
+    Project(ProjectArgs),
+    /// get statistics of the dependencies of a cargo project
+    Deps(DepsArgs),
+    /// get crate dependents information
+    Dependents(DependentsArgs),
+    /// list user and team owners of a crate
+    Owners(OwnersArgs),
+    /// store today's crate numbers in the local snapshot database
+    Snapshot(SnapshotArgs),
+    /// compare two stored snapshots of a crate
+    Diff(DiffArgs),
+    /// plot long-term crate downloads from the local snapshot store
+    History(HistoryArgs),
+    /// import download history from a crates.io database dump
+    ImportDump(ImportDumpArgs),
+    /// browse a user's crates, downloads and dependents interactively
+    Tui(TuiArgs),
+    /// serve crate download metrics for prometheus
+    Serve(ServeArgs),
+    /// push crate download metrics to statsd, graphite or a prometheus pushgateway once
+    Push(PushArgs),
+    /// post configured download alerts to a webhook
+    Notify(NotifyArgs),
+    /// post a download digest of crates to slack or discord
+    Digest(DigestArgs),
+    /// run the config file's scheduled commands and optionally serve metrics
+    Daemon(DaemonArgs),
+    /// write shields.io endpoint badge json for a crate
+    Badge(BadgeArgs),
+    /// average downloads of a crate per weekday over the last 90 days
+    Pattern(PatternArgs),
+    /// list the published versions of a crate with dates, downloads, msrv and size
+    Versions(VersionsArgs),
+    /// release cadence of crates: days between releases and releases per year
+    Cadence(CadenceArgs),
+    /// share of a version in the daily downloads of its crate since it was published
+    Adoption(AdoptionArgs),
+    /// search crates.io with downloads, last update and dependents of each result
+    Search(SearchArgs),
+    /// most downloaded crates of a category or keyword
+    Top(TopArgs),
+    /// crates with the fastest growing downloads
+    Trending(TrendingArgs),
+    /// most recently published crates
+    New(FeedArgs),
+    /// most recently updated crates
+    Updated(FeedArgs),
+    /// daily downloads of a crate against the median and mean of its categories
+    Benchmark(BenchmarkArgs),
+    /// check whether crate names are free, taken or held by a placeholder crate
+    CheckName(CheckNameArgs),
+    /// registered misspellings of a crate name with their downloads and owners
+    Typosquat(TyposquatArgs),
+    /// rustsec advisories of a crate and whether they affect its latest version
+    Advisories(AdvisoriesArgs),
+    /// crates related to a crate, by shared keywords and categories or from lib.rs
+    Related(RelatedArgs),
+    /// score a crate on downloads trend, releases, docs, advisories, dependents and repository
+    Health(HealthArgs),
+    /// licenses in the dependency tree of a crate, flagging copyleft and unknown ones
+    Licenses(LicensesArgs),
+    /// crates resolved to several incompatible versions in the dependency tree of a crate
+    Duplicates(DuplicatesArgs),
+    /// report crates read from stdin, one name per line or as csv or json, in one table
+    #[command(long_flag = "stdin")]
+    Stdin(StdinArgs),
+}
+
+#[derive(Args)]
+#[command(next_help_heading = "Output")]
+pub struct OutputArgs {
+    /// output format
+    #[arg(short, long, global = true, value_enum, default_value_t = OutputFormat::Table)]
+    pub output: OutputFormat,
+
+    /// write output to the given file
+    #[arg(long, global = true, value_name = "FILE")]
+    pub out_file: Option<PathBuf>,
+
+    /// append a timestamped record of the printed results to an ndjson file
+    #[arg(long, global = true, value_name = "FILE")]
+    pub append: Option<PathBuf>,
+
+    /// also render the downloads as an svg chart into the given file
+    #[arg(long, global = true, value_name = "FILE", value_parser = svg_path)]
+    pub chart_file: Option<PathBuf>,
+
+    /// table header casing
+    #[arg(long, global = true, value_enum, default_value_t = HeaderCase::AsIs)]
+    pub header_case: HeaderCase,
+
+    /// numeric column alignment
+    #[arg(long, global = true, value_enum, default_value_t = Align::Auto)]
+    pub align: Align,
+
+    /// re-run and redraw every n minutes, 5 when no interval is given
+    #[arg(
+        long,
+        global = true,
+        value_name = "MINUTES",
+        num_args = 0..=1,
+        default_missing_value = "5",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub watch: Option<u64>,
+}
+
+fn svg_path(path: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(path);
+    match path.extension() {
+        Some(extension) if extension.eq_ignore_ascii_case("svg") => Ok(path),
+        _ => Err("chart files are written as svg, use a .svg file name".to_string()),
+    }
+}
+
+#[derive(Args)]
+#[command(next_help_heading = "Logging")]
+pub struct LoggingArgs {
+    /// log api requests, cache hits and retries to stderr, -vv adds throttling
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+    /// print only results and errors, without warnings or progress
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+}
+
+#[derive(Args)]
+#[command(next_help_heading = "Network")]
+pub struct NetworkArgs {
+    /// parallel crates.io requests, 3 by default and at most 16
+    #[arg(
+        short,
+        long,
+        global = true,
+        value_name = "N",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..=crate::config::MAX_JOBS as u64)
+    )]
+    pub jobs: Option<usize>,
+
+    /// base url of a crates.io compatible registry api
+    #[arg(long, global = true, value_name = "URL")]
+    pub registry_url: Option<String>,
+
+    /// proxy for all requests, HTTP_PROXY, HTTPS_PROXY and NO_PROXY are used otherwise
+    #[arg(long, global = true, value_name = "URL")]
+    pub proxy: Option<String>,
+
+    /// user agent sent to crates.io, e.g. "crabst (you@example.com)"
+    #[arg(long, global = true, value_name = "AGENT")]
+    pub user_agent: Option<String>,
+
+    /// retry rate limited and failed crates.io requests n times, 3 by default
+    #[arg(long, global = true, value_name = "N")]
+    pub retries: Option<u32>,
+
+    /// reuse cached crates.io responses younger than n minutes, 60 by default
+    #[arg(long, global = true, value_name = "MINUTES")]
+    pub cache_ttl: Option<u64>,
+
+    /// always fetch fresh data from crates.io
+    #[arg(long, global = true)]
+    pub no_cache: bool,
+
+    /// never contact crates.io, render cached responses of any age
+    #[arg(long, global = true, conflicts_with = "no_cache")]
+    pub offline: bool,
+
+    /// where rankings and related crates come from
+    #[arg(long, global = true, value_enum, default_value_t = DataSource::CratesIo)]
+    pub source: DataSource,
+}
+
+#[derive(Args)]
+pub struct CrateArgs {
+    /// crate names to compare, comma separated or repeated, - reads names from stdin
+    #[arg(required = true, value_delimiter = ',')]
+    pub names: Vec<String>,
+
+    /// print the bare latest count instead of a table
+    #[arg(long, requires = "latest_only")]
+    pub porcelain: bool,
+
+    /// highlight the most downloaded day of the crate
+    #[arg(long)]
+    pub peak_day: bool,
+
+    /// group downloads by period
+    #[arg(long, visible_alias = "granularity", value_enum, value_name = "PERIOD")]
+    pub group_by: Option<Period>,
+
+    /// overlay a moving average over n points on graphs
+    #[arg(long, value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(2..))]
+    pub smooth: Option<usize>,
+
+    /// flag downloads more than n standard deviations from the trailing mean, 3 when no n is given
+    #[arg(long, value_name = "SIGMA", num_args = 0..=1, default_missing_value = "3")]
+    pub flag_anomalies: Option<f64>,
+
+    /// extend the daily downloads with a forecast of the next n days
+    #[arg(long, value_name = "DAYS", value_parser = clap::value_parser!(u32).range(1..=90))]
+    pub forecast: Option<u32>,
+
+    /// break downloads down per published version, with day columns for bounded windows
+    #[arg(long)]
+    pub by_version: bool,
+
+    /// list the dependencies of the latest version with their download counts
+    #[arg(long)]
+    pub dependencies: bool,
+
+    /// with --dependencies, flag crates without a release in n years or with an archived repository, 2 when no n is given
+    #[arg(long, value_name = "YEARS", num_args = 0..=1, default_missing_value = "2", requires = "dependencies")]
+    pub unmaintained: Option<u32>,
+
+    /// print crate metadata such as license, links and keywords before the downloads
+    #[arg(long)]
+    pub info: bool,
+
+    /// print the position of the crate in the downloads leaderboard, overall and per category
+    #[arg(long)]
+    pub rank: bool,
+
+    /// print the docs.rs build status of the latest version
+    #[arg(long)]
+    pub docs: bool,
+
+    /// print stars, open issues and last commit of the crate's github repository
+    #[arg(long)]
+    pub github: bool,
+
+    /// print the rustsec advisories affecting the latest version
+    #[arg(long)]
+    pub advisories: bool,
+
+    #[command(flatten)]
+    pub window: WindowArgs,
+
+    #[command(flatten)]
+    pub rows: RowArgs,
+
+    #[command(flatten)]
+    pub owners: OwnerArgs,
+}
+
+#[derive(Args)]
+pub struct UserArgs {
+    /// crates.io user logins or github:org:team, comma separated or repeated, - reads logins from stdin
+    #[arg(required = true, value_delimiter = ',')]
+    pub names: Vec<String>,
+
+    /// rank the crates depending on the user's crates instead of reporting downloads
+    #[arg(long)]
+    pub dependents: bool,
+
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+#[derive(Args)]
+pub struct MeArgs {
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+#[derive(Args)]
+pub struct ListArgs {
+    /// watchlist name from the config file
+    pub name: String,
+
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+#[derive(Args)]
+pub struct ProjectArgs {
+    /// workspace directory or Cargo.toml
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+#[derive(Args)]
+pub struct DepsArgs {
+    /// project directory or Cargo.toml
+    #[arg(default_value = ".")]
+    pub path: PathBuf,
+
+    /// include transitive dependencies from Cargo.lock
+    #[arg(long)]
+    pub all: bool,
+
+    /// add the rustsec advisories affecting the locked versions
+    #[arg(long)]
+    pub advisories: bool,
+
+    /// flag dependencies without a release in n years or with an archived repository, 2 when no n is given
+    #[arg(long, value_name = "YEARS", num_args = 0..=1, default_missing_value = "2")]
+    pub unmaintained: Option<u32>,
+
+    #[command(flatten)]
+    pub rows: RowArgs,
+}
+
+#[derive(Args)]
+pub struct ReportArgs {
+    /// print bare numbers instead of tables
+    #[arg(long)]
+    pub porcelain: bool,
+
+    /// compare each crate's window with the previous one of the same length
+    #[arg(long)]
+    pub trend: bool,
+
+    /// overlay a moving average over n points on graphs
+    #[arg(long, value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(2..))]
+    pub smooth: Option<usize>,
+
+    /// add the docs.rs build status of each crate's latest version
+    #[arg(long)]
+    pub docs: bool,
+
+    /// add stars, open issues and last commit of each crate's github repository
+    #[arg(long)]
+    pub github: bool,
+
+    /// add the rustsec advisories affecting each crate's latest version
+    #[arg(long)]
+    pub advisories: bool,
+
+    /// exit with an error when the downloads of any crate can not be fetched
+    #[arg(long)]
+    pub strict: bool,
+
+    #[command(flatten)]
+    pub window: WindowArgs,
+
+    #[command(flatten)]
+    pub rows: RowArgs,
+
+    #[command(flatten)]
+    pub owners: OwnerArgs,
+}
+
+#[derive(Args)]
+#[command(next_help_heading = "Date window")]
+pub struct WindowArgs {
+    /// show last n days output, user mode defaults to 1
+    #[arg(short, long, value_name = "LAST", value_parser = clap::value_parser!(u32).range(1..))]
+    pub last: Option<u32>,
+
+    /// first day of the reporting period, e.g. 2024-01-01
+    #[arg(long, value_name = "DATE")]
+    pub from: Option<NaiveDate>,
+
+    /// last day of the reporting period, e.g. 2024-01-31
+    #[arg(long, value_name = "DATE")]
+    pub to: Option<NaiveDate>,
+
+    /// show only the most recent day's download count
+    #[arg(long)]
+    pub latest_only: bool,
+
+    /// skip today's incomplete download count
+    #[arg(long)]
+    pub exclude_today: bool,
+}
+
+impl WindowArgs {
+    pub fn is_bounded(&self) -> bool {
+        self.last.is_some() || self.from.is_some() || self.to.is_some()
+    }
+}
+
+#[derive(Args)]
+pub struct DependentsArgs {
+    /// crate name, - reads names from stdin
+    #[arg(required_unless_present = "compare")]
+    pub name: Option<String>,
+
+    /// compare the dependents of two crates: shared ones and those of either crate alone
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["CRATE", "OTHER"],
+        conflicts_with_all = ["name", "yanked", "behind", "impact", "group_by", "changes"]
+    )]
+    pub compare: Vec<String>,
+
+    /// walk reverse dependencies n levels deep and print a tree
+    #[arg(long, default_value_t = 1, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub depth: u32,
+
+    /// stop walking the tree after this many crates
+    #[arg(long, default_value_t = 200, value_name = "N")]
+    pub max_nodes: usize,
+
+    /// list only dependents whose requirement is satisfied by yanked versions alone
+    #[arg(long)]
+    pub yanked: bool,
+
+    /// list only dependents whose requirement does not match this version, or latest
+    #[arg(long, value_name = "VERSION")]
+    pub behind: Option<String>,
+
+    /// summarize total dependent downloads, the top 10 share and their concentration
+    #[arg(long)]
+    pub impact: bool,
+
+    /// group dependents by their owners
+    #[arg(long, value_enum, value_name = "GROUP")]
+    pub group_by: Option<DependentsGroup>,
+
+    /// list dependents gained and lost since the previous --changes run
+    #[arg(long)]
+    pub changes: bool,
+
+    #[command(flatten)]
+    pub store: StoreArgs,
+
+    #[command(flatten)]
+    pub rows: RowArgs,
+}
+
+#[derive(Args)]
+pub struct TuiArgs {
+    /// crates.io user login
+    pub name: String,
+
+    /// reload data every n minutes
+    #[arg(long, default_value_t = 5, value_name = "MINUTES", value_parser = clap::value_parser!(u64).range(1..))]
+    pub refresh: u64,
+}
+
+#[derive(Args)]
+pub struct ServeArgs {
+    /// address to serve prometheus metrics on, e.g. :9184
+    #[arg(long, value_name = "ADDR")]
+    pub prometheus: String,
+
+    /// refresh metrics every n minutes
+    #[arg(long, default_value_t = 15, value_name = "MINUTES", value_parser = clap::value_parser!(u64).range(1..))]
+    pub interval: u64,
+
+    #[command(flatten)]
+    pub crates: CrateListArgs,
+}
+
+#[derive(Args)]
+pub struct PushArgs {
+    /// statsd://host:port, graphite://host:port or the http(s) url of a pushgateway, repeatable
+    #[arg(long, required = true, value_name = "URL")]
+    pub target: Vec<String>,
+
+    #[command(flatten)]
+    pub crates: CrateListArgs,
+}
+
+#[derive(Args)]
+pub struct NotifyArgs {
+    /// url to post alerts to, overrides the webhook config key
+    #[arg(long, value_name = "URL")]
+    pub webhook: Option<String>,
+
+    /// file used to remember sent alerts between runs
+    #[arg(long, default_value = "alerts.json", value_name = "STATE_FILE")]
+    pub state_file: PathBuf,
+}
+
+#[derive(Args)]
+pub struct DigestArgs {
+    /// slack incoming webhook url to post the digest to
+    #[arg(long, value_name = "URL")]
+    pub slack_webhook: Option<String>,
+
+    /// discord webhook url to post the digest to
+    #[arg(long, value_name = "URL")]
+    pub discord_webhook: Option<String>,
+
+    /// compare the last n days with the n days before
+    #[arg(long, default_value_t = 7, value_name = "DAYS", value_parser = clap::value_parser!(u32).range(1..=45))]
+    pub days: u32,
+
+    /// number of top movers to list
+    #[arg(long, default_value_t = 3, value_name = "N")]
+    pub top: usize,
+
+    #[command(flatten)]
+    pub crates: CrateListArgs,
+
+    #[command(flatten)]
+    pub store: StoreArgs,
+}
+
+#[derive(Args)]
+pub struct DaemonArgs {
+    /// also serve prometheus metrics of the given crates on this address
+    #[arg(long, value_name = "ADDR")]
+    pub prometheus: Option<String>,
+
+    /// refresh served metrics every n minutes
+    #[arg(long, default_value_t = 15, value_name = "MINUTES", value_parser = clap::value_parser!(u64).range(1..))]
+    pub interval: u64,
+
+    #[command(flatten)]
+    pub crates: CrateListArgs,
+}
+
+#[derive(Args)]
+pub struct BadgeArgs {
+    /// crate name
+    pub name: String,
+
+    /// downloads to show on the badge
+    #[arg(long, value_enum, default_value_t = BadgeMetric::Weekly)]
+    pub metric: BadgeMetric,
+
+    /// badge label
+    #[arg(long, default_value = "downloads")]
+    pub label: String,
+
+    /// badge color
+    #[arg(long, default_value = "orange")]
+    pub color: String,
+}
+
+#[derive(Args)]
+pub struct PatternArgs {
+    /// crate name, - reads names from stdin
+    pub name: String,
+}
+
+#[derive(Args)]
+pub struct VersionsArgs {
+    /// crate name, - reads names from stdin
+    pub name: String,
+
+    /// list only yanked versions
+    #[arg(long)]
+    pub yanked: bool,
+
+    /// add the docs.rs build status of each version
+    #[arg(long)]
+    pub docs: bool,
+}
+
+#[derive(Args)]
+pub struct CadenceArgs {
+    /// crate names, comma separated or repeated, - reads names from stdin
+    #[arg(required = true, value_delimiter = ',')]
+    pub names: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct AdoptionArgs {
+    /// crate and version as crate@version
+    #[arg(value_name = "CRATE@VERSION")]
+    pub target: String,
+}
+
+#[derive(Args)]
+pub struct SearchArgs {
+    /// search query
+    pub query: String,
+
+    /// order of the results
+    #[arg(long, value_enum, default_value_t = SearchSort::Relevance)]
+    pub sort: SearchSort,
+
+    /// number of results to show
+    #[arg(long, default_value_t = 10, value_name = "N", value_parser = clap::value_parser!(u64).range(1..=100))]
+    pub limit: u64,
+}
+
+#[derive(Args)]
+pub struct TopArgs {
+    /// category slug, e.g. asynchronous
+    #[arg(long, required_unless_present = "keyword")]
+    pub category: Option<String>,
+
+    /// keyword, e.g. cli
+    #[arg(long)]
+    pub keyword: Option<String>,
+
+    /// rank by downloads of the last 90 days instead of all time
+    #[arg(long)]
+    pub recent: bool,
+
+    /// number of crates to show
+    #[arg(long, default_value_t = 20, value_name = "N", value_parser = clap::value_parser!(u64).range(1..=100))]
+    pub limit: u64,
+}
+
+#[derive(Args)]
+pub struct TrendingArgs {
+    /// rank by the change of the last 7 days over the 7 days before, fetching daily downloads
+    #[arg(long)]
+    pub week_over_week: bool,
+
+    /// skip crates published less than n days ago
+    #[arg(long, default_value_t = 30, value_name = "DAYS")]
+    pub min_age: u32,
+
+    /// skip crates with fewer recent downloads
+    #[arg(long, default_value_t = 10_000, value_name = "COUNT")]
+    pub min_downloads: u64,
+
+    /// number of most recently downloaded crates to rank
+    #[arg(long, default_value_t = 100, value_name = "N", value_parser = clap::value_parser!(u64).range(1..=100))]
+    pub candidates: u64,
+
+    /// number of crates to show
+    #[arg(long, default_value_t = 20, value_name = "N")]
+    pub limit: usize,
+}
+
+#[derive(Args)]
+pub struct FeedArgs {
+    /// show only crates since an age like 24h or 7d, or a date
+    #[arg(long, value_name = "TIME", value_parser = parse_point_in_time)]
+    pub since: Option<DateTime<Utc>>,
+
+    /// number of crates to show
+    #[arg(long, default_value_t = 20, value_name = "N")]
+    pub limit: usize,
+}
+
+#[derive(Args)]
+pub struct CrateListArgs {
+    /// crate names, comma separated or repeated, - reads names from stdin
+    #[arg(value_delimiter = ',')]
+    pub names: Vec<String>,
+
+    /// add the crates of a watchlist from the config file
+    #[arg(long, value_name = "NAME")]
+    pub list: Option<String>,
+}
+
+#[derive(Args)]
+pub struct SnapshotArgs {
+    /// crate names to snapshot, comma separated or repeated, - reads names from stdin
+    #[arg(value_delimiter = ',')]
+    pub names: Vec<String>,
+
+    /// also snapshot every crate of the given crates.io user
+    #[arg(short, long, value_name = "USER")]
+    pub user: Option<String>,
+
+    #[command(flatten)]
+    pub store: StoreArgs,
+}
+
+#[derive(Args)]
+pub struct DiffArgs {
+    /// crate name, - reads names from stdin
+    pub name: String,
+
+    /// baseline snapshot, an age like 30d, 12h, 2w or a date/timestamp
+    #[arg(long, value_name = "TIME", default_value = "30d", value_parser = parse_point_in_time)]
+    pub since: DateTime<Utc>,
+
+    /// compared snapshot, defaults to the latest one
+    #[arg(long, value_name = "TIME", value_parser = parse_point_in_time)]
+    pub until: Option<DateTime<Utc>>,
+
+    #[command(flatten)]
+    pub store: StoreArgs,
+}
+
+#[derive(Args)]
+pub struct HistoryArgs {
+    /// crate name, - reads names from stdin
+    pub name: String,
+
+    /// group downloads by period
+    #[arg(long, visible_alias = "granularity", value_enum, value_name = "PERIOD")]
+    pub group_by: Option<Period>,
+
+    /// overlay a moving average over n points on graphs
+    #[arg(long, value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(2..))]
+    pub smooth: Option<usize>,
+
+    /// graph width in columns, longer histories are resampled
+    #[arg(long, default_value_t = 100, value_name = "COLUMNS")]
+    pub width: u32,
+
+    #[command(flatten)]
+    pub window: WindowArgs,
+
+    #[command(flatten)]
+    pub store: StoreArgs,
+}
+
+#[derive(Args)]
+pub struct ImportDumpArgs {
+    /// dump tarball, extracted dump directory or url, e.g. https://static.crates.io/db-dump.tar.gz
+    #[arg(value_name = "PATH|URL")]
+    pub source: String,
+
+    /// only import the given crates, comma separated or repeated
+    #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+    pub crates: Vec<String>,
+
+    #[command(flatten)]
+    pub store: StoreArgs,
+}
+
+#[derive(Args)]
+#[command(next_help_heading = "Snapshot store")]
+pub struct StoreArgs {
+    /// snapshot database, defaults to crabst/history.db in the user data directory
+    #[arg(long, value_name = "FILE")]
+    pub db: Option<PathBuf>,
+}
+
+#[derive(Args)]
+#[command(next_help_heading = "Rows")]
+pub struct RowArgs {
+    /// sort table rows by the given key
+    #[arg(long, value_enum, value_name = "KEY")]
+    pub sort: Option<SortKey>,
+
+    /// sort rows in descending order
+    #[arg(long)]
+    pub desc: bool,
+
+    /// keep only the first n rows after sorting
+    #[arg(long, value_name = "N")]
+    pub top: Option<usize>,
+
+    /// hide rows with fewer downloads than the given count
+    #[arg(long, value_name = "COUNT")]
+    pub min_downloads: Option<u64>,
+}
+
+#[derive(Args)]
+pub struct OwnersArgs {
+    /// crate name, - reads names from stdin
+    pub name: String,
+}
+
+#[derive(Args)]
+#[command(next_help_heading = "Owner tracking")]
+pub struct OwnerArgs {
+    /// report crate owner changes since the last run
+    #[arg(long)]
+    pub track_owners: bool,
+
+    /// file used to remember crate owners between runs
+    #[arg(long, default_value = "owners.json", value_name = "STATE_FILE")]
+    pub state_file: PathBuf,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[value(alias = "t")]
+    Table,
+    #[value(alias = "g")]
+    Graph,
+    Json,
+    Ndjson,
+    Csv,
+    Md,
+    Svg,
+    Influx,
+    Html,
+    Dot,
+    Mermaid,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+    Quarter,
+    /// semver major versions, with --by-version
+    Major,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DependentsGroup {
+    Owner,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum DataSource {
+    #[default]
+    CratesIo,
+    LibRs,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SearchSort {
+    Relevance,
+    Downloads,
+    RecentDownloads,
+    RecentUpdates,
+    New,
+    Alpha,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BadgeMetric {
+    Daily,
+    Weekly,
+    Total,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortKey {
+    Name,
+    Total,
+    Daily,
+    Downloads,
+}
+
+#[derive(Args)]
+pub struct BenchmarkArgs {
+    /// crate name, - reads names from stdin
+    pub name: String,
+
+    /// number of most recently downloaded crates to compare against per category
+    #[arg(long, default_value_t = 500, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub sample: u64,
+}
+
+#[derive(Args)]
+pub struct CheckNameArgs {
+    /// crate names, comma separated or repeated, - reads names from stdin
+    #[arg(required = true, value_delimiter = ',')]
+    pub names: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct TyposquatArgs {
+    /// crate name, - reads names from stdin
+    pub name: String,
+}
+
+#[derive(Args)]
+pub struct AdvisoriesArgs {
+    /// crate name, - reads names from stdin
+    pub name: String,
+}
+
+#[derive(Args)]
+pub struct RelatedArgs {
+    /// crate name, - reads names from stdin
+    pub name: String,
+
+    /// number of crates to show
+    #[arg(long, default_value_t = 10, value_name = "N")]
+    pub limit: usize,
+}
+
+#[derive(Args)]
+pub struct HealthArgs {
+    /// crate name, - reads names from stdin
+    pub name: String,
+}
+
+#[derive(Args)]
+pub struct LicensesArgs {
+    /// crate name, - reads names from stdin
+    pub name: String,
+}
+
+#[derive(Args)]
+pub struct DuplicatesArgs {
+    /// crate name, - reads names from stdin
+    pub name: String,
+}
+
+#[derive(Args)]
+pub struct StdinArgs {
+    #[command(flatten)]
+    pub report: ReportArgs,
+}
+
+fn parse_point_in_time(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+    if let Ok(date) = value.parse::<NaiveDate>() {
+        return Ok(date.and_hms_opt(23, 59, 59).unwrap().and_utc());
+    }
+    let not_an_age = || format!("`{}` is not an age like 30d or a date", value);
+    let (split, unit) = value.char_indices().last().ok_or_else(not_an_age)?;
+    let amount = value[..split]
+        .parse::<u32>()
+        .map(i64::from)
+        .map_err(|_| not_an_age())?;
+    let age = match unit {
+        'h' => Duration::try_hours(amount),
+        'd' => Duration::try_days(amount),
+        'w' => Duration::try_weeks(amount),
+        _ => return Err(format!("unknown unit in `{}`, use h, d or w", value)),
+    };
+    age.and_then(|age| Utc::now().checked_sub_signed(age))
+        .ok_or_else(|| format!("`{}` is too far from now", value))
+}
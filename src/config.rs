@@ -0,0 +1,308 @@
+use crate::advisory::OSV_URL;
+use crate::api::{API_URL, USER_AGENT};
+use crate::cache::Cache;
+use crate::cli::{Cli, Command, DataSource};
+use crate::docsrs::DOCS_URL;
+use crate::error::{Context, Error, Result};
+use crate::github::GITHUB_API_URL;
+use crate::librs::LIBRS_URL;
+use clap::{CommandFactory, FromArgMatches};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+const DEFAULT_CACHE_TTL_MINUTES: u64 = 60;
+pub const MAX_JOBS: usize = 16;
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub user: Option<String>,
+    pub output: Option<String>,
+    pub last: Option<u32>,
+    pub concurrency: Option<usize>,
+    pub request_interval_ms: Option<u64>,
+    pub retries: Option<u32>,
+    pub cache_ttl_minutes: Option<u64>,
+    pub user_agent: Option<String>,
+    pub registry_url: Option<String>,
+    pub docs_url: Option<String>,
+    pub github_url: Option<String>,
+    pub osv_url: Option<String>,
+    pub librs_url: Option<String>,
+    pub proxy: Option<String>,
+    #[serde(skip)]
+    no_cache: bool,
+    #[serde(skip)]
+    offline: bool,
+    #[serde(skip)]
+    source: DataSource,
+    pub token: Option<String>,
+    pub webhook: Option<String>,
+    pub lists: BTreeMap<String, WatchList>,
+    pub alerts: Vec<AlertRule>,
+    pub schedule: Vec<ScheduledJob>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WatchList {
+    pub crates: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertRule {
+    #[serde(rename = "crate")]
+    pub crate_name: String,
+    pub total_above: Option<u64>,
+    pub daily_above: Option<u64>,
+    pub daily_drop_percent: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduledJob {
+    pub cron: String,
+    pub run: String,
+}
+
+impl Config {
+    pub fn load() -> Result<Config> {
+        let Some(path) = config_path() else {
+            return Ok(Config::default());
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                toml::from_str(&content).context(format!("can not parse {}", path.display()))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(err) => Err(err).context(format!("can not read {}", path.display())),
+        }
+    }
+
+    pub fn apply_defaults(&self, mut command: clap::Command) -> clap::Command {
+        if let Some(output) = &self.output {
+            command = command.mut_arg("output", |arg| arg.default_value(output.clone()));
+        }
+        if let Some(user) = &self.user {
+            for (subcommand, arg) in [("user", "names"), ("tui", "name")] {
+                command = command.mut_subcommand(subcommand, |subcommand| {
+                    subcommand.mut_arg(arg, |arg| arg.required(false).default_value(user.clone()))
+                });
+            }
+        }
+        command
+    }
+
+    pub fn apply_window(&self, cli: &mut Cli) {
+        let window = match &mut cli.command {
+            Command::Crate(args) => &mut args.window,
+            Command::User(args) => &mut args.report.window,
+            Command::Me(args) => &mut args.report.window,
+            Command::List(args) => &mut args.report.window,
+            Command::Project(args) => &mut args.report.window,
+            Command::Stdin(args) => &mut args.report.window,
+            Command::History(args) => &mut args.window,
+            _ => return,
+        };
+        if !window.is_bounded() {
+            window.last = self.last;
+        }
+    }
+
+    pub fn apply_network(&mut self, cli: &Cli) {
+        let network = &cli.network;
+        if network.jobs.is_some() {
+            self.concurrency = network.jobs;
+        }
+        if network.user_agent.is_some() {
+            self.user_agent = network.user_agent.clone();
+        }
+        if network.registry_url.is_some() {
+            self.registry_url = network.registry_url.clone();
+        }
+        if network.proxy.is_some() {
+            self.proxy = network.proxy.clone();
+        }
+        if network.retries.is_some() {
+            self.retries = network.retries;
+        }
+        if network.cache_ttl.is_some() {
+            self.cache_ttl_minutes = network.cache_ttl;
+        }
+        self.no_cache = network.no_cache;
+        self.offline = network.offline;
+        self.source = network.source;
+
+        let refresh = match &cli.command {
+            Command::Tui(args) => Some(args.refresh),
+            Command::Serve(args) => Some(args.interval),
+            Command::Daemon(args) if args.prometheus.is_some() => Some(args.interval),
+            _ => cli.output.watch,
+        };
+        if let Some(refresh) = refresh {
+            let ttl = self.cache_ttl_minutes.unwrap_or(DEFAULT_CACHE_TTL_MINUTES);
+            self.cache_ttl_minutes = Some(ttl.min(refresh));
+        }
+    }
+}
+
+pub fn set(config: Config) {
+    let _ = CONFIG.set(config);
+}
+
+pub fn concurrency() -> usize {
+    CONFIG
+        .get()
+        .and_then(|config| config.concurrency)
+        .unwrap_or(3)
+        .clamp(1, MAX_JOBS)
+}
+
+pub fn request_interval() -> Duration {
+    Duration::from_millis(
+        CONFIG
+            .get()
+            .and_then(|config| config.request_interval_ms)
+            .unwrap_or(100),
+    )
+}
+
+pub fn retries() -> u32 {
+    CONFIG.get().and_then(|config| config.retries).unwrap_or(3)
+}
+
+pub fn user_agent() -> String {
+    CONFIG
+        .get()
+        .and_then(|config| config.user_agent.clone())
+        .unwrap_or(USER_AGENT.to_string())
+}
+
+pub fn registry_url() -> String {
+    CONFIG
+        .get()
+        .and_then(|config| config.registry_url.clone())
+        .unwrap_or(API_URL.to_string())
+}
+
+pub fn docs_url() -> String {
+    CONFIG
+        .get()
+        .and_then(|config| config.docs_url.clone())
+        .unwrap_or(DOCS_URL.to_string())
+}
+
+pub fn github_url() -> String {
+    CONFIG
+        .get()
+        .and_then(|config| config.github_url.clone())
+        .unwrap_or(GITHUB_API_URL.to_string())
+}
+
+pub fn github_token() -> Option<String> {
+    ["GITHUB_TOKEN", "GH_TOKEN"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|token| !token.is_empty()))
+}
+
+pub fn osv_url() -> String {
+    CONFIG
+        .get()
+        .and_then(|config| config.osv_url.clone())
+        .unwrap_or(OSV_URL.to_string())
+}
+
+pub fn librs_url() -> String {
+    CONFIG
+        .get()
+        .and_then(|config| config.librs_url.clone())
+        .unwrap_or(LIBRS_URL.to_string())
+}
+
+pub fn source() -> DataSource {
+    CONFIG
+        .get()
+        .map_or(DataSource::CratesIo, |config| config.source)
+}
+
+pub fn proxy() -> Option<String> {
+    CONFIG.get().and_then(|config| config.proxy.clone())
+}
+
+pub fn cache() -> Option<Cache> {
+    let config = CONFIG.get()?;
+    let ttl = config
+        .cache_ttl_minutes
+        .unwrap_or(DEFAULT_CACHE_TTL_MINUTES);
+    let dir = dirs::cache_dir()?.join("crabst");
+    if config.offline {
+        return Some(Cache::new(dir, None));
+    }
+    if config.no_cache || ttl == 0 {
+        return None;
+    }
+    Some(Cache::new(dir, Some(Duration::from_secs(ttl * 60))))
+}
+
+pub fn offline() -> bool {
+    CONFIG.get().is_some_and(|config| config.offline)
+}
+
+pub fn api_token() -> Option<String> {
+    ["CRABST_TOKEN", "CARGO_REGISTRY_TOKEN"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|token| !token.is_empty()))
+        .or_else(|| CONFIG.get().and_then(|config| config.token.clone()))
+}
+
+pub fn webhook() -> Option<String> {
+    CONFIG.get().and_then(|config| config.webhook.clone())
+}
+
+pub fn alerts() -> &'static [AlertRule] {
+    CONFIG.get().map_or(&[], |config| &config.alerts)
+}
+
+pub fn schedule() -> &'static [ScheduledJob] {
+    CONFIG.get().map_or(&[], |config| &config.schedule)
+}
+
+pub fn parse_command(line: &str) -> Result<Cli> {
+    let command = match CONFIG.get() {
+        Some(config) => config.apply_defaults(Cli::command()),
+        None => Cli::command(),
+    };
+    let args = std::iter::once("crabst").chain(line.split_whitespace());
+    let mut cli = command
+        .try_get_matches_from(args)
+        .and_then(|matches| Cli::from_arg_matches(&matches))
+        .map_err(|err| {
+            let message = err.to_string();
+            let reason = message.lines().next().unwrap_or_default();
+            Error::Usage(format!(
+                "invalid scheduled command `{}`: {}",
+                line,
+                reason.trim_start_matches("error: ")
+            ))
+        })?;
+    if let Some(config) = CONFIG.get() {
+        config.apply_window(&mut cli);
+    }
+    Ok(cli)
+}
+
+pub fn watch_list(name: &str) -> Option<&'static WatchList> {
+    CONFIG.get().and_then(|config| config.lists.get(name))
+}
+
+fn config_path() -> Option<PathBuf> {
+    match std::env::var_os("CRABST_CONFIG") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => dirs::config_dir().map(|dir| dir.join("crabst").join("config.toml")),
+    }
+}
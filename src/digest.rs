@@ -0,0 +1,171 @@
+use crate::api::{self, Client};
+use crate::config;
+use crate::error::{crate_not_found, Context, Result};
+use crate::store::Store;
+use chrono::{Days, Utc};
+use crabst_lib::stats::{daily_downloads, trend};
+use futures::{stream, StreamExt};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::Url;
+use serde_json::json;
+use std::fmt::Write;
+
+const DISCORD_LIMIT: usize = 2000;
+
+pub struct CrateDigest {
+    pub name: String,
+    pub downloads: u64,
+    pub window: u64,
+    pub previous_window: u64,
+    pub dependents: u64,
+    pub previous_dependents: Option<u64>,
+}
+
+#[derive(Clone, Copy)]
+pub enum Markup {
+    Plain,
+    Slack,
+    Discord,
+}
+
+pub async fn collect(
+    client: &Client,
+    crate_names: &[String],
+    days: u32,
+    store: Option<&Store>,
+) -> Result<Vec<CrateDigest>> {
+    let today = Utc::now().date_naive();
+    let start = today - Days::new(days as u64);
+    let previous_start = start - Days::new(days as u64);
+    let fetched = stream::iter(crate_names.to_vec())
+        .map(|crate_name| async move {
+            let api_crate = client
+                .get_crate(&crate_name)
+                .await
+                .map_err(crate_not_found(&crate_name))?
+                .crate_data;
+            let downloads = client
+                .crate_downloads(&crate_name)
+                .await
+                .context(format!("can not get downloads of {}", crate_name))?;
+            let dependents = client
+                .crate_reverse_dependency_count(&crate_name)
+                .await
+                .context(format!("can not get dependents of {}", crate_name))?;
+            let daily = daily_downloads(&downloads);
+            Ok::<_, crate::error::Error>(CrateDigest {
+                window: daily.range(start..today).map(|(_, count)| count).sum(),
+                previous_window: daily
+                    .range(previous_start..start)
+                    .map(|(_, count)| count)
+                    .sum(),
+                name: crate_name,
+                downloads: api_crate.downloads,
+                dependents,
+                previous_dependents: None,
+            })
+        })
+        .buffered(config::concurrency())
+        .collect::<Vec<_>>()
+        .await;
+
+    let since = Utc::now() - chrono::Duration::days(days as i64);
+    let mut digests = Vec::new();
+    for result in fetched {
+        let mut digest = result?;
+        if let Some(store) = store {
+            digest.previous_dependents = store
+                .snapshot_near(&digest.name, since)?
+                .map(|snapshot| snapshot.dependents);
+        }
+        digests.push(digest);
+    }
+    Ok(digests)
+}
+
+pub fn render(digests: &[CrateDigest], days: u32, top: usize, markup: Markup) -> String {
+    let bold = |text: &str| match markup {
+        Markup::Plain => text.to_string(),
+        Markup::Slack => format!("*{}*", text),
+        Markup::Discord => format!("**{}**", text),
+    };
+    let window = digests.iter().map(|d| d.window).sum::<u64>();
+    let previous = digests.iter().map(|d| d.previous_window).sum::<u64>();
+    let total = digests.iter().map(|d| d.downloads).sum::<u64>();
+
+    let mut text = String::new();
+    let _ = writeln!(
+        text,
+        "{} {} crates, last {} days",
+        bold("crabst digest"),
+        digests.len(),
+        days
+    );
+    let _ = writeln!(
+        text,
+        "Downloads: {} ({} vs previous {} days), {} all time",
+        window,
+        trend(window, previous),
+        days,
+        total
+    );
+
+    let mut movers = digests
+        .iter()
+        .filter(|d| d.window != d.previous_window)
+        .collect::<Vec<_>>();
+    movers.sort_by_key(|d| std::cmp::Reverse(d.window.abs_diff(d.previous_window)));
+    if !movers.is_empty() {
+        let _ = writeln!(text, "{}", bold("Top movers"));
+        for d in movers.iter().take(top) {
+            let _ = writeln!(
+                text,
+                "• {} {} ({} → {})",
+                d.name,
+                trend(d.window, d.previous_window),
+                d.previous_window,
+                d.window
+            );
+        }
+    }
+
+    let new_dependents = digests
+        .iter()
+        .filter_map(|d| {
+            let previous = d.previous_dependents?;
+            (d.dependents > previous).then_some((d, d.dependents - previous))
+        })
+        .collect::<Vec<_>>();
+    if !new_dependents.is_empty() {
+        let _ = writeln!(text, "{}", bold("New dependents"));
+        for (d, added) in new_dependents {
+            let _ = writeln!(text, "• {} +{} ({} total)", d.name, added, d.dependents);
+        }
+    }
+    text
+}
+
+pub async fn post(webhook: &Url, text: &str, markup: Markup) -> Result<()> {
+    let payload = match markup {
+        Markup::Discord => json!({"content": truncate(text, DISCORD_LIMIT)}),
+        _ => json!({"text": text}),
+    };
+    api::http_client()?
+        .post(webhook.clone())
+        .header(CONTENT_TYPE, "application/json")
+        .body(payload.to_string())
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .context(format!("can not post digest to {}", webhook))?;
+    Ok(())
+}
+
+fn truncate(text: &str, limit: usize) -> String {
+    if text.chars().count() <= limit {
+        return text.to_string();
+    }
+    let mut truncated = text.chars().take(limit - 1).collect::<String>();
+    truncated.push('…');
+    truncated
+}
@@ -0,0 +1,116 @@
+use crate::api::http_client;
+use crate::cache::Cache;
+use crate::config;
+use crate::error::{Context, Error, Result};
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const DOCS_URL: &str = "https://docs.rs";
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BuildStatus {
+    Success,
+    Failure,
+    InProgress,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Build {
+    pub status: BuildStatus,
+    pub built_at: Option<DateTime<Utc>>,
+    pub rustc_version: Option<String>,
+}
+
+impl Build {
+    pub fn marker(&self) -> &'static str {
+        match self.status {
+            BuildStatus::Success => "✓",
+            BuildStatus::Failure => "✗",
+            BuildStatus::InProgress => "…",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    cache: Option<Cache>,
+    offline: bool,
+}
+
+impl Client {
+    pub fn new() -> Result<Client> {
+        Ok(Client {
+            http: http_client()?,
+            base_url: config::docs_url(),
+            cache: config::cache(),
+            offline: config::offline(),
+        })
+    }
+
+    /// The latest docs.rs build of a crate version, none when docs.rs has not seen it.
+    pub async fn latest_build(&self, crate_name: &str, version: &str) -> Result<Option<Build>> {
+        let key = format!("docs.rs/{}/{}", crate_name, version);
+        if let Some(build) = self.cache.as_ref().and_then(|cache| cache.get(&key)) {
+            tracing::info!("cache hit {}", key);
+            return Ok(build);
+        }
+        let context = || format!("can not get docs.rs builds of {} {}", crate_name, version);
+        if self.offline {
+            return Err(Error::Usage(format!("{}: not cached", context())));
+        }
+        let url = format!(
+            "{}/crate/{}/{}/builds.json",
+            self.base_url.trim_end_matches('/'),
+            crate_name,
+            version
+        );
+        tracing::info!("GET {}", url);
+        let response = self.http.get(url).send().await.context(context())?;
+        let build = match response.status() {
+            StatusCode::NOT_FOUND => None,
+            _ => {
+                let body = response
+                    .error_for_status()
+                    .context(context())?
+                    .text()
+                    .await
+                    .context(context())?;
+                let builds: Vec<Value> = serde_json::from_str(&body).context(context())?;
+                builds.first().map(parse_build)
+            }
+        };
+        // builds still running are fetched again next time
+        if !build
+            .as_ref()
+            .is_some_and(|build| build.status == BuildStatus::InProgress)
+        {
+            if let Some(cache) = &self.cache {
+                cache.put(&key, &build);
+            }
+        }
+        Ok(build)
+    }
+}
+
+// older docs.rs releases report the status as a boolean
+fn parse_build(build: &Value) -> Build {
+    let status = match &build["build_status"] {
+        Value::Bool(true) => BuildStatus::Success,
+        Value::String(status) if status == "success" => BuildStatus::Success,
+        Value::String(status) if status == "in_progress" => BuildStatus::InProgress,
+        _ => BuildStatus::Failure,
+    };
+    Build {
+        status,
+        built_at: build["build_time"]
+            .as_str()
+            .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+            .map(|time| time.with_timezone(&Utc)),
+        rustc_version: build["rustc_version"].as_str().map(str::to_string),
+    }
+}
@@ -0,0 +1,179 @@
+use crate::error::{Context, Error, Result};
+use crate::store::Store;
+use chrono::NaiveDate;
+use flate2::read::GzDecoder;
+use indicatif::ProgressBar;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+
+const DUMP_TABLES: [&str; 3] = ["crates.csv", "versions.csv", "version_downloads.csv"];
+
+pub async fn download(url: &str, path: &Path, pb: &ProgressBar) -> Result<()> {
+    let mut response = crate::api::http_client()?
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .context(format!("can not download {}", url))?;
+    let mut file = tokio::fs::File::create(path)
+        .await
+        .context(format!("can not create {}", path.display()))?;
+    let mut fetched = 0;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .context(format!("can not download {}", url))?
+    {
+        file.write_all(&chunk)
+            .await
+            .context(format!("can not write {}", path.display()))?;
+        fetched += chunk.len() as u64;
+        pb.set_message(format!(
+            "Downloading dump {} MiB...",
+            fetched / (1024 * 1024)
+        ));
+    }
+    file.flush()
+        .await
+        .context(format!("can not write {}", path.display()))
+}
+
+pub fn unpack(archive: &Path, dir: &Path) -> Result<()> {
+    let file = File::open(archive).context(format!("can not open {}", archive.display()))?;
+    let mut tarball = tar::Archive::new(GzDecoder::new(file));
+    let entries = tarball
+        .entries()
+        .context(format!("can not read {}", archive.display()))?;
+    for entry in entries {
+        let mut entry = entry.context(format!("can not read {}", archive.display()))?;
+        let path = entry
+            .path()
+            .context(format!("can not read {}", archive.display()))?
+            .into_owned();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if path.parent().and_then(|parent| parent.file_name()) == Some("data".as_ref())
+            && DUMP_TABLES.contains(&name)
+        {
+            entry
+                .unpack(dir.join(name))
+                .context(format!("can not unpack {}", name))?;
+        }
+    }
+    Ok(())
+}
+
+pub fn find_tables(dir: &Path) -> Result<PathBuf> {
+    if DUMP_TABLES.iter().all(|table| dir.join(table).is_file()) {
+        return Ok(dir.to_path_buf());
+    }
+    let entries = std::fs::read_dir(dir).context(format!("can not read {}", dir.display()))?;
+    for entry in entries {
+        let path = entry
+            .context(format!("can not read {}", dir.display()))?
+            .path();
+        if path.is_dir() {
+            if let Ok(found) = find_tables(&path) {
+                return Ok(found);
+            }
+        }
+    }
+    Err(Error::Usage(format!(
+        "{} does not contain {}",
+        dir.display(),
+        DUMP_TABLES.join(", ")
+    )))
+}
+
+pub fn import(dir: &Path, store: &mut Store, only: &[String]) -> Result<usize> {
+    let only = only.iter().map(String::as_str).collect::<HashSet<_>>();
+
+    let mut crate_names = HashMap::new();
+    read_table(dir, "crates.csv", &["id", "name"], |fields| {
+        if only.is_empty() || only.contains(fields[1]) {
+            crate_names.insert(parse_id(fields[0])?, fields[1].to_string());
+        }
+        Ok(())
+    })?;
+
+    let mut version_crates = HashMap::new();
+    read_table(dir, "versions.csv", &["id", "crate_id"], |fields| {
+        if let Some(crate_name) = crate_names.get(&parse_id(fields[1])?) {
+            version_crates.insert(parse_id(fields[0])?, crate_name.clone());
+        }
+        Ok(())
+    })?;
+
+    let path = dir.join("version_downloads.csv");
+    let mut reader =
+        csv::Reader::from_path(&path).context(format!("can not open {}", path.display()))?;
+    let columns = columns(&mut reader, &path, &["version_id", "downloads", "date"])?;
+    let rows = reader.into_records().filter_map(|record| {
+        let record = match record.context(format!("can not read {}", path.display())) {
+            Ok(record) => record,
+            Err(err) => return Some(Err(err)),
+        };
+        let fields = columns.iter().map(|&i| &record[i]).collect::<Vec<_>>();
+        let crate_name = match parse_id(fields[0]) {
+            Ok(version_id) => version_crates.get(&version_id)?,
+            Err(err) => return Some(Err(err)),
+        };
+        Some(
+            parse_download(fields[1], fields[2])
+                .map(|(date, downloads)| (crate_name.clone(), date, downloads)),
+        )
+    });
+    store.import_version_downloads(rows)
+}
+
+fn read_table(
+    dir: &Path,
+    table: &str,
+    names: &[&str],
+    mut visit: impl FnMut(&[&str]) -> Result<()>,
+) -> Result<()> {
+    let path = dir.join(table);
+    let mut reader =
+        csv::Reader::from_path(&path).context(format!("can not open {}", path.display()))?;
+    let columns = columns(&mut reader, &path, names)?;
+    for record in reader.records() {
+        let record = record.context(format!("can not read {}", path.display()))?;
+        let fields = columns.iter().map(|&i| &record[i]).collect::<Vec<_>>();
+        visit(&fields)?;
+    }
+    Ok(())
+}
+
+fn columns(reader: &mut csv::Reader<File>, path: &Path, names: &[&str]) -> Result<Vec<usize>> {
+    let headers = reader
+        .headers()
+        .context(format!("can not read {}", path.display()))?;
+    names
+        .iter()
+        .map(|name| {
+            headers
+                .iter()
+                .position(|header| header == *name)
+                .ok_or_else(|| Error::Usage(format!("{} has no {} column", path.display(), name)))
+        })
+        .collect()
+}
+
+fn parse_id(value: &str) -> Result<u64> {
+    value
+        .parse()
+        .map_err(|_| Error::Usage(format!("invalid id `{}` in dump", value)))
+}
+
+fn parse_download(downloads: &str, date: &str) -> Result<(NaiveDate, u64)> {
+    let date = date
+        .parse()
+        .map_err(|_| Error::Usage(format!("invalid date `{}` in dump", date)))?;
+    let downloads = downloads
+        .parse()
+        .map_err(|_| Error::Usage(format!("invalid download count `{}` in dump", downloads)))?;
+    Ok((date, downloads))
+}
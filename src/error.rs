@@ -0,0 +1,170 @@
+use std::process::ExitCode;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("crate `{0}` was not found on crates.io")]
+    CrateNotFound(String),
+    #[error("user `{0}` was not found on crates.io")]
+    UserNotFound(String),
+    #[error("team `{0}` was not found on crates.io")]
+    TeamNotFound(String),
+    #[error("{context}: {source}")]
+    Api {
+        context: String,
+        #[source]
+        source: crates_io_api::Error,
+    },
+    #[error("{context}: {source}")]
+    Io {
+        context: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("{context}: {source}")]
+    Json {
+        context: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("{context}: {source}")]
+    Store {
+        context: String,
+        #[source]
+        source: rusqlite::Error,
+    },
+    #[error("{context}: {source}")]
+    Csv {
+        context: String,
+        #[source]
+        source: csv::Error,
+    },
+    #[error("{context}: {source}")]
+    Http {
+        context: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("{context}: {source}")]
+    Config {
+        context: String,
+        #[source]
+        source: toml::de::Error,
+    },
+    #[error("invalid user agent: {0}")]
+    UserAgent(#[from] reqwest::header::InvalidHeaderValue),
+    #[error("{0}")]
+    Usage(String),
+    #[error("interrupted before fetching {}", .0.join(", "))]
+    Interrupted(Vec<String>),
+    #[error("can not fetch downloads of {}", .0.join(", "))]
+    FetchFailed(Vec<String>),
+}
+
+impl Error {
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            Error::Usage(_) | Error::Config { .. } | Error::UserAgent(_) => ExitCode::from(2),
+            Error::Interrupted(_) => ExitCode::from(130),
+            Error::CrateNotFound(_) | Error::UserNotFound(_) | Error::TeamNotFound(_) => {
+                ExitCode::from(3)
+            }
+            Error::Api { .. } | Error::Http { .. } | Error::FetchFailed(_) => ExitCode::from(4),
+            Error::Io { .. } | Error::Json { .. } | Error::Store { .. } | Error::Csv { .. } => {
+                ExitCode::from(1)
+            }
+        }
+    }
+}
+
+pub trait Context<T> {
+    fn context(self, context: impl Into<String>) -> Result<T>;
+}
+
+impl<T> Context<T> for std::result::Result<T, crates_io_api::Error> {
+    fn context(self, context: impl Into<String>) -> Result<T> {
+        self.map_err(|source| Error::Api {
+            context: context.into(),
+            source,
+        })
+    }
+}
+
+impl<T> Context<T> for std::result::Result<T, std::io::Error> {
+    fn context(self, context: impl Into<String>) -> Result<T> {
+        self.map_err(|source| Error::Io {
+            context: context.into(),
+            source,
+        })
+    }
+}
+
+impl<T> Context<T> for std::result::Result<T, serde_json::Error> {
+    fn context(self, context: impl Into<String>) -> Result<T> {
+        self.map_err(|source| Error::Json {
+            context: context.into(),
+            source,
+        })
+    }
+}
+
+impl<T> Context<T> for std::result::Result<T, rusqlite::Error> {
+    fn context(self, context: impl Into<String>) -> Result<T> {
+        self.map_err(|source| Error::Store {
+            context: context.into(),
+            source,
+        })
+    }
+}
+
+impl<T> Context<T> for std::result::Result<T, csv::Error> {
+    fn context(self, context: impl Into<String>) -> Result<T> {
+        self.map_err(|source| Error::Csv {
+            context: context.into(),
+            source,
+        })
+    }
+}
+
+impl<T> Context<T> for std::result::Result<T, reqwest::Error> {
+    fn context(self, context: impl Into<String>) -> Result<T> {
+        self.map_err(|source| Error::Http {
+            context: context.into(),
+            source,
+        })
+    }
+}
+
+impl<T> Context<T> for std::result::Result<T, toml::de::Error> {
+    fn context(self, context: impl Into<String>) -> Result<T> {
+        self.map_err(|source| Error::Config {
+            context: context.into(),
+            source,
+        })
+    }
+}
+
+impl From<crabst_lib::Error> for Error {
+    fn from(err: crabst_lib::Error) -> Error {
+        match err {
+            crabst_lib::Error::CrateNotFound(name) => Error::CrateNotFound(name),
+            crabst_lib::Error::UserNotFound(name) => Error::UserNotFound(name),
+            crabst_lib::Error::TeamNotFound(name) => Error::TeamNotFound(name),
+            crabst_lib::Error::Api { context, source } => Error::Api { context, source },
+            crabst_lib::Error::Http { context, source } => Error::Http { context, source },
+            crabst_lib::Error::UserAgent(err) => Error::UserAgent(err),
+            crabst_lib::Error::Usage(message) => Error::Usage(message),
+        }
+    }
+}
+
+pub fn crate_not_found(crate_name: &str) -> impl Fn(crates_io_api::Error) -> Error + '_ {
+    let not_found = crabst_lib::error::crate_not_found(crate_name);
+    move |source| not_found(source).into()
+}
+
+pub fn user_not_found(user_name: &str) -> impl Fn(crates_io_api::Error) -> Error + '_ {
+    let not_found = crabst_lib::error::user_not_found(user_name);
+    move |source| not_found(source).into()
+}
@@ -0,0 +1,55 @@
+const SEASON: usize = 7;
+const ALPHA: f64 = 0.3;
+const BETA: f64 = 0.05;
+const GAMMA: f64 = 0.2;
+
+/// Extends `history` by `days` values, using Holt-Winters with weekly
+/// seasonality when two full weeks are known and a linear trend otherwise.
+pub fn extend(history: &[f64], days: usize) -> Vec<f64> {
+    let forecast = if history.len() >= 2 * SEASON {
+        holt_winters(history, days)
+    } else {
+        linear(history, days)
+    };
+    forecast.into_iter().map(|value| value.max(0.0)).collect()
+}
+
+fn holt_winters(history: &[f64], days: usize) -> Vec<f64> {
+    let mean = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+    let first = mean(&history[..SEASON]);
+    let second = mean(&history[SEASON..2 * SEASON]);
+    let mut level = first;
+    let mut trend = (second - first) / SEASON as f64;
+    let mut seasonal = history[..SEASON]
+        .iter()
+        .map(|value| value - first)
+        .collect::<Vec<_>>();
+
+    for (i, value) in history.iter().enumerate().skip(SEASON) {
+        let season = seasonal[i % SEASON];
+        let previous_level = level;
+        level = ALPHA * (value - season) + (1.0 - ALPHA) * (level + trend);
+        trend = BETA * (level - previous_level) + (1.0 - BETA) * trend;
+        seasonal[i % SEASON] = GAMMA * (value - level) + (1.0 - GAMMA) * season;
+    }
+    (1..=days)
+        .map(|step| level + step as f64 * trend + seasonal[(history.len() + step - 1) % SEASON])
+        .collect()
+}
+
+fn linear(history: &[f64], days: usize) -> Vec<f64> {
+    let n = history.len() as f64;
+    if history.len() < 2 {
+        return vec![history.first().copied().unwrap_or(0.0); days];
+    }
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = history.iter().sum::<f64>() / n;
+    let (covariance, variance) = history.iter().enumerate().fold((0.0, 0.0), |acc, (x, y)| {
+        let dx = x as f64 - mean_x;
+        (acc.0 + dx * (y - mean_y), acc.1 + dx * dx)
+    });
+    let slope = covariance / variance;
+    (0..days)
+        .map(|step| mean_y + slope * (n + step as f64 - mean_x))
+        .collect()
+}
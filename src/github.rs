@@ -0,0 +1,147 @@
+use crate::api::http_client;
+use crate::cache::Cache;
+use crate::config;
+use crate::error::{Context, Error, Result};
+use chrono::{DateTime, Utc};
+use reqwest::header::{ACCEPT, AUTHORIZATION};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+
+pub const GITHUB_API_URL: &str = "https://api.github.com";
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Repo {
+    pub full_name: String,
+    pub stars: u64,
+    pub open_issues: u64,
+    pub last_commit: Option<DateTime<Utc>>,
+    pub archived: bool,
+}
+
+#[derive(Deserialize)]
+struct RepoResponse {
+    full_name: String,
+    stargazers_count: u64,
+    open_issues_count: u64,
+    archived: bool,
+}
+
+#[derive(Deserialize)]
+struct CommitResponse {
+    commit: CommitDetails,
+}
+
+#[derive(Deserialize)]
+struct CommitDetails {
+    committer: Option<CommitAuthor>,
+}
+
+#[derive(Deserialize)]
+struct CommitAuthor {
+    date: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    token: Option<String>,
+    cache: Option<Cache>,
+    offline: bool,
+}
+
+impl Client {
+    pub fn new() -> Result<Client> {
+        Ok(Client {
+            http: http_client()?,
+            base_url: config::github_url(),
+            token: config::github_token(),
+            cache: config::cache(),
+            offline: config::offline(),
+        })
+    }
+
+    /// Stats of the GitHub repository a crate links to, none for other hosts and
+    /// missing repositories.
+    pub async fn repo(&self, repository: &str) -> Result<Option<Repo>> {
+        let Some((owner, name)) = parse_repository(repository) else {
+            return Ok(None);
+        };
+        let key = format!("github/{}/{}", owner, name);
+        if let Some(repo) = self.cache.as_ref().and_then(|cache| cache.get(&key)) {
+            tracing::info!("cache hit {}", key);
+            return Ok(repo);
+        }
+        let context = || format!("can not get github repository {}/{}", owner, name);
+        if self.offline {
+            return Err(Error::Usage(format!("{}: not cached", context())));
+        }
+        let Some(response) = self
+            .get::<RepoResponse>(&format!("repos/{}/{}", owner, name))
+            .await?
+        else {
+            return Ok(None);
+        };
+        let commits = self
+            .get::<Vec<CommitResponse>>(&format!("repos/{}/{}/commits?per_page=1", owner, name))
+            .await?
+            .unwrap_or_default();
+        let repo = Some(Repo {
+            full_name: response.full_name,
+            stars: response.stargazers_count,
+            open_issues: response.open_issues_count,
+            last_commit: commits
+                .first()
+                .and_then(|commit| commit.commit.committer.as_ref())
+                .map(|committer| committer.date),
+            archived: response.archived,
+        });
+        if let Some(cache) = &self.cache {
+            cache.put(&key, &repo);
+        }
+        Ok(repo)
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<Option<T>> {
+        let url = format!("{}/{}", self.base_url.trim_end_matches('/'), path);
+        let context = || format!("can not get {} from github", path);
+        tracing::info!("GET {}", url);
+        let mut request = self
+            .http
+            .get(url)
+            .header(ACCEPT, "application/vnd.github+json");
+        if let Some(token) = &self.token {
+            request = request.header(AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let response = request.send().await.context(context())?;
+        match response.status() {
+            // empty repositories answer their commits with 409
+            StatusCode::NOT_FOUND | StatusCode::CONFLICT => return Ok(None),
+            StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS if self.token.is_none() => {
+                return Err(Error::Usage(
+                    "github rate limit reached, set GITHUB_TOKEN for a higher limit".to_string(),
+                ))
+            }
+            _ => {}
+        }
+        let body = response
+            .error_for_status()
+            .context(context())?
+            .text()
+            .await
+            .context(context())?;
+        serde_json::from_str(&body).map(Some).context(context())
+    }
+}
+
+fn parse_repository(repository: &str) -> Option<(String, String)> {
+    let url = reqwest::Url::parse(repository).ok()?;
+    if !matches!(url.host_str(), Some("github.com" | "www.github.com")) {
+        return None;
+    }
+    let mut segments = url.path_segments()?.filter(|segment| !segment.is_empty());
+    let owner = segments.next()?;
+    let name = segments.next()?;
+    let name = name.strip_suffix(".git").unwrap_or(name);
+    Some((owner.to_string(), name.to_string()))
+}
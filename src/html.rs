@@ -0,0 +1,165 @@
+use crate::chart;
+use crate::escape_xml;
+use chrono::{NaiveDate, Utc};
+use crates_io_api::Crate;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+const STYLE: &str = "body{font-family:sans-serif;max-width:960px;margin:2em auto;color:#222}\
+table{border-collapse:collapse;margin:1em 0}\
+th,td{border:1px solid #ddd;padding:4px 8px}\
+td.n{text-align:right}\
+th{background:#f6f6f6}\
+footer{color:#888;font-size:small}";
+
+pub fn crates_report(
+    title: &str,
+    crates: &[Crate],
+    daily_downloads: &HashMap<String, HashMap<NaiveDate, u64>>,
+    days: &[NaiveDate],
+) -> String {
+    let day_count = |crate_name: &str, day: &NaiveDate| {
+        daily_downloads
+            .get(crate_name)
+            .and_then(|downloads| downloads.get(day))
+            .copied()
+            .unwrap_or(0)
+    };
+    let daily_totals = days
+        .iter()
+        .map(|day| crates.iter().map(|c| day_count(&c.name, day)).sum::<u64>() as f64)
+        .collect::<Vec<_>>();
+
+    let mut body = String::new();
+    let _ = writeln!(
+        body,
+        "<p>{} crates, {} downloads all time, {} in the selected days</p>",
+        crates.len(),
+        crates.iter().map(|c| c.downloads).sum::<u64>(),
+        daily_totals.iter().sum::<f64>() as u64
+    );
+    body.push_str(&chart::render(
+        days,
+        &[("daily downloads", &daily_totals)],
+        "daily downloads",
+    ));
+
+    let headers = [
+        "Crate",
+        "Version",
+        "Description",
+        "Downloads",
+        "Recent",
+        "Selected days",
+        "Updated",
+    ];
+    let rows = crates
+        .iter()
+        .map(|c| {
+            vec![
+                crate_link(c),
+                escape_xml(&c.max_version),
+                escape_xml(c.description.as_deref().unwrap_or_default()),
+                c.downloads.to_string(),
+                c.recent_downloads.unwrap_or(0).to_string(),
+                days.iter()
+                    .map(|day| day_count(&c.name, day))
+                    .sum::<u64>()
+                    .to_string(),
+                c.updated_at.date_naive().to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    body.push_str(&table(&headers, &rows));
+    page(title, &body)
+}
+
+pub fn downloads_report(
+    title: &str,
+    crate_data: Option<&Crate>,
+    downloads: &[(NaiveDate, f64)],
+) -> String {
+    let mut body = String::new();
+    if let Some(c) = crate_data {
+        if let Some(description) = &c.description {
+            let _ = writeln!(body, "<p>{}</p>", escape_xml(description));
+        }
+        let mut facts = vec![
+            ("Latest version", escape_xml(&c.max_version)),
+            ("All time downloads", c.downloads.to_string()),
+            (
+                "Recent downloads",
+                c.recent_downloads.unwrap_or(0).to_string(),
+            ),
+            ("Created", c.created_at.date_naive().to_string()),
+            ("Updated", c.updated_at.date_naive().to_string()),
+        ];
+        for (label, url) in [
+            ("Repository", &c.repository),
+            ("Homepage", &c.homepage),
+            ("Documentation", &c.documentation),
+        ] {
+            if let Some(url) = url {
+                let url = escape_xml(url);
+                facts.push((label, format!("<a href=\"{}\">{}</a>", url, url)));
+            }
+        }
+        body.push_str("<table>\n");
+        for (label, value) in facts {
+            let _ = writeln!(body, "<tr><th>{}</th><td>{}</td></tr>", label, value);
+        }
+        body.push_str("</table>\n");
+    }
+
+    let dates = downloads.iter().map(|d| d.0).collect::<Vec<_>>();
+    let counts = downloads.iter().map(|d| d.1).collect::<Vec<_>>();
+    body.push_str(&chart::render(
+        &dates,
+        &[("daily downloads", &counts)],
+        "daily downloads",
+    ));
+    let rows = downloads
+        .iter()
+        .map(|(date, count)| vec![date.to_string(), (*count as u64).to_string()])
+        .collect::<Vec<_>>();
+    body.push_str(&table(&["Date", "Downloads"], &rows));
+    page(title, &body)
+}
+
+fn crate_link(c: &Crate) -> String {
+    format!(
+        "<a href=\"https://crates.io/crates/{}\">{}</a>",
+        escape_xml(&c.name),
+        escape_xml(&c.name)
+    )
+}
+
+fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut html = String::from("<table>\n<tr>");
+    for header in headers {
+        let _ = write!(html, "<th>{}</th>", header);
+    }
+    html.push_str("</tr>\n");
+    for row in rows {
+        html.push_str("<tr>");
+        for cell in row {
+            let class = if cell.parse::<u64>().is_ok() {
+                " class=\"n\""
+            } else {
+                ""
+            };
+            let _ = write!(html, "<td{}>{}</td>", class, cell);
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n{body}<footer>Generated by crabst on {}</footer>\n</body>\n</html>\n",
+        Utc::now().format("%Y-%m-%d %H:%M UTC"),
+        title = escape_xml(title),
+    )
+}
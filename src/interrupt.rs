@@ -0,0 +1,34 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::Notify;
+
+static CATCHING: AtomicBool = AtomicBool::new(false);
+static INTERRUPT: Notify = Notify::const_new();
+
+/// Handles Ctrl-C for the whole run: it stops the future given to [`catch`]
+/// and exits like the default handler anywhere else.
+pub fn listen() {
+    tokio::spawn(async {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if !CATCHING.swap(false, Ordering::SeqCst) {
+                std::process::exit(130);
+            }
+            INTERRUPT.notify_waiters();
+        }
+    });
+}
+
+/// Runs the future until it completes or Ctrl-C is pressed, `None` when it was
+/// interrupted. A second Ctrl-C exits.
+pub async fn catch<F: Future>(future: F) -> Option<F::Output> {
+    let interrupted = INTERRUPT.notified();
+    tokio::pin!(interrupted);
+    interrupted.as_mut().enable();
+    CATCHING.store(true, Ordering::SeqCst);
+    let output = tokio::select! {
+        output = future => Some(output),
+        _ = interrupted => None,
+    };
+    CATCHING.store(false, Ordering::SeqCst);
+    output
+}
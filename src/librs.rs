@@ -0,0 +1,138 @@
+use crate::api::http_client;
+use crate::cache::Cache;
+use crate::config;
+use crate::error::{Context, Error, Result};
+use reqwest::StatusCode;
+
+pub const LIBRS_URL: &str = "https://lib.rs";
+
+// headings of the section listing similar crates on a crate page
+const RELATED_HEADINGS: [&str; 3] = [">Related crates<", ">See also<", ">Alternatives<"];
+
+/// lib.rs has no api, so its crate pages are read as html.
+#[derive(Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    cache: Option<Cache>,
+    offline: bool,
+}
+
+impl Client {
+    pub fn new() -> Result<Client> {
+        Ok(Client {
+            http: http_client()?,
+            base_url: config::librs_url(),
+            cache: config::cache(),
+            offline: config::offline(),
+        })
+    }
+
+    /// Positions of the crate in its lib.rs categories, like `#7 in Parser implementations`.
+    pub async fn rankings(&self, crate_name: &str) -> Result<Vec<(String, usize)>> {
+        let page = self.crate_page(crate_name).await?;
+        let lines = text_lines(&page);
+        let mut rankings = Vec::new();
+        for (i, line) in lines.iter().enumerate() {
+            let Some((position, category)) = line
+                .strip_prefix('#')
+                .and_then(|rest| rest.split_once(" in"))
+            else {
+                continue;
+            };
+            let Ok(position) = position.parse::<usize>() else {
+                continue;
+            };
+            let category = match category.trim() {
+                "" => lines.get(i + 1).cloned().unwrap_or_default(),
+                category => category.to_string(),
+            };
+            if !category.is_empty() && !rankings.iter().any(|(known, _)| *known == category) {
+                rankings.push((category, position));
+            }
+        }
+        Ok(rankings)
+    }
+
+    /// Crates lib.rs lists as related to the crate, most relevant first.
+    pub async fn related(&self, crate_name: &str) -> Result<Vec<String>> {
+        let page = self.crate_page(crate_name).await?;
+        let Some(start) = RELATED_HEADINGS
+            .iter()
+            .filter_map(|heading| page.find(heading))
+            .min()
+        else {
+            return Ok(Vec::new());
+        };
+        let section = &page[start..];
+        let section = &section[..section.find("</section>").unwrap_or(section.len())];
+        let mut related = Vec::new();
+        for link in section.split("href=\"/crates/").skip(1) {
+            let name = link
+                .split(['"', '/', '#', '?'])
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            if !name.is_empty() && name != crate_name && !related.contains(&name) {
+                related.push(name);
+            }
+        }
+        Ok(related)
+    }
+
+    async fn crate_page(&self, crate_name: &str) -> Result<String> {
+        let key = format!("lib.rs/{}", crate_name);
+        if let Some(page) = self.cache.as_ref().and_then(|cache| cache.get(&key)) {
+            tracing::info!("cache hit {}", key);
+            return Ok(page);
+        }
+        let context = || format!("can not get crate {} from lib.rs", crate_name);
+        if self.offline {
+            return Err(Error::Usage(format!("{}: not cached", context())));
+        }
+        let url = format!(
+            "{}/crates/{}",
+            self.base_url.trim_end_matches('/'),
+            crate_name
+        );
+        tracing::info!("GET {}", url);
+        let response = self.http.get(url).send().await.context(context())?;
+        // lib.rs has not indexed crates published moments ago
+        let page = match response.status() {
+            StatusCode::NOT_FOUND => String::new(),
+            _ => response
+                .error_for_status()
+                .context(context())?
+                .text()
+                .await
+                .context(context())?,
+        };
+        if let Some(cache) = &self.cache {
+            cache.put(&key, &page);
+        }
+        Ok(page)
+    }
+}
+
+// the text of a page with every tag as a line break, trimmed and without blank lines
+fn text_lines(page: &str) -> Vec<String> {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in page.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                text.push('\n');
+            }
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&amp;", "&")
+        .replace("&nbsp;", " ")
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect()
+}
@@ -0,0 +1,80 @@
+// licenses requiring derived works to carry the same license
+const COPYLEFT: [&str; 6] = ["GPL-", "AGPL-", "EUPL-", "OSL-", "CC-BY-SA-", "SSPL-"];
+// licenses requiring it only for the licensed files or library
+const WEAK_COPYLEFT: [&str; 5] = ["LGPL-", "MPL-", "EPL-", "CDDL-", "CECILL-"];
+const PERMISSIVE: [&str; 18] = [
+    "MIT",
+    "MIT-0",
+    "Apache-2.0",
+    "BSD-1-Clause",
+    "BSD-2-Clause",
+    "BSD-3-Clause",
+    "0BSD",
+    "ISC",
+    "Zlib",
+    "Unlicense",
+    "BSL-1.0",
+    "CC0-1.0",
+    "Unicode-DFS-2016",
+    "Unicode-3.0",
+    "BlueOak-1.0.0",
+    "WTFPL",
+    "PSF-2.0",
+    "NCSA",
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Kind {
+    Permissive,
+    WeakCopyleft,
+    Copyleft,
+    Unknown,
+}
+
+impl Kind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Kind::Permissive => "permissive",
+            Kind::WeakCopyleft => "weak copyleft",
+            Kind::Copyleft => "copyleft",
+            Kind::Unknown => "unknown",
+        }
+    }
+}
+
+/// Classifies an SPDX license expression by the most permissive choice it
+/// offers, crates without a license field only point to a license file.
+pub fn classify(expression: Option<&str>) -> Kind {
+    let Some(expression) = expression.filter(|expression| !expression.trim().is_empty()) else {
+        return Kind::Unknown;
+    };
+    let expression = expression.replace(['(', ')'], " ");
+    // crates.io still accepts the old slash separated alternatives
+    expression
+        .split(" OR ")
+        .flat_map(|choice| choice.split('/'))
+        .map(|choice| {
+            choice
+                .split(" AND ")
+                .map(|license| {
+                    let license = license.split(" WITH ").next().unwrap_or_default().trim();
+                    let license = license.trim_end_matches('+');
+                    if PERMISSIVE.contains(&license) {
+                        Kind::Permissive
+                    } else if WEAK_COPYLEFT
+                        .iter()
+                        .any(|prefix| license.starts_with(prefix))
+                    {
+                        Kind::WeakCopyleft
+                    } else if COPYLEFT.iter().any(|prefix| license.starts_with(prefix)) {
+                        Kind::Copyleft
+                    } else {
+                        Kind::Unknown
+                    }
+                })
+                .max()
+                .unwrap_or(Kind::Unknown)
+        })
+        .min()
+        .unwrap_or(Kind::Unknown)
+}
@@ -0,0 +1,85 @@
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+static PROGRESS: AtomicBool = AtomicBool::new(true);
+
+/// Logs crabst's own events to stderr, warnings by default, requests, cache
+/// hits and retries with -v and throttling with -vv. Quiet keeps only errors.
+pub fn init(verbose: u8, quiet: bool) {
+    let level = match (quiet, verbose) {
+        (true, _) => Level::ERROR,
+        (false, 0) => Level::WARN,
+        (false, 1) => Level::INFO,
+        (false, 2) => Level::DEBUG,
+        (false, _) => Level::TRACE,
+    };
+    // the spinner would be drawn over log lines
+    PROGRESS.store(!quiet && verbose == 0, Ordering::Relaxed);
+    let logger = StderrLogger {
+        level,
+        timed: verbose > 0,
+        started: Instant::now(),
+    };
+    let _ = tracing::subscriber::set_global_default(logger);
+}
+
+/// Whether progress spinners should be shown.
+pub fn progress() -> bool {
+    PROGRESS.load(Ordering::Relaxed)
+}
+
+struct StderrLogger {
+    level: Level,
+    timed: bool,
+    started: Instant,
+}
+
+impl Subscriber for StderrLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        *metadata.level() <= self.level && metadata.target().starts_with("crabst")
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut line = String::new();
+        if self.timed {
+            let _ = write!(line, "{:>8.3}s ", self.started.elapsed().as_secs_f64());
+        }
+        line.push_str(match *event.metadata().level() {
+            Level::ERROR => "error: ",
+            Level::WARN => "warning: ",
+            Level::INFO => "info: ",
+            Level::DEBUG => "debug: ",
+            Level::TRACE => "trace: ",
+        });
+        event.record(&mut Line(&mut line));
+        eprintln!("{}", line);
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+struct Line<'a>(&'a mut String);
+
+impl Visit for Line<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        match field.name() {
+            "message" => write!(self.0, "{:?}", value),
+            name => write!(self.0, " {}={:?}", name, value),
+        }
+        .unwrap_or_default()
+    }
+}
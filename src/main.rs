@@ -1,78 +1,403 @@
-use chrono::NaiveDate;
-use comfy_table::{
-    modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL, Cell, CellAlignment, Row, Table,
+mod advisory;
+mod api;
+mod chart;
+mod cli;
+mod config;
+mod digest;
+mod docsrs;
+mod dump;
+mod error;
+mod forecast;
+mod github;
+mod html;
+mod interrupt;
+mod librs;
+mod license;
+mod logging;
+mod manifest;
+mod metrics;
+mod notify;
+mod schedule;
+mod source;
+mod store;
+mod tree;
+mod tui;
+mod typo;
+
+use chrono::{Datelike, Days, NaiveDate, Utc};
+use clap::{CommandFactory, FromArgMatches};
+use cli::{
+    AdoptionArgs, Align, BadgeArgs, BadgeMetric, BenchmarkArgs, CheckNameArgs, Cli, Command,
+    CrateArgs, CrateListArgs, DaemonArgs, DependentsArgs, DependentsGroup, DepsArgs, DiffArgs,
+    DigestArgs, FeedArgs, HistoryArgs, ImportDumpArgs, ListArgs, MeArgs, NotifyArgs, OutputArgs,
+    OutputFormat, Period, ProjectArgs, PushArgs, RelatedArgs, ReportArgs, RowArgs, SearchArgs,
+    SearchSort, ServeArgs, SnapshotArgs, SortKey, StdinArgs, TopArgs, TrendingArgs, TuiArgs,
+    UserArgs, VersionsArgs, WindowArgs,
+};
+use crabst_lib::render::{
+    format_header, render_csv, render_highlighted_table, render_markdown, render_ndjson,
+    render_table, row_objects,
 };
-use crates_io_api::ReverseDependencies;
-use crates_io_api::{AsyncClient, Crate, CratesQueryBuilder, Sort};
-use dotago::Dotago;
+use crabst_lib::stats::{
+    daily_downloads, find_anomalies, get_crate_downloads_multi, median, moving_average, trend,
+};
+use crabst_lib::{cache, semver};
+use crates_io_api::{Crate, CrateDownloads, CrateResponse};
+use crates_io_api::{Meta, ReverseDependencies};
+use error::{crate_not_found, user_not_found, Context, Error, Result};
 use futures::{stream, StreamExt};
-use getopts::Matches;
-use getopts::Options;
 use indicatif::ProgressBar;
 use indicatif::ProgressStyle;
 use itertools::Itertools;
-use rasciigraph::{plot, Config};
-use std::collections::HashMap;
-use std::env;
+use rasciigraph::{plot_many, Config};
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::io::{self, AsyncWriteExt};
+use store::{Snapshot, Store};
+use tokio::io::{self, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
 use tokio::sync::Mutex;
 
 #[tokio::main]
-async fn main() {
-    let args: Vec<String> = env::args().collect();
-    let program = args[0].clone();
-
-    let mut opts = Options::new();
-    opts.optopt(
-        "c",
-        "crate",
-        "get single crate download statistics",
-        "CRATE",
-    );
-    opts.optopt(
-        "d",
-        "dependents",
-        "get crate dependents inpormation",
-        "CRATE DEPENDENTS",
-    );
-    opts.optopt("u", "user", "get user download statistics", "USER");
-    opts.optopt("o", "output", "output format g: graph t: table", "OUTPUT");
-    opts.optopt("l", "last", "show last n days output", "LAST");
-    opts.optflag("h", "help", "print this help menu");
+async fn main() -> ExitCode {
+    let mut config = match config::Config::load() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return err.exit_code();
+        }
+    };
+    let matches = config.apply_defaults(Cli::command()).get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    config.apply_window(&mut cli);
+    config.apply_network(&cli);
+    config::set(config);
+    logging::init(cli.logging.verbose, cli.logging.quiet);
+    interrupt::listen();
+
+    let Some(minutes) = cli.output.watch else {
+        return match run(&cli).await {
+            Ok(()) => {
+                print_offline_age();
+                ExitCode::SUCCESS
+            }
+            Err(err) => {
+                print_error(&err).await;
+                err.exit_code()
+            }
+        };
+    };
+    loop {
+        if std::io::stdout().is_terminal() {
+            print!("\x1b[2J\x1b[H");
+        }
+        match run(&cli).await {
+            Err(err @ (Error::Usage(_) | Error::Interrupted(_))) => {
+                eprintln!("error: {}", err);
+                return err.exit_code();
+            }
+            Err(err) => print_error(&err).await,
+            Ok(()) => print_offline_age(),
+        }
+        println!(
+            "Last refresh {}, next in {} minutes, press Ctrl-C to stop",
+            chrono::Local::now().format("%H:%M:%S"),
+            minutes
+        );
+        tokio::time::sleep(Duration::from_secs(minutes * 60)).await;
+    }
+}
 
-    let matches = match opts.parse(&args[1..]) {
-        Ok(m) => m,
-        Err(_) => {
-            panic!("failed to read program arguments")
+async fn print_error(err: &Error) {
+    eprintln!("error: {}", err);
+    if let Error::CrateNotFound(crate_name) = err {
+        let suggestions = crate_suggestions(crate_name).await;
+        if !suggestions.is_empty() {
+            eprintln!("did you mean {}?", suggestions.join(", "));
         }
+    }
+}
+
+async fn crate_suggestions(crate_name: &str) -> Vec<String> {
+    let Ok(client) = new_client() else {
+        return Vec::new();
     };
+    let mut queries = vec![crate_name.to_string()];
+    if crate_name.chars().count() > 2 {
+        let half = crate_name.chars().count().div_ceil(2);
+        queries.push(crate_name.chars().take(half).collect());
+    }
+    let mut candidates = BTreeSet::new();
+    for query in queries {
+        if let Ok(page) = client.search_crates(&query).await {
+            candidates.extend(page.crates.into_iter().map(|c| c.name));
+        }
+    }
+    let max_distance = (crate_name.chars().count() / 3).max(2);
+    candidates
+        .into_iter()
+        .map(|name| (edit_distance(crate_name, &name), name))
+        .filter(|(distance, name)| *distance <= max_distance && name != crate_name)
+        .sorted()
+        .take(3)
+        .map(|(_, name)| name)
+        .collect()
+}
 
-    if matches.opt_present("h") {
-        print_usage(&program, opts).await;
-        return;
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
     }
+    previous[b.len()]
+}
 
-    if matches.opt_present("c") {
-        handle_crate_option(&matches).await;
-    } else if matches.opt_present("u") {
-        handle_user_option(&matches).await;
-    } else if matches.opt_present("d") {
-        handle_dependents_option(&matches).await;
-    } else {
-        print_usage(&program, opts).await;
+fn print_offline_age() {
+    let Some(age) = cache::oldest_hit().filter(|_| config::offline()) else {
+        return;
+    };
+    let minutes = age.as_secs() / 60;
+    let age = match minutes {
+        0..=59 => format!("{}m", minutes),
+        60..=1439 => format!("{}h {}m", minutes / 60, minutes % 60),
+        _ => format!("{}d {}h", minutes / 1440, minutes % 1440 / 60),
+    };
+    tracing::warn!("offline: showing cached crates.io data up to {} old", age);
+}
+
+async fn run(cli: &Cli) -> Result<()> {
+    match &cli.command {
+        Command::Crate(args) => {
+            let mut crate_names = Vec::new();
+            for name in &args.names {
+                crate_names.extend(read_targets(name.clone()).await?);
+            }
+            if args.group_by == Some(Period::Major) && !args.by_version {
+                return Err(Error::Usage(
+                    "--group-by major works with --by-version".to_string(),
+                ));
+            }
+            if args.dependencies {
+                for crate_name in crate_names {
+                    handle_dependencies_command(args, &cli.output, crate_name).await?;
+                }
+                return Ok(());
+            }
+            match crate_names.len() {
+                0 => return Err(Error::Usage("no crate names given".to_string())),
+                1 => handle_crate_command(args, &cli.output, crate_names.remove(0)).await?,
+                _ => handle_crate_comparison(args, &cli.output, &crate_names).await?,
+            }
+        }
+        Command::User(args) => {
+            let mut user_names = Vec::new();
+            for name in &args.names {
+                user_names.extend(read_targets(name.clone()).await?);
+            }
+            if user_names.is_empty() {
+                return Err(Error::Usage("no user names given".to_string()));
+            }
+            handle_user_command(args, &cli.output, user_names).await?;
+        }
+        Command::Me(args) => handle_me_command(args, &cli.output).await?,
+        Command::List(args) => {
+            for list_name in read_targets(args.name.clone()).await? {
+                handle_list_command(args, &cli.output, list_name).await?;
+            }
+        }
+        Command::Project(args) => handle_project_command(args, &cli.output).await?,
+        Command::Stdin(args) => handle_stdin_command(args, &cli.output).await?,
+        Command::Owners(args) => {
+            for crate_name in read_targets(args.name.clone()).await? {
+                handle_owners_command(&cli.output, crate_name).await?;
+            }
+        }
+        Command::Deps(args) => handle_deps_command(args, &cli.output).await?,
+        Command::Dependents(args) => match &args.name {
+            Some(name) => {
+                for crate_name in read_targets(name.clone()).await? {
+                    handle_dependents_command(args, &cli.output, crate_name).await?;
+                }
+            }
+            None => handle_dependents_comparison(args, &cli.output).await?,
+        },
+        Command::Diff(args) => {
+            for crate_name in read_targets(args.name.clone()).await? {
+                handle_diff_command(args, &cli.output, crate_name).await?;
+            }
+        }
+        Command::History(args) => {
+            for crate_name in read_targets(args.name.clone()).await? {
+                handle_history_command(args, &cli.output, crate_name).await?;
+            }
+        }
+        Command::ImportDump(args) => handle_import_dump_command(args).await?,
+        Command::Tui(args) => handle_tui_command(args).await?,
+        Command::Serve(args) => handle_serve_command(args).await?,
+        Command::Push(args) => handle_push_command(args).await?,
+        Command::Notify(args) => handle_notify_command(args).await?,
+        Command::Digest(args) => handle_digest_command(args).await?,
+        Command::Daemon(args) => handle_daemon_command(args).await?,
+        Command::Badge(args) => handle_badge_command(args, &cli.output).await?,
+        Command::Pattern(args) => {
+            for crate_name in read_targets(args.name.clone()).await? {
+                handle_pattern_command(&cli.output, crate_name).await?;
+            }
+        }
+        Command::Adoption(args) => handle_adoption_command(args, &cli.output).await?,
+        Command::Benchmark(args) => {
+            for crate_name in read_targets(args.name.clone()).await? {
+                handle_benchmark_command(args, &cli.output, crate_name).await?;
+            }
+        }
+        Command::Search(args) => handle_search_command(args, &cli.output).await?,
+        Command::Top(args) => handle_top_command(args, &cli.output).await?,
+        Command::Trending(args) => handle_trending_command(args, &cli.output).await?,
+        Command::New(args) => handle_feed_command(args, &cli.output, false).await?,
+        Command::Updated(args) => handle_feed_command(args, &cli.output, true).await?,
+        Command::CheckName(args) => handle_check_name_command(args, &cli.output).await?,
+        Command::Health(args) => {
+            for crate_name in read_targets(args.name.clone()).await? {
+                handle_health_command(&cli.output, crate_name).await?;
+            }
+        }
+        Command::Duplicates(args) => {
+            for crate_name in read_targets(args.name.clone()).await? {
+                handle_duplicates_command(&cli.output, crate_name).await?;
+            }
+        }
+        Command::Licenses(args) => {
+            for crate_name in read_targets(args.name.clone()).await? {
+                handle_licenses_command(&cli.output, crate_name).await?;
+            }
+        }
+        Command::Related(args) => {
+            for crate_name in read_targets(args.name.clone()).await? {
+                handle_related_command(args, &cli.output, crate_name).await?;
+            }
+        }
+        Command::Advisories(args) => {
+            for crate_name in read_targets(args.name.clone()).await? {
+                handle_advisories_command(&cli.output, crate_name).await?;
+            }
+        }
+        Command::Typosquat(args) => {
+            for crate_name in read_targets(args.name.clone()).await? {
+                handle_typosquat_command(&cli.output, crate_name).await?;
+            }
+        }
+        Command::Cadence(args) => {
+            let mut crate_names = Vec::new();
+            for name in &args.names {
+                crate_names.extend(read_targets(name.clone()).await?);
+            }
+            handle_cadence_command(&cli.output, crate_names).await?;
+        }
+        Command::Versions(args) => {
+            for crate_name in read_targets(args.name.clone()).await? {
+                handle_versions_command(args, &cli.output, crate_name).await?;
+            }
+        }
+        Command::Snapshot(args) => {
+            let mut crate_names = Vec::new();
+            for name in &args.names {
+                crate_names.extend(read_targets(name.clone()).await?);
+            }
+            handle_snapshot_command(args, &cli.output, crate_names).await?;
+        }
     }
+    Ok(())
 }
 
-async fn handle_dependents_option(matches: &Matches) {
-    let crate_name = matches
-        .opt_str("d")
-        .expect("user did not supplied crate argument");
+async fn read_targets(target: String) -> Result<Vec<String>> {
+    if target != "-" {
+        return Ok(vec![target]);
+    }
+    let mut targets = Vec::new();
+    let mut lines = BufReader::new(io::stdin()).lines();
+    while let Some(line) = lines.next_line().await.context("can not read stdin")? {
+        let line = line.trim();
+        if !line.is_empty() && !line.starts_with('#') {
+            targets.push(line.to_string());
+        }
+    }
+    Ok(targets)
+}
 
-    let client = AsyncClient::new("crabst stats agent", std::time::Duration::from_millis(100))
-        .expect("can not get client");
+// crate names as plain lines, a csv with a name or crate column, or json strings and objects
+fn parse_crate_names(input: &str) -> Result<Vec<String>> {
+    const NAME_KEYS: [&str; 4] = ["name", "crate", "crate_name", "crate name"];
+    let input = input.trim();
+    let names = if input.starts_with('[') || input.starts_with('{') {
+        let values = match serde_json::from_str::<serde_json::Value>(input) {
+            Ok(serde_json::Value::Array(values)) => values,
+            // crabst json output lists its crates under a key
+            Ok(serde_json::Value::Object(object)) if object.contains_key("crates") => {
+                object["crates"].as_array().cloned().unwrap_or_default()
+            }
+            Ok(value) => vec![value],
+            Err(_) => input
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(serde_json::from_str)
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|err| Error::Usage(format!("can not parse json from stdin: {}", err)))?,
+        };
+        values
+            .iter()
+            .filter_map(|value| match value {
+                serde_json::Value::String(name) => Some(name.clone()),
+                value => NAME_KEYS
+                    .iter()
+                    .find_map(|key| value[key].as_str())
+                    .or_else(|| value["crate"]["name"].as_str())
+                    .map(str::to_string),
+            })
+            .collect::<Vec<_>>()
+    } else {
+        let records = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .comment(Some(b'#'))
+            .from_reader(input.as_bytes())
+            .into_records()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context("can not parse csv from stdin")?;
+        let column = records.first().and_then(|header| {
+            header
+                .iter()
+                .position(|field| NAME_KEYS.contains(&field.to_lowercase().as_str()))
+        });
+        let (rows, column) = match column {
+            Some(column) => (&records[1..], column),
+            None => (&records[..], 0),
+        };
+        rows.iter()
+            .filter_map(|record| record.get(column))
+            .map(str::to_string)
+            .collect()
+    };
+    Ok(names
+        .into_iter()
+        .filter(|name| !name.is_empty())
+        .unique()
+        .collect())
+}
 
+fn new_spinner() -> ProgressBar {
+    if !logging::progress() {
+        return ProgressBar::hidden();
+    }
     let pb = ProgressBar::new_spinner();
     pb.set_style(
         ProgressStyle::with_template("{spinner:.blue} {msg}")
@@ -87,317 +412,5582 @@ async fn handle_dependents_option(matches: &Matches) {
                 "▪▪▪▪▪",
             ]),
     );
-    pb.set_message(format!("Fetching crate {} dependent infos...", &crate_name));
-    pb.enable_steady_tick(Duration::from_millis(500));
-    let dependents = client
-        .crate_reverse_dependencies(&crate_name)
-        .await
-        .expect("can not retrieve crate dependents");
-    pb.finish_with_message(format!("fetched {} crate dependents", &crate_name));
-
-    print_crate_dependents(&dependents).await;
+    pb
 }
 
-async fn handle_user_option(matches: &Matches) {
-    // let today = Utc::now();
-    // let today_naive = NaiveDate::from_ymd_opt(today.year(), today.month(), today.day())
-    //     .expect("Invalid date value");
+fn new_client() -> Result<api::Client> {
+    api::client()
+}
 
-    let user_name = matches
-        .opt_str("u")
-        .expect("user did not supply user argument");
+async fn get_user_crates(
+    client: &api::Client,
+    user_id: u64,
+    user_name: &str,
+) -> Result<Vec<Crate>> {
+    let mut crates = Vec::new();
+    for page in 1.. {
+        let crates_page = client
+            .user_crates(user_id, page)
+            .await
+            .context(format!("can not get crates of user {}", user_name))?;
+        let page_len = crates_page.crates.len();
+        crates.extend(crates_page.crates);
+        if page_len < 100 || crates.len() as u64 >= crates_page.meta.total {
+            break;
+        }
+    }
+    Ok(crates)
+}
 
-    let client = AsyncClient::new("crabst stats agent", std::time::Duration::from_millis(100))
-        .expect("can not get client");
+async fn handle_dependents_command(
+    args: &DependentsArgs,
+    output: &OutputArgs,
+    crate_name: String,
+) -> Result<()> {
+    let client = new_client()?;
+    let filtered = args.yanked || args.behind.is_some();
+    let tree = args.depth > 1 || matches!(output.output, OutputFormat::Dot | OutputFormat::Mermaid);
+    if tree && (filtered || args.impact || args.group_by.is_some() || args.changes) {
+        return Err(Error::Usage(
+            "--yanked, --behind, --impact, --group-by and --changes work with direct dependents, without --depth or graph output"
+                .to_string(),
+        ));
+    }
+    if args.changes && (filtered || args.group_by.is_some() || args.impact) {
+        return Err(Error::Usage(
+            "--changes can not be combined with --yanked, --behind, --group-by or --impact"
+                .to_string(),
+        ));
+    }
+    if tree {
+        return print_dependents_tree(&client, args, output, crate_name).await;
+    }
+    let api_crate = match filtered {
+        true => Some(
+            client
+                .get_crate(&crate_name)
+                .await
+                .map_err(crate_not_found(&crate_name))?,
+        ),
+        false => None,
+    };
+    let behind = match (&args.behind, &api_crate) {
+        (Some(version), Some(api_crate)) => {
+            let version = match version.as_str() {
+                "latest" => default_version(&api_crate.crate_data),
+                version => version.to_string(),
+            };
+            Some(
+                semver::Version::parse(&version)
+                    .ok_or_else(|| Error::Usage(format!("invalid version `{}`", version)))?,
+            )
+        }
+        _ => None,
+    };
+    let versions = api_crate
+        .map(|api_crate| api_crate.versions)
+        .unwrap_or_default();
 
-    let user = client
-        .user(&user_name)
-        .await
-        .expect("can not get user information from crates.io");
+    let mut dependents = fetch_dependents(&client, &crate_name).await?;
+    if args.changes {
+        return print_dependent_changes(args, &dependents.dependencies, output, &crate_name).await;
+    }
+    if args.yanked {
+        dependents
+            .dependencies
+            .retain(|rd| yanked_only(&rd.dependency.req, &versions));
+    }
+    if let Some(behind) = &behind {
+        dependents.dependencies.retain(|rd| {
+            semver::VersionReq::parse(&rd.dependency.req).is_some_and(|req| !req.matches(behind))
+        });
+        dependents
+            .dependencies
+            .sort_by_key(|rd| std::cmp::Reverse(rd.dependency.downloads));
+    }
+    if args.group_by == Some(DependentsGroup::Owner) {
+        if args.impact {
+            return Err(Error::Usage(
+                "--impact can not be combined with --group-by".to_string(),
+            ));
+        }
+        return print_dependents_by_owner(&client, &dependents.dependencies, args, output).await;
+    }
+    let impact = dependents_impact(&dependents.dependencies);
+    select_rows(
+        &mut dependents.dependencies,
+        &args.rows,
+        |rd| rd.crate_version.crate_name.clone(),
+        |rd, _| rd.dependency.downloads,
+    );
 
-    let crates = client
-        .crates(
-            CratesQueryBuilder::new()
-                .page_size(100)
-                .sort(Sort::Alphabetical)
-                .user_id(user.id)
-                .build(),
+    if output.output == OutputFormat::Json {
+        let mut json = json!({
+            "crate": crate_name,
+            "total": dependents.meta.total,
+            "dependents": dependents
+                .dependencies
+                .iter()
+                .map(|rd| {
+                    json!({
+                        "name": rd.crate_version.crate_name,
+                        "version": rd.crate_version.num,
+                        "downloads": rd.dependency.downloads,
+                        "req": rd.dependency.req,
+                        "kind": rd.dependency.kind,
+                        "optional": rd.dependency.optional,
+                    })
+                })
+                .collect::<Vec<_>>(),
+        });
+        if args.impact {
+            json["impact"] = json!({
+                "dependents": impact.dependents,
+                "downloads": impact.downloads,
+                "top_share": (impact.top_share * 1000.0).round() / 1000.0,
+                "gini": (impact.gini * 1000.0).round() / 1000.0,
+            });
+        }
+        write_json(output, &json).await
+    } else if args.impact {
+        let rows = vec![
+            vec!["Dependents".to_string(), impact.dependents.to_string()],
+            vec![
+                "Dependent Downloads".to_string(),
+                impact.downloads.to_string(),
+            ],
+            vec![
+                format!("Top {} Share", IMPACT_TOP),
+                format!("{:.1}%", impact.top_share * 100.0),
+            ],
+            vec!["Gini".to_string(), format!("{:.2}", impact.gini)],
+        ];
+        print_rows(
+            output,
+            vec!["Metric".to_string(), "Value".to_string()],
+            rows,
         )
         .await
-        .expect("can not get users crates");
+    } else {
+        print_crate_dependents(&dependents, output).await
+    }
+}
 
-    // let crate_daily_downloads: Arc<Mutex<HashMap<String, u64>>> =
-    //     Arc::new(Mutex::new(HashMap::new()));
-    let crate_n_day_downloads: Arc<Mutex<HashMap<String, HashMap<NaiveDate, u64>>>> =
-        Arc::new(Mutex::new(HashMap::new()));
+async fn print_dependent_changes(
+    args: &DependentsArgs,
+    dependents: &[crates_io_api::ReverseDependency],
+    output: &OutputArgs,
+    crate_name: &str,
+) -> Result<()> {
+    let mut store = Store::open(args.store.db.as_deref())?;
+    let downloads = dependents
+        .iter()
+        .map(|rd| (rd.crate_version.crate_name.clone(), rd.dependency.downloads))
+        .collect::<HashMap<_, _>>();
+    let current = downloads.keys().cloned().collect::<BTreeSet<_>>();
+    let previous = store.latest_dependents(crate_name)?;
+    store.insert_dependents(crate_name, Utc::now(), &current)?;
+    let Some((taken_at, previous)) = previous else {
+        tracing::warn!(
+            "stored {} dependents of {}, run again later to see changes",
+            current.len(),
+            crate_name
+        );
+        return Ok(());
+    };
 
-    let last_n_day = if matches.opt_present("l") {
-        matches
-            .opt_get("l")
-            .expect("number of days not defined")
-            .expect("user forget to deefine number of days")
-    } else {
-        1
-    };
-    let mut days = Vec::new();
-    for i in 0..last_n_day {
-        days.push(
-            i.days()
-                .ago()
-                .as_date()
-                .expect("undefined date")
-                .naive_utc()
-                .date(),
+    let mut changes = current
+        .difference(&previous)
+        .map(|name| ("new", name.clone(), downloads.get(name).copied()))
+        .chain(
+            previous
+                .difference(&current)
+                .map(|name| ("lost", name.clone(), None)),
         )
+        .collect::<Vec<_>>();
+    select_rows(
+        &mut changes,
+        &args.rows,
+        |change| change.1.clone(),
+        |change, _| change.2.unwrap_or(0),
+    );
+    if output.output == OutputFormat::Json {
+        let json = json!({
+            "crate": crate_name,
+            "since": taken_at,
+            "new": changes
+                .iter()
+                .filter(|change| change.0 == "new")
+                .map(|change| json!({"name": change.1, "downloads": change.2}))
+                .collect::<Vec<_>>(),
+            "lost": changes
+                .iter()
+                .filter(|change| change.0 == "lost")
+                .map(|change| &change.1)
+                .collect::<Vec<_>>(),
+        });
+        return write_json(output, &json).await;
     }
-    days.reverse();
+    if changes.is_empty() {
+        println!(
+            "No dependent changes since {}",
+            taken_at.format("%Y-%m-%d %H:%M")
+        );
+        return Ok(());
+    }
+    let rows = changes
+        .into_iter()
+        .map(|(change, name, downloads)| {
+            vec![
+                change.to_string(),
+                name,
+                downloads.map_or(String::new(), |count| count.to_string()),
+            ]
+        })
+        .collect();
+    let headers = ["Change", "Crate Name", "Download Count"]
+        .iter()
+        .map(|header| header.to_string())
+        .collect();
+    print_rows(output, headers, rows).await
+}
 
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::with_template("{spinner:.blue} {msg}")
-            .unwrap()
-            .tick_strings(&[
-                "▹▹▹▹▹",
-                "▸▹▹▹▹",
-                "▹▸▹▹▹",
-                "▹▹▸▹▹",
-                "▹▹▹▸▹",
-                "▹▹▹▹▸",
-                "▪▪▪▪▪",
-            ]),
-    );
-    pb.set_message("Fetching crates infos...");
-    let download_futures = stream::iter(crates.crates.clone())
-        .map(|crate_info| {
+async fn print_dependents_by_owner(
+    client: &api::Client,
+    dependents: &[crates_io_api::ReverseDependency],
+    args: &DependentsArgs,
+    output: &OutputArgs,
+) -> Result<()> {
+    const LISTED_CRATES: usize = 5;
+    let mut downloads: BTreeMap<String, u64> = BTreeMap::new();
+    for rd in dependents {
+        downloads.insert(rd.crate_version.crate_name.clone(), rd.dependency.downloads);
+    }
+    let pb = new_spinner();
+    pb.set_message(format!(
+        "Fetching owners of {} dependents...",
+        downloads.len()
+    ));
+    pb.enable_steady_tick(Duration::from_millis(500));
+    let owners = stream::iter(downloads.keys().cloned())
+        .map(|crate_name| {
             let client = client.clone();
-            let n_daily_downloads = crate_n_day_downloads.clone();
-            let inner_pb = pb.clone();
-            let days_clone = days.clone();
-            tokio::spawn(async move {
-                let download_count =
-                    get_crate_downloads_multi(&client, &crate_info.name, &days_clone).await;
-                n_daily_downloads
-                    .lock()
+            async move {
+                let owners = client
+                    .crate_owners(&crate_name)
                     .await
-                    .insert(crate_info.name.clone(), download_count);
-                inner_pb.set_message(format!("Fetching {} info...", crate_info.name));
-                inner_pb.tick();
-            })
+                    .map_err(crate_not_found(&crate_name))?;
+                Ok::<_, Error>((crate_name, owners))
+            }
         })
-        .buffer_unordered(3);
-    download_futures.collect::<Vec<_>>().await;
-    pb.finish_with_message("Finished gathering crate info!");
+        .buffer_unordered(config::concurrency())
+        .collect::<Vec<_>>()
+        .await;
+    pb.finish_and_clear();
 
-    let mut output_type: Option<String> = None;
-    if matches.opt_present("o") {
-        output_type = matches.opt_str("o")
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (crate_name, owners) in owners.into_iter().collect::<Result<Vec<_>>>()? {
+        for owner in owners {
+            groups
+                .entry(owner.login)
+                .or_default()
+                .push(crate_name.clone());
+        }
     }
+    let mut groups = groups
+        .into_iter()
+        .map(|(owner, mut crates)| {
+            crates.sort_by_key(|name| std::cmp::Reverse(downloads[name]));
+            let total = crates.iter().map(|name| downloads[name]).sum::<u64>();
+            (owner, crates, total)
+        })
+        .collect::<Vec<_>>();
+    groups.sort_by_key(|group| std::cmp::Reverse(group.2));
+    select_rows(
+        &mut groups,
+        &args.rows,
+        |group| group.0.clone(),
+        |group, _| group.2,
+    );
 
-    if output_type.unwrap_or_else(|| "t".to_string()) == *"g" {
-        todo!("implement graph output")
-    } else {
-        print_crates_table(
-            &crates.crates,
-            &crate_n_day_downloads.lock().await.clone(),
-            &days,
-        )
-        .await;
+    if output.output == OutputFormat::Json {
+        let json = groups
+            .iter()
+            .map(|(owner, crates, total)| {
+                json!({"owner": owner, "downloads": total, "dependents": crates})
+            })
+            .collect::<Vec<_>>();
+        return write_json(output, &json!(json)).await;
     }
+    let rows = groups
+        .iter()
+        .map(|(owner, crates, total)| {
+            let mut listed = crates
+                .iter()
+                .take(LISTED_CRATES)
+                .cloned()
+                .collect::<Vec<_>>();
+            if crates.len() > LISTED_CRATES {
+                listed.push(format!("+{} more", crates.len() - LISTED_CRATES));
+            }
+            vec![
+                owner.clone(),
+                crates.len().to_string(),
+                total.to_string(),
+                listed.join(", "),
+            ]
+        })
+        .collect();
+    let headers = ["Owner", "Dependents", "Download Count", "Crates"]
+        .iter()
+        .map(|header| header.to_string())
+        .collect();
+    print_rows(output, headers, rows).await
 }
 
-async fn handle_crate_option(matches: &Matches) {
-    let crate_name = matches
-        .opt_str("c")
-        .expect("user did not supplied crate argument");
+const IMPACT_TOP: usize = 10;
 
-    let client = AsyncClient::new("stats agent", std::time::Duration::from_millis(100))
-        .expect("can not get client");
+struct Impact {
+    dependents: usize,
+    downloads: u64,
+    top_share: f64,
+    gini: f64,
+}
 
-    let crate_downloads = client.crate_downloads(&crate_name).await;
-    // .expect("can not get crate downloads");
-    let api_crate = client
-        .get_crate(&crate_name)
-        .await
-        .expect("can not get detailed information about crate from api");
-    match crate_downloads {
-        Ok(downloads) => {
-            let mut version_downloads = Vec::new();
-            for (key, group) in &downloads.version_downloads.iter().group_by(|&vd| vd.date) {
-                let all_version_downloads = group.fold(0, |init, gvd| init + gvd.downloads);
-                version_downloads.push((key, all_version_downloads as f64));
-            }
-            let dc = version_downloads.iter().map(|vd| vd.1).collect::<Vec<_>>();
-
-            let mut output_type: Option<String> = None;
-            if matches.opt_present("o") {
-                output_type = matches.opt_str("o")
-            }
-
-            if output_type.unwrap_or_else(|| "t".to_string()) == "g" {
-                println!(
-                    "{}",
-                    plot(
-                        dc,
-                        Config::default()
-                            .with_offset(10)
-                            .with_height(10)
-                            .with_caption(format!(
-                                "{} total downloads {}",
-                                &crate_name, api_crate.crate_data.downloads
-                            ))
-                    )
-                )
-            } else {
-                print_downloads_table(
-                    &version_downloads
-                        .iter()
-                        .map(|t| (format!("{}", t.0), t.1))
-                        .collect::<Vec<(String, f64)>>(),
-                    api_crate.crate_data.downloads,
-                )
-                .await;
-            }
-        }
-        Err(_) => println!("Failed to get downloads"),
+fn dependents_impact(dependents: &[crates_io_api::ReverseDependency]) -> Impact {
+    let mut per_crate: HashMap<&str, u64> = HashMap::new();
+    for rd in dependents {
+        per_crate.insert(&rd.crate_version.crate_name, rd.dependency.downloads);
+    }
+    let mut downloads = per_crate.into_values().collect::<Vec<_>>();
+    downloads.sort_unstable();
+    let total = downloads.iter().sum::<u64>();
+    let top = downloads.iter().rev().take(IMPACT_TOP).sum::<u64>();
+    let n = downloads.len() as f64;
+    let weighted = downloads
+        .iter()
+        .enumerate()
+        .map(|(i, count)| (i + 1) as f64 * *count as f64)
+        .sum::<f64>();
+    let gini = match total {
+        0 => 0.0,
+        total => 2.0 * weighted / (n * total as f64) - (n + 1.0) / n,
+    };
+    Impact {
+        dependents: downloads.len(),
+        downloads: total,
+        top_share: top as f64 / total.max(1) as f64,
+        gini,
     }
 }
 
-async fn print_downloads_table(downloads: &[(String, f64)], total: u64) {
-    let mut table = Table::new();
-    table
-        .load_preset(UTF8_FULL)
-        .apply_modifier(UTF8_ROUND_CORNERS)
-        .set_header(vec!["Date", "Download Count"]);
+async fn fetch_dependents(client: &api::Client, crate_name: &str) -> Result<ReverseDependencies> {
+    let pb = new_spinner();
+    pb.set_message(format!("Fetching crate {} dependent infos...", crate_name));
+    pb.enable_steady_tick(Duration::from_millis(500));
+    let mut dependents = ReverseDependencies {
+        dependencies: Vec::new(),
+        meta: Meta { total: 0 },
+    };
+    for page_number in 1.. {
+        let page = client
+            .crate_reverse_dependencies_page(crate_name, page_number)
+            .await
+            .map_err(crate_not_found(crate_name))?;
+        if page.dependencies.is_empty() {
+            break;
+        }
+        dependents.dependencies.extend(page.dependencies);
+        dependents.meta.total = page.meta.total;
+        pb.set_message(format!(
+            "Fetching crate {} dependent infos {}/{}...",
+            crate_name,
+            dependents.dependencies.len(),
+            dependents.meta.total
+        ));
+        if dependents.dependencies.len() as u64 >= dependents.meta.total {
+            break;
+        }
+    }
+    pb.finish_with_message(format!(
+        "fetched {} crate dependents of {}",
+        dependents.dependencies.len(),
+        crate_name
+    ));
+    Ok(dependents)
+}
 
-    let table_rows = downloads.iter().map(|c| {
-        Row::from(vec![
-            Cell::new(c.0.clone()),
-            Cell::new(c.1).set_alignment(CellAlignment::Right),
-        ])
+async fn handle_dependents_comparison(args: &DependentsArgs, output: &OutputArgs) -> Result<()> {
+    let [first, second] = &args.compare[..] else {
+        return Err(Error::Usage(
+            "--compare needs exactly two crate names".to_string(),
+        ));
+    };
+    let client = new_client()?;
+    let mut downloads: BTreeMap<String, u64> = BTreeMap::new();
+    let mut names = Vec::new();
+    for crate_name in [first, second] {
+        let dependents = fetch_dependents(&client, crate_name).await?;
+        let mut dependent_names = BTreeSet::new();
+        for rd in dependents.dependencies {
+            downloads.insert(rd.crate_version.crate_name.clone(), rd.dependency.downloads);
+            dependent_names.insert(rd.crate_version.crate_name);
+        }
+        names.push(dependent_names);
+    }
+    let group = |name: &String| match (names[0].contains(name), names[1].contains(name)) {
+        (true, true) => "both",
+        (true, false) => first.as_str(),
+        _ => second.as_str(),
+    };
+    let mut rows = downloads
+        .iter()
+        .map(|(name, count)| (name.clone(), *count, group(name)))
+        .collect::<Vec<_>>();
+    rows.sort_by_key(|row| {
+        let rank = match row.2 {
+            "both" => 0,
+            group if group == first => 1,
+            _ => 2,
+        };
+        (rank, std::cmp::Reverse(row.1))
     });
-    for row in table_rows {
-        table.add_row(row);
+    select_rows(&mut rows, &args.rows, |row| row.0.clone(), |row, _| row.1);
+
+    if output.output == OutputFormat::Json {
+        let list = |group: &str| {
+            rows.iter()
+                .filter(|row| row.2 == group)
+                .map(|row| json!({"name": row.0, "downloads": row.1}))
+                .collect::<Vec<_>>()
+        };
+        let json = json!({
+            "crates": [first, second],
+            "both": list("both"),
+            "only": {first.as_str(): list(first), second.as_str(): list(second)},
+        });
+        return write_json(output, &json).await;
     }
-    table.add_row(vec![
-        Cell::new("Total"),
-        Cell::new(total).set_alignment(CellAlignment::Right),
+    let shared = names[0].intersection(&names[1]).count();
+    let mut table = rows
+        .into_iter()
+        .map(|(name, count, group)| vec![name, count.to_string(), group.to_string()])
+        .collect::<Vec<_>>();
+    table.push(vec![
+        format!(
+            "Total ({} both, {} {} only, {} {} only)",
+            shared,
+            names[0].len() - shared,
+            first,
+            names[1].len() - shared,
+            second
+        ),
+        String::new(),
+        String::new(),
     ]);
-    let mut stdout = io::stdout();
-    let _ = stdout.write_all(table.to_string().as_bytes()).await;
+    let headers = ["Crate Name", "Download Count", "Depends On"]
+        .iter()
+        .map(|header| header.to_string())
+        .collect();
+    print_rows(output, headers, table).await
 }
 
-async fn print_crates_table(
-    crates: &[Crate],
-    daily_downloads: &HashMap<String, HashMap<NaiveDate, u64>>,
-    days: &Vec<NaiveDate>,
-) {
-    let mut table = Table::new();
-    let mut header_vec = vec!["Crate Name".to_owned(), "Download Count".to_owned()];
-    for date in days {
-        header_vec.push(date.format("%Y-%m-%d").to_string())
-    }
+fn yanked_only(req: &str, versions: &[crates_io_api::Version]) -> bool {
+    let Some(req) = semver::VersionReq::parse(req) else {
+        return false;
+    };
+    let matching = versions
+        .iter()
+        .filter(|version| {
+            semver::Version::parse(&version.num).is_some_and(|version| req.matches(&version))
+        })
+        .collect::<Vec<_>>();
+    !matching.is_empty() && matching.iter().all(|version| version.yanked)
+}
 
-    let mut default_zero_hash = HashMap::new();
-    for day in days {
-        default_zero_hash.insert(*day, 0);
-    }
+async fn handle_owners_command(output: &OutputArgs, crate_name: String) -> Result<()> {
+    let client = new_client()?;
+    let owners = client
+        .crate_owners(&crate_name)
+        .await
+        .map_err(crate_not_found(&crate_name))?;
 
-    table
-        .load_preset(UTF8_FULL)
-        .apply_modifier(UTF8_ROUND_CORNERS)
-        .set_header(header_vec);
-    let table_rows = crates.iter().map(|c| {
-        let mut cell_vec = vec![
-            Cell::new(c.name.clone()),
-            Cell::new(c.downloads.to_string()).set_alignment(CellAlignment::Right),
-        ];
-        for day in days {
-            cell_vec.push(
-                Cell::new(
-                    *daily_downloads
-                        .get(&c.name)
-                        .unwrap_or(&default_zero_hash)
-                        .get(day)
-                        .unwrap_or(&0),
-                )
-                .set_alignment(CellAlignment::Right),
-            )
-        }
-        Row::from(cell_vec)
-    });
-    for row in table_rows {
-        table.add_row(row);
+    let github_handle = |owner: &crates_io_api::User| match owner.login.strip_prefix("github:") {
+        Some(team) => format!("@{}", team.replacen(':', "/", 1)),
+        None => format!("@{}", owner.login),
+    };
+    let kind = |owner: &crates_io_api::User| owner.kind.clone().unwrap_or("user".to_string());
+
+    if output.output == OutputFormat::Json {
+        let json = json!({
+            "crate": crate_name,
+            "owners": owners
+                .iter()
+                .map(|owner| {
+                    json!({
+                        "kind": kind(owner),
+                        "login": owner.login,
+                        "name": owner.name,
+                        "github": github_handle(owner),
+                        "url": owner.url,
+                    })
+                })
+                .collect::<Vec<_>>(),
+        });
+        return write_json(output, &json).await;
     }
 
-    let mut cell_vec = vec![
-        Cell::new("Total"),
-        Cell::new(crates.iter().fold(0, |init, c| init + c.downloads))
-            .set_alignment(CellAlignment::Right),
-    ];
+    let rows = owners
+        .iter()
+        .map(|owner| {
+            vec![
+                kind(owner),
+                owner.login.clone(),
+                owner.name.clone().unwrap_or_default(),
+                github_handle(owner),
+            ]
+        })
+        .collect::<Vec<_>>();
+    print_rows(
+        output,
+        vec![
+            "Kind".to_string(),
+            "Login".to_string(),
+            "Name".to_string(),
+            "GitHub".to_string(),
+        ],
+        rows,
+    )
+    .await
+}
 
-    for day in days {
-        let total_cell = Cell::new(
-            daily_downloads
-                .values()
-                .map(|download_maps| download_maps.get(day).unwrap_or(&0))
-                .sum::<u64>()
-                .to_string(),
-        )
-        .set_alignment(CellAlignment::Right);
-        cell_vec.push(total_cell);
+async fn print_dependents_tree(
+    client: &api::Client,
+    args: &DependentsArgs,
+    output: &OutputArgs,
+    crate_name: String,
+) -> Result<()> {
+    let pb = new_spinner();
+    pb.enable_steady_tick(Duration::from_millis(500));
+    let mut children: HashMap<String, Vec<(String, u64)>> = HashMap::new();
+    let mut seen = HashSet::from([crate_name.clone()]);
+    let mut frontier = vec![crate_name.clone()];
+    let mut truncated = false;
+    for level in 1..=args.depth {
+        pb.set_message(format!(
+            "Fetching level {} dependents of {}, {} crates so far...",
+            level,
+            crate_name,
+            seen.len()
+        ));
+        let fetched = stream::iter(frontier)
+            .map(|parent| {
+                let client = client.clone();
+                async move {
+                    let mut dependencies = Vec::new();
+                    for page_number in 1.. {
+                        let page = client
+                            .crate_reverse_dependencies_page(&parent, page_number)
+                            .await
+                            .map_err(crate_not_found(&parent))?;
+                        if page.dependencies.is_empty() {
+                            break;
+                        }
+                        dependencies.extend(page.dependencies);
+                        if dependencies.len() as u64 >= page.meta.total {
+                            break;
+                        }
+                    }
+                    Ok::<_, Error>((parent, dependencies))
+                }
+            })
+            .buffered(config::concurrency())
+            .collect::<Vec<_>>()
+            .await;
+        let mut next = Vec::new();
+        for result in fetched {
+            let (parent, dependencies) = result?;
+            let mut dependents: Vec<(String, u64)> = Vec::new();
+            for rd in dependencies {
+                let name = rd.crate_version.crate_name;
+                match dependents.iter_mut().find(|dependent| dependent.0 == name) {
+                    Some(dependent) => dependent.1 = dependent.1.max(rd.dependency.downloads),
+                    None => dependents.push((name, rd.dependency.downloads)),
+                }
+            }
+            let count = dependents.len();
+            select_rows(
+                &mut dependents,
+                &args.rows,
+                |dependent| dependent.0.clone(),
+                |dependent, _| dependent.1,
+            );
+            truncated |= dependents.len() < count;
+            for (name, _) in &dependents {
+                if seen.len() >= args.max_nodes {
+                    truncated = true;
+                    break;
+                }
+                if seen.insert(name.clone()) {
+                    next.push(name.clone());
+                }
+            }
+            children.insert(parent, dependents);
+        }
+        frontier = next;
+        if frontier.is_empty() || seen.len() >= args.max_nodes {
+            break;
+        }
     }
+    pb.finish_and_clear();
 
-    table.add_row(Row::from(cell_vec));
+    if output.output == OutputFormat::Json {
+        let mut expanded = HashSet::new();
+        let json = json!({
+            "crate": crate_name,
+            "depth": args.depth,
+            "truncated": truncated,
+            "dependents": dependents_json(&crate_name, &children, &mut expanded),
+        });
+        return write_json(output, &json).await;
+    }
+    if matches!(output.output, OutputFormat::Dot | OutputFormat::Mermaid) {
+        let graph = dependents_graph(&crate_name, &children, output.output);
+        return write_output(output, &graph).await;
+    }
 
-    let mut stdout = io::stdout();
-    let _ = stdout.write_all(table.to_string().as_bytes()).await;
+    let mut lines = vec![crate_name.clone()];
+    let mut expanded = HashSet::from([crate_name.clone()]);
+    dependents_tree_lines(&crate_name, "", &children, &mut expanded, &mut lines);
+    if truncated {
+        lines.push(format!(
+            "(tree truncated, only the top dependents of each crate and at most {} crates are shown)",
+            args.max_nodes
+        ));
+    }
+    lines.push(String::new());
+    write_output(output, &lines.join("\n")).await
 }
 
-async fn print_usage(program: &str, opts: Options) {
-    let brief = format!("Usage: {} [options]", program);
-    let mut stdout = io::stdout();
-    let _ = stdout
-        .write_all(opts.usage(&brief).to_string().as_bytes())
-        .await;
+fn dependents_tree_lines(
+    parent: &str,
+    prefix: &str,
+    children: &HashMap<String, Vec<(String, u64)>>,
+    expanded: &mut HashSet<String>,
+    lines: &mut Vec<String>,
+) {
+    let Some(dependents) = children.get(parent) else {
+        return;
+    };
+    for (i, (name, downloads)) in dependents.iter().enumerate() {
+        let last = i + 1 == dependents.len();
+        let branch = if last { "└── " } else { "├── " };
+        let repeated = !expanded.insert(name.clone());
+        lines.push(format!(
+            "{}{}{} ({}){}",
+            prefix,
+            branch,
+            name,
+            downloads,
+            if repeated { " (*)" } else { "" }
+        ));
+        if !repeated {
+            let child_prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            dependents_tree_lines(name, &child_prefix, children, expanded, lines);
+        }
+    }
 }
 
-async fn get_crate_downloads_multi(
-    client: &AsyncClient,
-    crate_name: &str,
-    dates: &Vec<NaiveDate>,
-) -> HashMap<NaiveDate, u64> {
-    let crate_downloads = client.crate_downloads(crate_name).await;
-    let mut result = HashMap::<NaiveDate, u64>::new();
-    dates.iter().for_each(|d| {
-        let dcount = match &crate_downloads {
-            Ok(downloads) => downloads
-                .version_downloads
-                .iter()
-                .filter(|vd| vd.date == *d)
-                .fold(0, |init, crate_download| init + crate_download.downloads),
-            _ => 0,
-        };
-        result.insert(d.clone(), dcount);
-    });
-    return result;
-}
-
-async fn print_crate_dependents(dependents: &ReverseDependencies) {
-    let mut table = Table::new();
-    table
-        .load_preset(UTF8_FULL)
-        .apply_modifier(UTF8_ROUND_CORNERS)
-        .set_header(vec!["Crate Name", "Download Count"]);
-    let table_rows = dependents.dependencies.iter().map(|rd| {
-        Row::from(vec![
-            Cell::new(rd.crate_version.crate_name.clone()),
-            Cell::new(rd.dependency.downloads).set_alignment(CellAlignment::Right),
-        ])
-    });
-    for row in table_rows {
-        table.add_row(row);
+fn dependents_graph(
+    root: &str,
+    children: &HashMap<String, Vec<(String, u64)>>,
+    format: OutputFormat,
+) -> String {
+    let mut ids = HashMap::from([(root.to_string(), 0)]);
+    let mut nodes = vec![(root.to_string(), None)];
+    let mut edges = Vec::new();
+    let mut queue = std::collections::VecDeque::from([root.to_string()]);
+    while let Some(parent) = queue.pop_front() {
+        for (name, downloads) in children.get(&parent).into_iter().flatten() {
+            if !ids.contains_key(name) {
+                ids.insert(name.clone(), nodes.len());
+                nodes.push((name.clone(), Some(*downloads)));
+                queue.push_back(name.clone());
+            }
+            edges.push((ids[name], ids[&parent]));
+        }
     }
+    let label = |name: &str, downloads: &Option<u64>| match downloads {
+        Some(downloads) => format!("{} ({})", name, downloads),
+        None => name.to_string(),
+    };
+    let mut lines = Vec::new();
+    if format == OutputFormat::Dot {
+        lines.push("digraph dependents {".to_string());
+        lines.push("    rankdir=LR;".to_string());
+        for (i, (name, downloads)) in nodes.iter().enumerate() {
+            let style = if i == 0 { ", style=bold" } else { "" };
+            lines.push(format!(
+                "    n{} [label=\"{}\"{}];",
+                i,
+                label(name, downloads),
+                style
+            ));
+        }
+        lines.extend(
+            edges
+                .iter()
+                .map(|(from, to)| format!("    n{} -> n{};", from, to)),
+        );
+        lines.push("}".to_string());
+    } else {
+        lines.push("graph LR".to_string());
+        for (i, (name, downloads)) in nodes.iter().enumerate() {
+            lines.push(format!("    n{}[\"{}\"]", i, label(name, downloads)));
+        }
+        lines.extend(
+            edges
+                .iter()
+                .map(|(from, to)| format!("    n{} --> n{}", from, to)),
+        );
+    }
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+fn dependents_json(
+    parent: &str,
+    children: &HashMap<String, Vec<(String, u64)>>,
+    expanded: &mut HashSet<String>,
+) -> Vec<serde_json::Value> {
+    expanded.insert(parent.to_string());
+    children
+        .get(parent)
+        .into_iter()
+        .flatten()
+        .map(|(name, downloads)| {
+            let dependents = if expanded.contains(name) {
+                Vec::new()
+            } else {
+                dependents_json(name, children, expanded)
+            };
+            json!({"name": name, "downloads": downloads, "dependents": dependents})
+        })
+        .collect()
+}
+
+async fn handle_user_command(
+    args: &UserArgs,
+    output: &OutputArgs,
+    user_names: Vec<String>,
+) -> Result<()> {
+    let client = new_client()?;
+
+    let mut owners = Vec::new();
+    let mut crates: Vec<Crate> = Vec::new();
+    for user_name in &user_names {
+        let (owner, owner_crates) = get_owner_crates(&client, user_name).await?;
+        owners.push(owner);
+        for owner_crate in owner_crates {
+            if !crates.iter().any(|c| c.name == owner_crate.name) {
+                crates.push(owner_crate);
+            }
+        }
+    }
+    crates.sort_by(|a, b| a.name.cmp(&b.name));
+    if args.dependents {
+        return print_owned_dependents(&client, &args.report.rows, output, &crates).await;
+    }
+
+    let subject = match owners.len() {
+        1 => (
+            if user_names[0].starts_with("github:") {
+                "team"
+            } else {
+                "user"
+            },
+            owners.remove(0),
+        ),
+        _ => ("owners", json!(owners)),
+    };
+    report_crates(
+        &client,
+        &args.report,
+        output,
+        crates,
+        subject,
+        &user_names.join(", "),
+    )
+    .await
+}
+
+async fn print_owned_dependents(
+    client: &api::Client,
+    rows: &RowArgs,
+    output: &OutputArgs,
+    crates: &[Crate],
+) -> Result<()> {
+    const LISTED_CRATES: usize = 5;
+    let owned = crates
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect::<HashSet<_>>();
+    let mut reverse_dependencies = 0;
+    let mut leaderboard: BTreeMap<String, (u64, Vec<String>)> = BTreeMap::new();
+    for owned_crate in crates {
+        let dependents = fetch_dependents(client, &owned_crate.name).await?;
+        for rd in dependents.dependencies {
+            if owned.contains(rd.crate_version.crate_name.as_str()) {
+                continue;
+            }
+            let entry = leaderboard.entry(rd.crate_version.crate_name).or_default();
+            entry.0 = entry.0.max(rd.dependency.downloads);
+            if !entry.1.contains(&owned_crate.name) {
+                entry.1.push(owned_crate.name.clone());
+                reverse_dependencies += 1;
+            }
+        }
+    }
+    let mut leaderboard = leaderboard.into_iter().collect::<Vec<_>>();
+    leaderboard.sort_by_key(|(_, (downloads, uses))| {
+        (std::cmp::Reverse(uses.len()), std::cmp::Reverse(*downloads))
+    });
+    let dependent_count = leaderboard.len();
+    select_rows(
+        &mut leaderboard,
+        rows,
+        |(name, _)| name.clone(),
+        |(_, (downloads, _)), _| *downloads,
+    );
+
+    if output.output == OutputFormat::Json {
+        let json = json!({
+            "crates": crates.len(),
+            "dependents": dependent_count,
+            "reverse_dependencies": reverse_dependencies,
+            "leaderboard": leaderboard
+                .iter()
+                .map(|(name, (downloads, uses))| {
+                    json!({"name": name, "downloads": downloads, "uses": uses})
+                })
+                .collect::<Vec<_>>(),
+        });
+        return write_json(output, &json).await;
+    }
+    let mut table = leaderboard
+        .into_iter()
+        .map(|(name, (downloads, uses))| {
+            let mut listed = uses.iter().take(LISTED_CRATES).cloned().collect::<Vec<_>>();
+            if uses.len() > LISTED_CRATES {
+                listed.push(format!("+{} more", uses.len() - LISTED_CRATES));
+            }
+            vec![
+                name,
+                downloads.to_string(),
+                uses.len().to_string(),
+                listed.join(", "),
+            ]
+        })
+        .collect::<Vec<_>>();
+    table.push(vec![
+        format!(
+            "Total ({} dependents of {} crates)",
+            dependent_count,
+            crates.len()
+        ),
+        String::new(),
+        reverse_dependencies.to_string(),
+        String::new(),
+    ]);
+    let headers = ["Crate Name", "Download Count", "Uses", "Crates"]
+        .iter()
+        .map(|header| header.to_string())
+        .collect();
+    print_rows(output, headers, table).await
+}
+
+async fn get_owner_crates(
+    client: &api::Client,
+    user_name: &str,
+) -> Result<(serde_json::Value, Vec<Crate>)> {
+    if user_name.starts_with("github:") {
+        let team = client.team(user_name).await?;
+        let crates = client.team_crates(&team).await?;
+        return Ok((json!(team), crates));
+    }
+    let user = client
+        .user(user_name)
+        .await
+        .map_err(user_not_found(user_name))?;
+    let crates = get_user_crates(client, user.id, user_name).await?;
+    Ok((json!(user), crates))
+}
+
+async fn handle_me_command(args: &MeArgs, output: &OutputArgs) -> Result<()> {
+    let token = config::api_token().ok_or_else(|| {
+        Error::Usage(
+            "no crates.io api token, set CRABST_TOKEN or token in the config file".to_string(),
+        )
+    })?;
+    let client = new_client()?;
+    let me = client.me(&token).await?;
+
+    let crate_names = me
+        .owned_crates
+        .iter()
+        .map(|owned| owned.name.clone())
+        .collect::<Vec<_>>();
+    let mut crates = get_crates(&client, &crate_names)
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+
+    let pb = new_spinner();
+    pb.set_message("Fetching team owners...");
+    let owners = stream::iter(crate_names)
+        .map(|crate_name| {
+            let client = client.clone();
+            async move {
+                client
+                    .crate_owners(&crate_name)
+                    .await
+                    .map_err(crate_not_found(&crate_name))
+            }
+        })
+        .buffer_unordered(config::concurrency())
+        .collect::<Vec<_>>()
+        .await;
+    let mut team_logins = BTreeSet::new();
+    for owner in owners
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+    {
+        if owner.kind.as_deref() == Some("team") {
+            team_logins.insert(owner.login);
+        }
+    }
+    for team_login in &team_logins {
+        pb.set_message(format!("Fetching crates of {}...", team_login));
+        let team = client.team(team_login).await?;
+        for team_crate in client.team_crates(&team).await? {
+            if !crates.iter().any(|c| c.name == team_crate.name) {
+                crates.push(team_crate);
+            }
+        }
+    }
+    pb.finish_and_clear();
+    crates.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let title = me.user.login.clone();
+    report_crates(
+        &client,
+        &args.report,
+        output,
+        crates,
+        ("user", json!(me.user)),
+        &title,
+    )
+    .await
+}
+
+async fn read_crate_list(args: &CrateListArgs) -> Result<Vec<String>> {
+    let mut crate_names = Vec::new();
+    for name in &args.names {
+        crate_names.extend(read_targets(name.clone()).await?);
+    }
+    if let Some(list_name) = &args.list {
+        crate_names.extend(watch_list(list_name)?.crates.iter().cloned());
+    }
+    if crate_names.is_empty() {
+        return Err(Error::Usage(
+            "no crates given, pass crate names or --list".to_string(),
+        ));
+    }
+    Ok(crate_names.into_iter().unique().collect())
+}
+
+fn watch_list(list_name: &str) -> Result<&'static config::WatchList> {
+    config::watch_list(list_name).ok_or_else(|| {
+        Error::Usage(format!(
+            "no list named {} in the config file, add a [lists.{}] table",
+            list_name, list_name
+        ))
+    })
+}
+
+async fn handle_serve_command(args: &ServeArgs) -> Result<()> {
+    let crate_names = read_crate_list(&args.crates).await?;
+    metrics::serve(
+        &listen_addr(&args.prometheus),
+        crate_names,
+        Duration::from_secs(args.interval * 60),
+    )
+    .await
+}
+
+fn listen_addr(addr: &str) -> String {
+    match addr.strip_prefix(':') {
+        Some(port) => format!("0.0.0.0:{}", port),
+        None => addr.to_string(),
+    }
+}
+
+async fn handle_daemon_command(args: &DaemonArgs) -> Result<()> {
+    let mut jobs = Vec::new();
+    for job in config::schedule() {
+        let schedule = schedule::Schedule::parse(&job.cron)?;
+        if schedule.next_after(chrono::Local::now()).is_none() {
+            return Err(Error::Usage(format!("schedule `{}` never runs", job.cron)));
+        }
+        let cli = config::parse_command(&job.run)?;
+        if matches!(
+            cli.command,
+            Command::Daemon(_) | Command::Serve(_) | Command::Tui(_)
+        ) || cli.output.watch.is_some()
+        {
+            return Err(Error::Usage(format!("`{}` can not be scheduled", job.run)));
+        }
+        jobs.push((job.run.as_str(), schedule, cli));
+    }
+    if jobs.is_empty() && args.prometheus.is_none() {
+        return Err(Error::Usage(
+            "nothing to run, add [[schedule]] to the config file or pass --prometheus".to_string(),
+        ));
+    }
+
+    let scheduler = run_schedule(&jobs);
+    match &args.prometheus {
+        Some(prometheus) => {
+            let crate_names = read_crate_list(&args.crates).await?;
+            let addr = listen_addr(prometheus);
+            let serve = metrics::serve(&addr, crate_names, Duration::from_secs(args.interval * 60));
+            tokio::try_join!(serve, scheduler).map(|_| ())
+        }
+        None => scheduler.await,
+    }
+}
+
+async fn run_schedule(jobs: &[(&str, schedule::Schedule, Cli)]) -> Result<()> {
+    if jobs.is_empty() {
+        return std::future::pending().await;
+    }
+    loop {
+        let now = chrono::Local::now();
+        let Some(next) = jobs
+            .iter()
+            .filter_map(|(_, schedule, _)| schedule.next_after(now))
+            .min()
+        else {
+            return Ok(());
+        };
+        tokio::time::sleep((next - now).to_std().unwrap_or_default()).await;
+        for (line, schedule, cli) in jobs {
+            if schedule.next_after(now) != Some(next) {
+                continue;
+            }
+            tracing::info!("{} running {}", next.format("%Y-%m-%d %H:%M"), line);
+            if let Err(err) = Box::pin(run(cli)).await {
+                print_error(&err).await;
+            }
+        }
+    }
+}
+
+async fn handle_push_command(args: &PushArgs) -> Result<()> {
+    let targets = args
+        .target
+        .iter()
+        .map(|target| metrics::PushTarget::parse(target))
+        .collect::<Result<Vec<_>>>()?;
+    let crate_names = read_crate_list(&args.crates).await?;
+    let client = new_client()?;
+    let pb = new_spinner();
+    pb.set_message("Fetching crate metrics...");
+    let metrics = metrics::collect(&client, &crate_names).await;
+    pb.finish_and_clear();
+    let metrics = metrics?;
+    for target in &targets {
+        metrics::push(target, &metrics).await?;
+    }
+    Ok(())
+}
+
+async fn handle_notify_command(args: &NotifyArgs) -> Result<()> {
+    let rules = config::alerts();
+    if rules.is_empty() {
+        return Err(Error::Usage(
+            "no alerts configured, add [[alerts]] to the config file".to_string(),
+        ));
+    }
+    let Some(webhook) = args.webhook.clone().or_else(config::webhook) else {
+        return Err(Error::Usage(
+            "no webhook configured, pass --webhook or set webhook in the config file".to_string(),
+        ));
+    };
+    let webhook = reqwest::Url::parse(&webhook)
+        .map_err(|err| Error::Usage(format!("invalid webhook url {}: {}", webhook, err)))?;
+    let client = new_client()?;
+    let pb = new_spinner();
+    pb.set_message("Checking alerts...");
+    let alerts = notify::run(&client, rules, &webhook, &args.state_file).await;
+    pb.finish_and_clear();
+    let alerts = alerts?;
+    if alerts.is_empty() {
+        println!("No new alerts");
+    }
+    for alert in alerts {
+        println!("{}", alert.text);
+    }
+    Ok(())
+}
+
+async fn handle_digest_command(args: &DigestArgs) -> Result<()> {
+    let parse = |url: &String| {
+        reqwest::Url::parse(url)
+            .map_err(|err| Error::Usage(format!("invalid webhook url {}: {}", url, err)))
+    };
+    let slack = args.slack_webhook.as_ref().map(parse).transpose()?;
+    let discord = args.discord_webhook.as_ref().map(parse).transpose()?;
+    let crate_names = read_crate_list(&args.crates).await?;
+    let store = Store::open(args.store.db.as_deref())?;
+    let client = new_client()?;
+    let pb = new_spinner();
+    pb.set_message("Fetching crate numbers...");
+    let digests = digest::collect(&client, &crate_names, args.days, Some(&store)).await;
+    pb.finish_and_clear();
+    let digests = digests?;
+
+    if let Some(url) = &slack {
+        let text = digest::render(&digests, args.days, args.top, digest::Markup::Slack);
+        digest::post(url, &text, digest::Markup::Slack).await?;
+    }
+    if let Some(url) = &discord {
+        let text = digest::render(&digests, args.days, args.top, digest::Markup::Discord);
+        digest::post(url, &text, digest::Markup::Discord).await?;
+    }
+    if slack.is_none() && discord.is_none() {
+        print!(
+            "{}",
+            digest::render(&digests, args.days, args.top, digest::Markup::Plain)
+        );
+    }
+    Ok(())
+}
+
+async fn handle_badge_command(args: &BadgeArgs, output: &OutputArgs) -> Result<()> {
+    let client = new_client()?;
+    let api_crate = client
+        .get_crate(&args.name)
+        .await
+        .map_err(crate_not_found(&args.name))?;
+    let message = match args.metric {
+        BadgeMetric::Total => compact_number(api_crate.crate_data.downloads),
+        metric => {
+            let downloads = client
+                .crate_downloads(&args.name)
+                .await
+                .context(format!("can not get downloads of crate {}", args.name))?;
+            let today = Utc::now().date_naive();
+            let (days, unit) = match metric {
+                BadgeMetric::Daily => (1, "day"),
+                _ => (7, "week"),
+            };
+            // days without downloads are missing from the map
+            let daily = daily_downloads(&downloads);
+            let count = (1..=days)
+                .map(|days_ago| today - Days::new(days_ago))
+                .map(|date| daily.get(&date).copied().unwrap_or(0))
+                .sum::<u64>();
+            format!("{}/{}", compact_number(count), unit)
+        }
+    };
+    let json = json!({
+        "schemaVersion": 1,
+        "label": args.label,
+        "message": message,
+        "color": args.color,
+    });
+    write_json(output, &json).await
+}
+
+async fn handle_versions_command(
+    args: &VersionsArgs,
+    output: &OutputArgs,
+    crate_name: String,
+) -> Result<()> {
+    let client = new_client()?;
+    let api_crate = client
+        .get_crate(&crate_name)
+        .await
+        .map_err(crate_not_found(&crate_name))?;
+    let mut versions = api_crate.versions;
+    versions.retain(|version| !args.yanked || version.yanked);
+    versions.sort_by_key(|version| std::cmp::Reverse(version.created_at));
+    let docs = match args.docs {
+        true => {
+            docs_builds(
+                versions
+                    .iter()
+                    .map(|version| (crate_name.clone(), version.num.clone()))
+                    .collect(),
+            )
+            .await?
+        }
+        false => Vec::new(),
+    };
+
+    if output.output == OutputFormat::Json {
+        let json = json!({
+            "crate": crate_name,
+            "versions": versions
+                .iter()
+                .enumerate()
+                .map(|(i, version)| {
+                    let mut entry = json!({
+                        "version": version.num,
+                        "published": version.created_at,
+                        "yanked": version.yanked,
+                        "downloads": version.downloads,
+                        "rust_version": version.rust_version,
+                        "crate_size": version.crate_size,
+                    });
+                    if args.docs {
+                        entry["docs"] = json!(docs[i]);
+                    }
+                    entry
+                })
+                .collect::<Vec<_>>(),
+        });
+        return write_json(output, &json).await;
+    }
+    let mut headers = [
+        "Version",
+        "Published",
+        "Yanked",
+        "Downloads",
+        "Rust Version",
+        "Size",
+    ]
+    .iter()
+    .map(|header| header.to_string())
+    .collect::<Vec<_>>();
+    if args.docs {
+        headers.push("Docs".to_string());
+    }
+    let rows = versions
+        .iter()
+        .enumerate()
+        .map(|(i, version)| {
+            let mut row = vec![
+                version.num.clone(),
+                version.created_at.format("%Y-%m-%d").to_string(),
+                if version.yanked { "yes" } else { "no" }.to_string(),
+                version.downloads.to_string(),
+                version.rust_version.clone().unwrap_or("-".to_string()),
+                version.crate_size.map_or("-".to_string(), format_size),
+            ];
+            if args.docs {
+                row.push(
+                    docs[i]
+                        .as_ref()
+                        .map_or("-".to_string(), |build| match build.built_at {
+                            Some(built_at) => {
+                                format!("{} {}", build.marker(), built_at.format("%Y-%m-%d"))
+                            }
+                            None => build.marker().to_string(),
+                        }),
+                );
+            }
+            row
+        })
+        .collect();
+    print_rows(output, headers, rows).await
+}
+
+async fn handle_search_command(args: &SearchArgs, output: &OutputArgs) -> Result<()> {
+    let sort = match args.sort {
+        SearchSort::Relevance => "relevance",
+        SearchSort::Downloads => "downloads",
+        SearchSort::RecentDownloads => "recent-downloads",
+        SearchSort::RecentUpdates => "recent-updates",
+        SearchSort::New => "new",
+        SearchSort::Alpha => "alpha",
+    };
+    let client = new_client()?;
+    let page = client
+        .crates_page(&[
+            ("q", args.query.clone()),
+            ("sort", sort.to_string()),
+            ("per_page", args.limit.to_string()),
+        ])
+        .await
+        .context(format!("can not search crates.io for {}", args.query))?;
+    let mut crates = page.crates;
+    crates.truncate(args.limit as usize);
+    print_crate_listing(&client, output, &crates).await
+}
+
+async fn handle_top_command(args: &TopArgs, output: &OutputArgs) -> Result<()> {
+    let sort = if args.recent {
+        "recent-downloads"
+    } else {
+        "downloads"
+    };
+    let mut query = vec![
+        ("sort", sort.to_string()),
+        ("per_page", args.limit.to_string()),
+    ];
+    let mut filters = Vec::new();
+    if let Some(category) = &args.category {
+        query.push(("category", category.clone()));
+        filters.push(format!("category {}", category));
+    }
+    if let Some(keyword) = &args.keyword {
+        query.push(("keyword", keyword.clone()));
+        filters.push(format!("keyword {}", keyword));
+    }
+    let client = new_client()?;
+    let page = client
+        .crates_page(&query)
+        .await
+        .context(format!("can not get crates of {}", filters.join(" and ")))?;
+    let mut crates = page.crates;
+    crates.truncate(args.limit as usize);
+
+    if output.output == OutputFormat::Json {
+        let json = json!({
+            "category": args.category,
+            "keyword": args.keyword,
+            "total": page.meta.total,
+            "crates": crates
+                .iter()
+                .enumerate()
+                .map(|(i, found)| {
+                    json!({
+                        "rank": i + 1,
+                        "name": found.name,
+                        "downloads": found.downloads,
+                        "recent_downloads": found.recent_downloads,
+                    })
+                })
+                .collect::<Vec<_>>(),
+        });
+        return write_json(output, &json).await;
+    }
+    let rows = crates
+        .iter()
+        .enumerate()
+        .map(|(i, found)| {
+            vec![
+                (i + 1).to_string(),
+                found.name.clone(),
+                found.downloads.to_string(),
+                found
+                    .recent_downloads
+                    .map_or("-".to_string(), |count| count.to_string()),
+            ]
+        })
+        .collect();
+    let headers = ["Rank", "Crate Name", "Download Count", "Recent Downloads"]
+        .iter()
+        .map(|header| header.to_string())
+        .collect();
+    print_rows(output, headers, rows).await
+}
+
+async fn handle_trending_command(args: &TrendingArgs, output: &OutputArgs) -> Result<()> {
+    let client = new_client()?;
+    let page = client
+        .crates_page(&[
+            ("sort", "recent-downloads".to_string()),
+            ("per_page", args.candidates.to_string()),
+        ])
+        .await
+        .context("can not get recently downloaded crates")?;
+    let oldest = Utc::now() - chrono::Duration::days(args.min_age as i64);
+    let candidates = page
+        .crates
+        .into_iter()
+        .filter(|found| found.created_at <= oldest)
+        .filter(|found| found.recent_downloads.unwrap_or(0) >= args.min_downloads)
+        .collect::<Vec<_>>();
+
+    // growth is the share of all-time downloads made in the last 90 days, or the
+    // week-over-week change with the last two complete weeks
+    let mut trending = if args.week_over_week {
+        let today = Utc::now().date_naive();
+        let pb = new_spinner();
+        pb.set_message("Fetching daily downloads...");
+        let fetched = stream::iter(candidates)
+            .map(|found| {
+                let client = client.clone();
+                async move {
+                    let downloads = client
+                        .crate_downloads(&found.name)
+                        .await
+                        .context(format!("can not get downloads of crate {}", found.name))?;
+                    Ok::<_, Error>((found, daily_downloads(&downloads)))
+                }
+            })
+            .buffered(config::concurrency())
+            .collect::<Vec<_>>()
+            .await;
+        pb.finish_and_clear();
+        let week = |daily: &BTreeMap<NaiveDate, u64>, weeks_ago: u64| {
+            (1..=7)
+                .map(|day| today - Days::new(weeks_ago * 7 + day))
+                .map(|date| daily.get(&date).copied().unwrap_or(0))
+                .sum::<u64>()
+        };
+        fetched
+            .into_iter()
+            .map(|result| {
+                let (found, daily) = result?;
+                let (current, previous) = (week(&daily, 0), week(&daily, 1));
+                let growth = match previous {
+                    0 if current > 0 => f64::INFINITY,
+                    0 => 0.0,
+                    previous => (current as f64 - previous as f64) / previous as f64,
+                };
+                Ok((found, growth, trend(current, previous)))
+            })
+            .collect::<Result<Vec<_>>>()?
+    } else {
+        candidates
+            .into_iter()
+            .map(|found| {
+                let growth =
+                    found.recent_downloads.unwrap_or(0) as f64 / found.downloads.max(1) as f64;
+                let label = format!("{:.0}%", growth * 100.0);
+                (found, growth, label)
+            })
+            .collect()
+    };
+    trending.sort_by(|a, b| b.1.total_cmp(&a.1));
+    trending.truncate(args.limit);
+
+    if output.output == OutputFormat::Json {
+        let json = trending
+            .iter()
+            .enumerate()
+            .map(|(i, (found, growth, _))| {
+                json!({
+                    "rank": i + 1,
+                    "name": found.name,
+                    "created_at": found.created_at,
+                    "downloads": found.downloads,
+                    "recent_downloads": found.recent_downloads,
+                    "growth": growth.is_finite().then(|| (growth * 1000.0).round() / 1000.0),
+                })
+            })
+            .collect::<Vec<_>>();
+        return write_json(output, &json!(json)).await;
+    }
+    let rows = trending
+        .iter()
+        .enumerate()
+        .map(|(i, (found, _, label))| {
+            vec![
+                (i + 1).to_string(),
+                found.name.clone(),
+                found.created_at.format("%Y-%m-%d").to_string(),
+                found
+                    .recent_downloads
+                    .map_or("-".to_string(), |count| count.to_string()),
+                found.downloads.to_string(),
+                label.clone(),
+            ]
+        })
+        .collect();
+    let growth = if args.week_over_week {
+        "Week Over Week"
+    } else {
+        "Recent Share"
+    };
+    let headers = [
+        "Rank",
+        "Crate Name",
+        "Created",
+        "Recent Downloads",
+        "Download Count",
+        growth,
+    ]
+    .iter()
+    .map(|header| header.to_string())
+    .collect();
+    print_rows(output, headers, rows).await
+}
+
+async fn handle_feed_command(args: &FeedArgs, output: &OutputArgs, updated: bool) -> Result<()> {
+    let sort = if updated { "recent-updates" } else { "new" };
+    let when = |found: &Crate| {
+        if updated {
+            found.updated_at
+        } else {
+            found.created_at
+        }
+    };
+    let client = new_client()?;
+    let mut crates = Vec::new();
+    'pages: for page_number in 1.. {
+        let page = client
+            .crates_page(&[
+                ("sort", sort.to_string()),
+                ("page", page_number.to_string()),
+                ("per_page", "100".to_string()),
+            ])
+            .await
+            .context("can not get the latest crates")?;
+        let page_len = page.crates.len();
+        for found in page.crates {
+            if crates.len() >= args.limit || args.since.is_some_and(|since| when(&found) < since) {
+                break 'pages;
+            }
+            crates.push(found);
+        }
+        if page_len < 100 {
+            break;
+        }
+    }
+
+    if output.output == OutputFormat::Json {
+        let json = crates
+            .iter()
+            .map(|found| {
+                json!({
+                    "name": found.name,
+                    "version": found.max_version,
+                    "description": found.description,
+                    "created_at": found.created_at,
+                    "updated_at": found.updated_at,
+                    "downloads": found.downloads,
+                })
+            })
+            .collect::<Vec<_>>();
+        return write_json(output, &json!(json)).await;
+    }
+    let rows = crates
+        .iter()
+        .map(|found| {
+            vec![
+                found.name.clone(),
+                found.max_version.clone(),
+                when(found).format("%Y-%m-%d %H:%M").to_string(),
+                found.downloads.to_string(),
+                short_description(found),
+            ]
+        })
+        .collect();
+    let headers = [
+        "Crate Name",
+        "Version",
+        if updated { "Updated" } else { "Published" },
+        "Download Count",
+        "Description",
+    ]
+    .iter()
+    .map(|header| header.to_string())
+    .collect();
+    print_rows(output, headers, rows).await
+}
+
+fn short_description(found: &Crate) -> String {
+    const DESCRIPTION_WIDTH: usize = 60;
+    let description = found
+        .description
+        .as_deref()
+        .unwrap_or_default()
+        .split_whitespace()
+        .join(" ");
+    match description.chars().count() > DESCRIPTION_WIDTH {
+        true => format!(
+            "{}…",
+            description
+                .chars()
+                .take(DESCRIPTION_WIDTH - 1)
+                .collect::<String>()
+        ),
+        false => description,
+    }
+}
+
+async fn print_crate_listing(
+    client: &api::Client,
+    output: &OutputArgs,
+    crates: &[Crate],
+) -> Result<()> {
+    let pb = new_spinner();
+    pb.set_message("Fetching dependents...");
+    let dependents = stream::iter(crates)
+        .map(|found| {
+            let client = client.clone();
+            async move {
+                client
+                    .crate_reverse_dependency_count(&found.name)
+                    .await
+                    .context(format!("can not get dependents of crate {}", found.name))
+            }
+        })
+        .buffered(config::concurrency())
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+    pb.finish_and_clear();
+
+    if output.output == OutputFormat::Json {
+        let json = crates
+            .iter()
+            .zip(&dependents)
+            .map(|(found, dependents)| {
+                json!({
+                    "name": found.name,
+                    "description": found.description,
+                    "max_version": found.max_version,
+                    "downloads": found.downloads,
+                    "recent_downloads": found.recent_downloads,
+                    "created_at": found.created_at,
+                    "updated_at": found.updated_at,
+                    "dependents": dependents,
+                })
+            })
+            .collect::<Vec<_>>();
+        return write_json(output, &json!(json)).await;
+    }
+    let rows = crates
+        .iter()
+        .zip(&dependents)
+        .map(|(found, dependents)| {
+            vec![
+                found.name.clone(),
+                short_description(found),
+                found.downloads.to_string(),
+                found
+                    .recent_downloads
+                    .map_or("-".to_string(), |count| count.to_string()),
+                found.updated_at.format("%Y-%m-%d").to_string(),
+                dependents.to_string(),
+            ]
+        })
+        .collect();
+    let headers = [
+        "Crate Name",
+        "Description",
+        "Download Count",
+        "Recent Downloads",
+        "Updated",
+        "Dependents",
+    ]
+    .iter()
+    .map(|header| header.to_string())
+    .collect();
+    print_rows(output, headers, rows).await
+}
+
+async fn handle_related_command(
+    args: &RelatedArgs,
+    output: &OutputArgs,
+    crate_name: String,
+) -> Result<()> {
+    let client = new_client()?;
+    let api_crate = client
+        .get_crate(&crate_name)
+        .await
+        .map_err(crate_not_found(&crate_name))?;
+    let mut related = source::Provider::new(config::source(), &client)?
+        .related(&api_crate)
+        .await?;
+    related.truncate(args.limit);
+    let mut crates = Vec::new();
+    for result in get_crates(&client, &related).await {
+        match result {
+            Ok(found) => crates.push(found),
+            Err(Error::CrateNotFound(_)) => {}
+            Err(err) => return Err(err),
+        }
+    }
+    print_crate_listing(&client, output, &crates).await
+}
+
+async fn handle_adoption_command(args: &AdoptionArgs, output: &OutputArgs) -> Result<()> {
+    let Some((crate_name, num)) = args
+        .target
+        .split_once('@')
+        .filter(|(name, num)| !name.is_empty() && !num.is_empty())
+    else {
+        return Err(Error::Usage(format!(
+            "invalid target `{}`, expected <crate>@<version>",
+            args.target
+        )));
+    };
+    let client = new_client()?;
+    let api_crate = client
+        .get_crate(crate_name)
+        .await
+        .map_err(crate_not_found(crate_name))?;
+    let Some(version) = api_crate.versions.iter().find(|version| version.num == num) else {
+        return Err(Error::Usage(format!(
+            "crate {} has no version {}",
+            crate_name, num
+        )));
+    };
+    let downloads = client
+        .crate_downloads(crate_name)
+        .await
+        .context(format!("can not get downloads of crate {}", crate_name))?;
+    let published = version.created_at.date_naive();
+    let mut version_daily: BTreeMap<NaiveDate, u64> = BTreeMap::new();
+    for vd in &downloads.version_downloads {
+        if vd.version == version.id {
+            *version_daily.entry(vd.date).or_insert(0) += vd.downloads;
+        }
+    }
+    let adoption = daily_downloads(&downloads)
+        .into_iter()
+        .filter(|(date, _)| *date >= published)
+        .map(|(date, total)| {
+            let count = version_daily.get(&date).copied().unwrap_or(0);
+            let share = count as f64 / total.max(1) as f64 * 100.0;
+            (date, count, total, share)
+        })
+        .collect::<Vec<_>>();
+    if adoption.is_empty() {
+        println!("No downloads found");
+        return Ok(());
+    }
+    let dates = adoption.iter().map(|day| day.0).collect::<Vec<_>>();
+    let shares = adoption.iter().map(|day| day.3).collect::<Vec<_>>();
+    let title = format!("{} {} share of daily downloads", crate_name, num);
+    write_chart(output, &dates, &[(&args.target, &shares)], &title).await?;
+
+    match output.output {
+        OutputFormat::Json => {
+            let json = json!({
+                "crate": crate_name,
+                "version": num,
+                "published": version.created_at,
+                "days": adoption
+                    .iter()
+                    .map(|(date, count, total, share)| {
+                        json!({
+                            "date": date,
+                            "downloads": count,
+                            "total": total,
+                            "share": (share * 10.0).round() / 10.0,
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            });
+            write_json(output, &json).await
+        }
+        OutputFormat::Graph => {
+            let graph = plot_smoothed(
+                shares,
+                None,
+                &[],
+                Config::default()
+                    .with_offset(10)
+                    .with_height(10)
+                    .with_caption(format!("{} since {} in %", title, published)),
+            );
+            println!("{}", graph);
+            Ok(())
+        }
+        _ => {
+            let headers = ["Date", "Version Downloads", "Total Downloads", "Share"]
+                .iter()
+                .map(|header| header.to_string())
+                .collect();
+            let rows = adoption
+                .iter()
+                .map(|(date, count, total, share)| {
+                    vec![
+                        date.to_string(),
+                        count.to_string(),
+                        total.to_string(),
+                        format!("{:.1}%", share),
+                    ]
+                })
+                .collect();
+            print_rows(output, headers, rows).await
+        }
+    }
+}
+
+async fn handle_cadence_command(output: &OutputArgs, crate_names: Vec<String>) -> Result<()> {
+    let client = new_client()?;
+    let now = Utc::now();
+    let mut cadences = Vec::new();
+    for crate_name in crate_names {
+        let api_crate = client
+            .get_crate(&crate_name)
+            .await
+            .map_err(crate_not_found(&crate_name))?;
+        let mut released = api_crate
+            .versions
+            .iter()
+            .filter(|version| !version.yanked)
+            .map(|version| version.created_at)
+            .collect::<Vec<_>>();
+        released.sort();
+        let mut gaps = released
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).num_seconds() as f64 / 86400.0)
+            .collect::<Vec<_>>();
+        gaps.sort_by(f64::total_cmp);
+        let average = (!gaps.is_empty()).then(|| gaps.iter().sum::<f64>() / gaps.len() as f64);
+        let median = median(&gaps);
+        let since_last = released.last().map(|last| (now - *last).num_days());
+        let per_year = released.first().map(|first| {
+            let years = (now - *first).num_seconds() as f64 / (365.25 * 86400.0);
+            released.len() as f64 / years.max(1.0 / 365.25)
+        });
+        cadences.push((
+            crate_name,
+            released.len(),
+            average,
+            median,
+            since_last,
+            per_year,
+        ));
+    }
+
+    if output.output == OutputFormat::Json {
+        let json = cadences
+            .iter()
+            .map(|(name, releases, average, median, since_last, per_year)| {
+                json!({
+                    "crate": name,
+                    "releases": releases,
+                    "average_days_between_releases": average.map(|days| (days * 10.0).round() / 10.0),
+                    "median_days_between_releases": median.map(|days| (days * 10.0).round() / 10.0),
+                    "days_since_last_release": since_last,
+                    "releases_per_year": per_year.map(|count| (count * 10.0).round() / 10.0),
+                })
+            })
+            .collect::<Vec<_>>();
+        return write_json(output, &json!(json)).await;
+    }
+    let decimal =
+        |value: &Option<f64>| value.map_or("-".to_string(), |value| format!("{:.1}", value));
+    let rows = cadences
+        .iter()
+        .map(|(name, releases, average, median, since_last, per_year)| {
+            vec![
+                name.clone(),
+                releases.to_string(),
+                decimal(average),
+                decimal(median),
+                since_last.map_or("-".to_string(), |days| days.to_string()),
+                decimal(per_year),
+            ]
+        })
+        .collect();
+    let headers = [
+        "Crate Name",
+        "Releases",
+        "Average Days Between",
+        "Median Days Between",
+        "Days Since Last",
+        "Releases Per Year",
+    ]
+    .iter()
+    .map(|header| header.to_string())
+    .collect();
+    print_rows(output, headers, rows).await
+}
+
+const PLACEHOLDER_DOWNLOADS: u64 = 1000;
+
+fn valid_crate_name(name: &str) -> bool {
+    name.len() <= 64
+        && name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+async fn handle_check_name_command(args: &CheckNameArgs, output: &OutputArgs) -> Result<()> {
+    let mut names = Vec::new();
+    for name in &args.names {
+        names.extend(read_targets(name.clone()).await?);
+    }
+    let names = names.into_iter().unique().collect::<Vec<_>>();
+    let valid = names
+        .iter()
+        .filter(|name| valid_crate_name(name))
+        .cloned()
+        .collect::<Vec<_>>();
+    let client = new_client()?;
+    let mut fetched = valid
+        .iter()
+        .zip(get_crates(&client, &valid).await)
+        .collect::<HashMap<_, _>>();
+    let mut checks = Vec::new();
+    for name in &names {
+        let found = match fetched.remove(name) {
+            None => {
+                checks.push((name.clone(), "invalid", None, Vec::new()));
+                continue;
+            }
+            Some(Ok(found)) => found,
+            Some(Err(Error::CrateNotFound(_))) => {
+                checks.push((name.clone(), "available", None, Vec::new()));
+                continue;
+            }
+            Some(Err(err)) => return Err(err),
+        };
+        // crates.io treats - and _ as the same and ignores case
+        let mut notes = Vec::new();
+        if found.name != *name {
+            notes.push(format!("registered as {}", found.name));
+        }
+        let signals = [
+            (found.repository.is_none(), "no repository".to_string()),
+            (
+                found.versions.as_ref().map_or(0, Vec::len) <= 1
+                    && found.max_version.starts_with("0.0."),
+                format!("single {} release", found.max_version),
+            ),
+            (
+                found.downloads < PLACEHOLDER_DOWNLOADS,
+                format!("{} downloads", found.downloads),
+            ),
+        ];
+        let placeholder = signals.iter().all(|(matched, _)| *matched);
+        notes.extend(
+            signals
+                .into_iter()
+                .filter(|(matched, _)| *matched)
+                .map(|(_, note)| note),
+        );
+        let status = if placeholder { "placeholder" } else { "taken" };
+        checks.push((name.clone(), status, Some(found), notes));
+    }
+
+    if output.output == OutputFormat::Json {
+        let json = checks
+            .iter()
+            .map(|(name, status, found, notes)| {
+                json!({
+                    "name": name,
+                    "status": status,
+                    "registered_as": found.as_ref().map(|found| &found.name),
+                    "downloads": found.as_ref().map(|found| found.downloads),
+                    "max_version": found.as_ref().map(|found| &found.max_version),
+                    "updated_at": found.as_ref().map(|found| found.updated_at),
+                    "notes": notes,
+                })
+            })
+            .collect::<Vec<_>>();
+        return write_json(output, &json!(json)).await;
+    }
+    let rows = checks
+        .iter()
+        .map(|(name, status, found, notes)| {
+            let field = |value: fn(&Crate) -> String| found.as_ref().map_or("-".to_string(), value);
+            vec![
+                name.clone(),
+                status.to_string(),
+                field(|found| found.downloads.to_string()),
+                field(|found| found.max_version.clone()),
+                field(|found| found.updated_at.format("%Y-%m-%d").to_string()),
+                notes.join(", "),
+            ]
+        })
+        .collect();
+    let headers = [
+        "Name",
+        "Status",
+        "Download Count",
+        "Latest Version",
+        "Updated",
+        "Notes",
+    ]
+    .iter()
+    .map(|header| header.to_string())
+    .collect();
+    print_rows(output, headers, rows).await
+}
+
+async fn handle_typosquat_command(output: &OutputArgs, crate_name: String) -> Result<()> {
+    let client = new_client()?;
+    let owners = client
+        .crate_owners(&crate_name)
+        .await
+        .map_err(crate_not_found(&crate_name))?;
+    let owner_ids = owners.iter().map(|owner| owner.id).collect::<HashSet<_>>();
+    let variants = typo::variants(&crate_name);
+    let names = variants.keys().cloned().collect::<Vec<_>>();
+    let mut registered = Vec::new();
+    for result in get_crates(&client, &names).await {
+        match result {
+            Ok(found) => registered.push(found),
+            Err(Error::CrateNotFound(_)) => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    let pb = new_spinner();
+    pb.set_message("Fetching owners...");
+    let fetched = stream::iter(registered)
+        .map(|found| {
+            let client = client.clone();
+            async move {
+                let owners = client
+                    .crate_owners(&found.name)
+                    .await
+                    .context(format!("can not get owners of crate {}", found.name))?;
+                Ok::<_, Error>((found, owners))
+            }
+        })
+        .buffered(config::concurrency())
+        .collect::<Vec<_>>()
+        .await;
+    pb.finish_and_clear();
+    let mut squats = fetched.into_iter().collect::<Result<Vec<_>>>()?;
+    squats.sort_by_key(|(found, _)| std::cmp::Reverse(found.downloads));
+    let kind = |found: &Crate| variants[&typo::normalize(&found.name)];
+    let same_owner =
+        |owners: &[crates_io_api::User]| owners.iter().any(|owner| owner_ids.contains(&owner.id));
+
+    if output.output == OutputFormat::Json {
+        let json = json!({
+            "crate": crate_name,
+            "checked": variants.len(),
+            "registered": squats
+                .iter()
+                .map(|(found, owners)| {
+                    json!({
+                        "name": found.name,
+                        "kind": kind(found),
+                        "downloads": found.downloads,
+                        "created_at": found.created_at,
+                        "owners": owners.iter().map(|owner| &owner.login).collect::<Vec<_>>(),
+                        "same_owner": same_owner(owners),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        });
+        return write_json(output, &json).await;
+    }
+    if output.output == OutputFormat::Table {
+        println!(
+            "{} of {} misspellings of {} are registered",
+            squats.len(),
+            variants.len(),
+            crate_name
+        );
+    }
+    let rows = squats
+        .iter()
+        .map(|(found, owners)| {
+            vec![
+                found.name.clone(),
+                kind(found).to_string(),
+                found.downloads.to_string(),
+                found.created_at.format("%Y-%m-%d").to_string(),
+                owners.iter().map(|owner| owner.login.as_str()).join(", "),
+                if same_owner(owners) { "yes" } else { "no" }.to_string(),
+            ]
+        })
+        .collect();
+    let headers = [
+        "Crate Name",
+        "Misspelling",
+        "Download Count",
+        "Created",
+        "Owners",
+        "Same Owner",
+    ]
+    .iter()
+    .map(|header| header.to_string())
+    .collect();
+    print_rows(output, headers, rows).await
+}
+
+fn default_version(found: &Crate) -> String {
+    found
+        .max_stable_version
+        .clone()
+        .unwrap_or(found.max_version.clone())
+}
+
+async fn docs_builds(targets: Vec<(String, String)>) -> Result<Vec<Option<docsrs::Build>>> {
+    let client = docsrs::Client::new()?;
+    let pb = new_spinner();
+    pb.set_message("Fetching docs.rs builds...");
+    let builds = stream::iter(targets)
+        .map(|(crate_name, version)| {
+            let client = client.clone();
+            async move { client.latest_build(&crate_name, &version).await }
+        })
+        .buffered(config::concurrency())
+        .collect::<Vec<_>>()
+        .await;
+    pb.finish_and_clear();
+    builds.into_iter().collect()
+}
+
+fn extra_columns(
+    docs: Option<&HashMap<String, Option<docsrs::Build>>>,
+    repos: Option<&HashMap<String, Option<github::Repo>>>,
+    advisories: Option<&HashMap<String, Vec<advisory::Advisory>>>,
+) -> Vec<(&'static str, HashMap<String, String>)> {
+    let mut columns = Vec::new();
+    if let Some(docs) = docs {
+        let markers = docs
+            .iter()
+            .map(|(name, build)| {
+                let marker = build.as_ref().map_or("-", docsrs::Build::marker);
+                (name.clone(), marker.to_string())
+            })
+            .collect();
+        columns.push(("Docs", markers));
+    }
+    if let Some(repos) = repos {
+        let column = |cell: fn(&github::Repo) -> String| {
+            repos
+                .iter()
+                .map(|(name, repo)| (name.clone(), repo.as_ref().map_or("-".to_string(), cell)))
+                .collect::<HashMap<_, _>>()
+        };
+        columns.push(("Stars", column(|repo| repo.stars.to_string())));
+        columns.push(("Open Issues", column(|repo| repo.open_issues.to_string())));
+        columns.push((
+            "Last Commit",
+            column(|repo| {
+                repo.last_commit
+                    .map_or("-".to_string(), |date| date.format("%Y-%m-%d").to_string())
+            }),
+        ));
+    }
+    if let Some(advisories) = advisories {
+        let ids = advisories
+            .iter()
+            .map(|(name, advisories)| {
+                (
+                    name.clone(),
+                    advisory_ids(&advisories.iter().collect::<Vec<_>>()),
+                )
+            })
+            .collect();
+        columns.push(("Advisories", ids));
+    }
+    columns
+}
+
+async fn github_repos(crates: &[Crate]) -> Result<Vec<Option<github::Repo>>> {
+    let client = github::Client::new()?;
+    let pb = new_spinner();
+    pb.set_message("Fetching github repositories...");
+    let repos = stream::iter(crates.iter().map(|c| c.repository.clone()))
+        .map(|repository| {
+            let client = client.clone();
+            async move {
+                match repository {
+                    Some(repository) => client.repo(&repository).await,
+                    None => Ok(None),
+                }
+            }
+        })
+        .buffered(config::concurrency())
+        .collect::<Vec<_>>()
+        .await;
+    pb.finish_and_clear();
+    repos.into_iter().collect()
+}
+
+fn github_summary(repo: &Option<github::Repo>) -> String {
+    let Some(repo) = repo else {
+        return "no github repository".to_string();
+    };
+    let mut summary = format!(
+        "{} ★ {}, {} open issues",
+        repo.full_name, repo.stars, repo.open_issues
+    );
+    if let Some(last_commit) = repo.last_commit {
+        summary.push_str(&format!(
+            ", last commit on {}",
+            last_commit.format("%Y-%m-%d")
+        ));
+    }
+    if repo.archived {
+        summary.push_str(", archived");
+    }
+    summary
+}
+
+async fn crate_advisories(crate_names: Vec<String>) -> Result<Vec<Vec<advisory::Advisory>>> {
+    let client = advisory::Client::new()?;
+    let pb = new_spinner();
+    pb.set_message("Fetching rustsec advisories...");
+    let advisories = stream::iter(crate_names)
+        .map(|crate_name| {
+            let client = client.clone();
+            async move { client.advisories(&crate_name).await }
+        })
+        .buffered(config::concurrency())
+        .collect::<Vec<_>>()
+        .await;
+    pb.finish_and_clear();
+    advisories.into_iter().collect()
+}
+
+fn open_advisories<'a>(
+    advisories: &'a [advisory::Advisory],
+    versions: &[String],
+) -> Vec<&'a advisory::Advisory> {
+    let versions = versions
+        .iter()
+        .filter_map(|version| semver::Version::parse(version))
+        .collect::<Vec<_>>();
+    advisories
+        .iter()
+        .filter(|advisory| versions.iter().any(|version| advisory.affects(version)))
+        .collect()
+}
+
+// unlike the other repository columns, a rate limited github only hides archived repositories
+async fn maintenance_repos(crates: &[Crate]) -> Vec<Option<github::Repo>> {
+    github_repos(crates).await.unwrap_or_else(|err| {
+        tracing::warn!("{}, archived repositories are not detected", err);
+        vec![None; crates.len()]
+    })
+}
+
+fn unmaintained_reasons(
+    api_crate: &CrateResponse,
+    repo: &Option<github::Repo>,
+    years: u32,
+) -> Vec<String> {
+    let mut reasons = Vec::new();
+    let cutoff = Utc::now() - chrono::Duration::days(365 * years as i64);
+    let last_release = api_crate
+        .versions
+        .iter()
+        .map(|version| version.created_at)
+        .max();
+    if let Some(last_release) = last_release.filter(|last_release| *last_release < cutoff) {
+        reasons.push(format!(
+            "no release since {}",
+            last_release.format("%Y-%m-%d")
+        ));
+    }
+    if repo.as_ref().is_some_and(|repo| repo.archived) {
+        reasons.push("archived repository".to_string());
+    }
+    reasons
+}
+
+fn print_unmaintained_count(unmaintained: usize, total: usize, years: u32) {
+    println!(
+        "{} of {} dependencies look unmaintained, without a release in {} year{} or archived",
+        unmaintained,
+        total,
+        years,
+        if years == 1 { "" } else { "s" }
+    );
+}
+
+fn unmaintained_summary(reasons: &[String]) -> String {
+    match reasons.is_empty() {
+        true => "-".to_string(),
+        false => reasons.join(", "),
+    }
+}
+
+fn advisory_ids(advisories: &[&advisory::Advisory]) -> String {
+    match advisories.is_empty() {
+        true => "-".to_string(),
+        false => advisories.iter().map(|advisory| &advisory.id).join(", "),
+    }
+}
+
+async fn handle_advisories_command(output: &OutputArgs, crate_name: String) -> Result<()> {
+    let client = new_client()?;
+    let api_crate = client
+        .get_crate(&crate_name)
+        .await
+        .map_err(crate_not_found(&crate_name))?;
+    let latest = default_version(&api_crate.crate_data);
+    let mut advisories = crate_advisories(vec![crate_name.clone()])
+        .await?
+        .pop()
+        .unwrap_or_default();
+    advisories.sort_by_key(|advisory| std::cmp::Reverse(advisory.published));
+    let latest_version = semver::Version::parse(&latest);
+    let status = |advisory: &advisory::Advisory| match (&advisory.withdrawn, &latest_version) {
+        (Some(_), _) => "withdrawn",
+        (None, Some(version)) if advisory.affects(version) => "open",
+        (None, Some(_)) => "patched",
+        (None, None) => "unknown",
+    };
+
+    if output.output == OutputFormat::Json {
+        let json = json!({
+            "crate": crate_name,
+            "latest_version": latest,
+            "advisories": advisories
+                .iter()
+                .map(|advisory| {
+                    json!({
+                        "id": advisory.id,
+                        "aliases": advisory.aliases,
+                        "kind": advisory.kind,
+                        "status": status(advisory),
+                        "summary": advisory.summary,
+                        "published": advisory.published,
+                        "patched": advisory.patched,
+                        "url": advisory.url,
+                    })
+                })
+                .collect::<Vec<_>>(),
+        });
+        return write_json(output, &json).await;
+    }
+    let rows = advisories
+        .iter()
+        .map(|advisory| {
+            vec![
+                advisory.id.clone(),
+                advisory.kind.clone(),
+                status(advisory).to_string(),
+                advisory
+                    .published
+                    .map_or("-".to_string(), |date| date.format("%Y-%m-%d").to_string()),
+                match advisory.patched.is_empty() {
+                    true => "-".to_string(),
+                    false => advisory.patched.join(", "),
+                },
+                advisory.summary.clone(),
+            ]
+        })
+        .collect();
+    let headers = ["ID", "Kind", "Status", "Published", "Patched", "Summary"]
+        .iter()
+        .map(|header| header.to_string())
+        .collect();
+    print_rows(output, headers, rows).await
+}
+
+// each signal scores 0 to 10, the total is the share of points over the known signals
+async fn handle_health_command(output: &OutputArgs, crate_name: String) -> Result<()> {
+    let client = new_client()?;
+    let api_crate = client
+        .get_crate(&crate_name)
+        .await
+        .map_err(crate_not_found(&crate_name))?;
+    let downloads = client
+        .crate_downloads(&crate_name)
+        .await
+        .context(format!("can not get downloads of crate {}", crate_name))?;
+    let dependents = client
+        .crate_reverse_dependency_count(&crate_name)
+        .await
+        .context(format!("can not get dependents of crate {}", crate_name))?;
+    let latest = default_version(&api_crate.crate_data);
+    // docs.rs, osv and github are scored as unknown when they can not be reached
+    let build = docs_builds(vec![(crate_name.clone(), latest.clone())])
+        .await
+        .map(|mut builds| builds.pop().flatten());
+    let advisories = crate_advisories(vec![crate_name.clone()])
+        .await
+        .map(|mut advisories| advisories.pop().unwrap_or_default());
+    let repo = github_repos(std::slice::from_ref(&api_crate.crate_data)).await;
+
+    let today = Utc::now().date_naive();
+    let daily = daily_downloads(&downloads);
+    let window = |from: u64, to: u64| {
+        (from..to)
+            .map(|days_ago| today - Days::new(days_ago + 1))
+            .map(|date| daily.get(&date).copied().unwrap_or(0))
+            .sum::<u64>()
+    };
+    let (current, previous) = (window(0, 30), window(30, 60));
+    let trend_score = match (current, previous) {
+        (0, 0) => 0,
+        (_, 0) => 10,
+        (current, previous) => match current as f64 / previous as f64 {
+            ratio if ratio >= 1.0 => 10,
+            ratio if ratio >= 0.9 => 8,
+            ratio if ratio >= 0.75 => 5,
+            ratio if ratio >= 0.5 => 2,
+            _ => 0,
+        },
+    };
+    let last_release = api_crate
+        .versions
+        .iter()
+        .filter(|version| !version.yanked)
+        .map(|version| version.created_at)
+        .max();
+    let release_age = last_release.map(|released| (Utc::now() - released).num_days());
+    let release_score = match release_age {
+        Some(0..=90) => 10,
+        Some(91..=180) => 8,
+        Some(181..=365) => 6,
+        Some(366..=730) => 3,
+        _ => 0,
+    };
+    let (docs_value, docs_score) = match &build {
+        Err(err) => (format!("unavailable: {}", err), None),
+        Ok(build) => {
+            let score = match build.as_ref().map(|build| build.status) {
+                Some(docsrs::BuildStatus::Success) => 10,
+                Some(docsrs::BuildStatus::InProgress) => 5,
+                _ => 0,
+            };
+            (format!("{} {}", latest, docs_summary(build)), Some(score))
+        }
+    };
+    let open = advisories
+        .as_ref()
+        .map(|advisories| open_advisories(advisories, std::slice::from_ref(&latest)));
+    let (advisory_value, advisory_score) = match &open {
+        Err(err) => (format!("unavailable: {}", err), None),
+        Ok(open) if open.is_empty() => ("none open".to_string(), Some(10)),
+        Ok(open) => {
+            let value = open
+                .iter()
+                .map(|advisory| format!("{} ({})", advisory.id, advisory.kind))
+                .join(", ");
+            let score = match open.iter().all(|advisory| advisory.kind != "vulnerability") {
+                true => 5,
+                false => 0,
+            };
+            (value, Some(score))
+        }
+    };
+    let dependents_score = match dependents {
+        1000.. => 10,
+        100..=999 => 8,
+        10..=99 => 5,
+        1..=9 => 2,
+        0 => 0,
+    };
+    let (repo_value, repo_score) = match &repo {
+        Err(err) => (format!("unavailable: {}", err), None),
+        Ok(repos) => match repos.first().and_then(Option::as_ref) {
+            None => ("no github repository".to_string(), None),
+            Some(repo) if repo.archived => (format!("{} is archived", repo.full_name), Some(0)),
+            Some(repo) => {
+                let age = repo
+                    .last_commit
+                    .map(|last_commit| (Utc::now() - last_commit).num_days());
+                let score = match age {
+                    Some(0..=30) => 10,
+                    Some(31..=90) => 8,
+                    Some(91..=365) => 5,
+                    Some(366..=730) => 2,
+                    _ => 0,
+                };
+                let value = age.map_or("no commits".to_string(), |age| {
+                    format!("last commit {} days ago", age)
+                });
+                (value, Some(score))
+            }
+        },
+    };
+    let signals = [
+        (
+            "Downloads trend",
+            format!(
+                "{} in the last 30 days, {}",
+                current,
+                trend(current, previous)
+            ),
+            Some(trend_score),
+        ),
+        (
+            "Releases",
+            release_age.map_or("no releases".to_string(), |age| {
+                format!("last release {} days ago", age)
+            }),
+            Some(release_score),
+        ),
+        ("Docs", docs_value, docs_score),
+        ("Advisories", advisory_value, advisory_score),
+        ("Dependents", dependents.to_string(), Some(dependents_score)),
+        ("Repository", repo_value, repo_score),
+    ];
+    let known = signals
+        .iter()
+        .filter_map(|(_, _, score)| *score)
+        .collect::<Vec<_>>();
+    let total = known.iter().sum::<u32>() * 100 / (known.len() as u32 * 10);
+
+    if output.output == OutputFormat::Json {
+        let json = json!({
+            "crate": crate_name,
+            "score": total,
+            "signals": signals
+                .iter()
+                .map(|(signal, value, score)| {
+                    json!({"signal": signal, "value": value, "score": score})
+                })
+                .collect::<Vec<_>>(),
+        });
+        return write_json(output, &json).await;
+    }
+    let mut rows = signals
+        .iter()
+        .map(|(signal, value, score)| {
+            vec![
+                signal.to_string(),
+                value.clone(),
+                score.map_or("-".to_string(), |score| format!("{}/10", score)),
+            ]
+        })
+        .collect::<Vec<_>>();
+    rows.push(vec![
+        "Total".to_string(),
+        format!("{} of {} signals known", known.len(), signals.len()),
+        format!("{}/100", total),
+    ]);
+    let headers = ["Signal", "Value", "Score"]
+        .iter()
+        .map(|header| header.to_string())
+        .collect();
+    print_rows(output, headers, rows).await
+}
+
+async fn dependency_tree(crate_name: &str) -> Result<Vec<tree::Node>> {
+    let client = new_client()?;
+    let api_crate = client
+        .get_crate(crate_name)
+        .await
+        .map_err(crate_not_found(crate_name))?;
+    let pb = new_spinner();
+    pb.set_message(format!("Resolving dependency tree of {}...", crate_name));
+    pb.enable_steady_tick(Duration::from_millis(500));
+    let nodes = tree::resolve(&client, api_crate).await;
+    pb.finish_and_clear();
+    nodes
+}
+
+async fn handle_licenses_command(output: &OutputArgs, crate_name: String) -> Result<()> {
+    const LISTED_CRATES: usize = 5;
+    let nodes = dependency_tree(&crate_name).await?;
+    let root = &nodes[0];
+    let kind = |node: &tree::Node| license::classify(node.license.as_deref());
+    let mut licenses: BTreeMap<Option<&str>, Vec<&tree::Node>> = BTreeMap::new();
+    for node in &nodes {
+        licenses
+            .entry(node.license.as_deref())
+            .or_default()
+            .push(node);
+    }
+    let mut licenses = licenses.into_iter().collect::<Vec<_>>();
+    licenses.sort_by_key(|(license, nodes)| {
+        (
+            std::cmp::Reverse(license::classify(*license)),
+            std::cmp::Reverse(nodes.len()),
+        )
+    });
+    let flagged = nodes
+        .iter()
+        .filter(|node| kind(node) > license::Kind::WeakCopyleft)
+        .count();
+
+    if output.output == OutputFormat::Json {
+        let json = json!({
+            "crate": root.name,
+            "version": root.version,
+            "flagged": flagged,
+            "crates": nodes
+                .iter()
+                .map(|node| {
+                    json!({
+                        "name": node.name,
+                        "version": node.version,
+                        "license": node.license,
+                        "kind": kind(node).label(),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        });
+        return write_json(output, &json).await;
+    }
+    if output.output == OutputFormat::Table {
+        println!(
+            "{} crates in the dependency tree of {} {}, {} with copyleft or unknown licenses",
+            nodes.len(),
+            root.name,
+            root.version,
+            flagged
+        );
+    }
+    let rows = licenses
+        .iter()
+        .map(|(license, nodes)| {
+            let mut names = nodes
+                .iter()
+                .take(LISTED_CRATES)
+                .map(|node| format!("{} {}", node.name, node.version))
+                .join(", ");
+            if nodes.len() > LISTED_CRATES {
+                names.push_str(&format!(" and {} more", nodes.len() - LISTED_CRATES));
+            }
+            vec![
+                license.unwrap_or("-").to_string(),
+                license::classify(*license).label().to_string(),
+                nodes.len().to_string(),
+                names,
+            ]
+        })
+        .collect();
+    let headers = ["License", "Kind", "Count", "Crates"]
+        .iter()
+        .map(|header| header.to_string())
+        .collect();
+    print_rows(output, headers, rows).await
+}
+
+async fn handle_duplicates_command(output: &OutputArgs, crate_name: String) -> Result<()> {
+    let nodes = dependency_tree(&crate_name).await?;
+    let root = &nodes[0];
+    let duplicates = tree::duplicates(&nodes);
+
+    if output.output == OutputFormat::Json {
+        let json = json!({
+            "crate": root.name,
+            "version": root.version,
+            "duplicates": duplicates
+                .iter()
+                .map(|(name, versions)| {
+                    json!({
+                        "name": name,
+                        "versions": versions
+                            .iter()
+                            .map(|node| {
+                                json!({
+                                    "version": node.version,
+                                    "required_by": node
+                                        .parents
+                                        .iter()
+                                        .map(|edge| {
+                                            json!({
+                                                "name": edge.name,
+                                                "version": edge.version,
+                                                "req": edge.req,
+                                            })
+                                        })
+                                        .collect::<Vec<_>>(),
+                                })
+                            })
+                            .collect::<Vec<_>>(),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        });
+        return write_json(output, &json).await;
+    }
+    if output.output == OutputFormat::Table {
+        println!(
+            "{} crates appear with incompatible versions in the dependency tree of {} {}",
+            duplicates.len(),
+            root.name,
+            root.version
+        );
+    }
+    let rows = duplicates
+        .iter()
+        .flat_map(|(name, versions)| {
+            versions.iter().map(move |node| {
+                vec![
+                    name.to_string(),
+                    node.version.clone(),
+                    node.parents
+                        .iter()
+                        .map(|edge| format!("{} {} ({})", edge.name, edge.version, edge.req))
+                        .join(", "),
+                ]
+            })
+        })
+        .collect();
+    let headers = ["Crate Name", "Version", "Required By"]
+        .iter()
+        .map(|header| header.to_string())
+        .collect();
+    print_rows(output, headers, rows).await
+}
+
+fn docs_summary(build: &Option<docsrs::Build>) -> String {
+    let Some(build) = build else {
+        return "not built on docs.rs".to_string();
+    };
+    let mut summary = match build.status {
+        docsrs::BuildStatus::Success => format!("{} built", build.marker()),
+        docsrs::BuildStatus::Failure => format!("{} failed", build.marker()),
+        docsrs::BuildStatus::InProgress => format!("{} building", build.marker()),
+    };
+    if let Some(built_at) = build.built_at {
+        summary.push_str(&format!(" on {}", built_at.format("%Y-%m-%d")));
+    }
+    if let Some(rustc_version) = &build.rustc_version {
+        summary.push_str(&format!(" with {}", rustc_version));
+    }
+    summary
+}
+
+async fn handle_benchmark_command(
+    args: &BenchmarkArgs,
+    output: &OutputArgs,
+    crate_name: String,
+) -> Result<()> {
+    let client = new_client()?;
+    let api_crate = client
+        .get_crate(&crate_name)
+        .await
+        .map_err(crate_not_found(&crate_name))?;
+    if api_crate.categories.is_empty() {
+        return Err(Error::Usage(format!(
+            "crate {} has no categories to compare against",
+            crate_name
+        )));
+    }
+    // recent downloads cover the last 90 days
+    let daily = |found: &Crate| found.recent_downloads.unwrap_or(0) as f64 / 90.0;
+    let own = daily(&api_crate.crate_data);
+
+    let pb = new_spinner();
+    let mut benchmarks = Vec::new();
+    for category in &api_crate.categories {
+        pb.set_message(format!("Fetching crates of {}...", category.slug));
+        let mut peers = Vec::new();
+        let mut total = 0;
+        for page_number in 1.. {
+            let page = client
+                .crates_page(&[
+                    ("category", category.slug.clone()),
+                    ("sort", "recent-downloads".to_string()),
+                    ("page", page_number.to_string()),
+                    ("per_page", "100".to_string()),
+                ])
+                .await
+                .context(format!("can not get crates of category {}", category.slug))?;
+            total = page.meta.total;
+            let last = page.crates.len() < 100;
+            peers.extend(
+                page.crates
+                    .iter()
+                    .filter(|found| found.name != api_crate.crate_data.name)
+                    .map(daily),
+            );
+            if last || peers.len() as u64 >= args.sample {
+                break;
+            }
+        }
+        peers.truncate(args.sample as usize);
+        peers.sort_by(f64::total_cmp);
+        let mean = (!peers.is_empty()).then(|| peers.iter().sum::<f64>() / peers.len() as f64);
+        let percentile = (!peers.is_empty())
+            .then(|| peers.iter().filter(|peer| **peer < own).count() as f64 / peers.len() as f64);
+        benchmarks.push((
+            category.slug.clone(),
+            peers.len(),
+            total,
+            median(&peers),
+            mean,
+            percentile,
+        ));
+    }
+    pb.finish_and_clear();
+
+    let ratio = |base: &Option<f64>| base.filter(|base| *base > 0.0).map(|base| own / base);
+    if output.output == OutputFormat::Json {
+        let round = |value: Option<f64>| value.map(|value| (value * 10.0).round() / 10.0);
+        let json = json!({
+            "crate": crate_name,
+            "daily_downloads": round(Some(own)),
+            "categories": benchmarks
+                .iter()
+                .map(|(category, sampled, total, median, mean, percentile)| {
+                    json!({
+                        "category": category,
+                        "sampled": sampled,
+                        "total": total,
+                        "median_daily_downloads": round(*median),
+                        "mean_daily_downloads": round(*mean),
+                        "versus_median": round(ratio(median)),
+                        "percentile": percentile.map(|share| (share * 100.0).round()),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        });
+        return write_json(output, &json).await;
+    }
+    let decimal =
+        |value: &Option<f64>| value.map_or("-".to_string(), |value| format!("{:.1}", value));
+    let rows = benchmarks
+        .iter()
+        .map(|(category, sampled, total, median, mean, percentile)| {
+            vec![
+                category.clone(),
+                format!("{} of {}", sampled, total),
+                format!("{:.1}", own),
+                decimal(median),
+                decimal(mean),
+                ratio(median).map_or("-".to_string(), |ratio| format!("{:.1}x", ratio)),
+                percentile.map_or("-".to_string(), |share| format!("{:.0}%", share * 100.0)),
+            ]
+        })
+        .collect();
+    let headers = [
+        "Category",
+        "Sampled",
+        "Daily Downloads",
+        "Median Daily",
+        "Mean Daily",
+        "Versus Median",
+        "Percentile",
+    ]
+    .iter()
+    .map(|header| header.to_string())
+    .collect();
+    print_rows(output, headers, rows).await
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1_048_575 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+async fn handle_pattern_command(output: &OutputArgs, crate_name: String) -> Result<()> {
+    const BAR_WIDTH: f64 = 30.0;
+    let client = new_client()?;
+    let downloads = client
+        .crate_downloads(&crate_name)
+        .await
+        .map_err(crate_not_found(&crate_name))?;
+    let today = Utc::now().date_naive();
+    let daily = daily_downloads(&downloads);
+    let mut weekdays = [(0u64, 0u64); 7];
+    // days without downloads are missing from the map but still count
+    for date in (today - Days::new(90))
+        .iter_days()
+        .take_while(|date| *date < today)
+    {
+        let weekday = &mut weekdays[date.weekday().num_days_from_monday() as usize];
+        weekday.0 += daily.get(&date).copied().unwrap_or(0);
+        weekday.1 += 1;
+    }
+    let averages = weekdays
+        .iter()
+        .map(|(total, days)| *total as f64 / (*days).max(1) as f64)
+        .collect::<Vec<_>>();
+    let sum = averages.iter().sum::<f64>().max(1.0);
+    let max = averages.iter().cloned().fold(0.0, f64::max).max(1.0);
+    let names = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+    match output.output {
+        OutputFormat::Json => {
+            let json = json!({
+                "crate": crate_name,
+                "weekdays": names
+                    .iter()
+                    .zip(&averages)
+                    .zip(&weekdays)
+                    .map(|((name, average), (_, days))| {
+                        json!({"weekday": name, "days": days, "average": average.round() as u64})
+                    })
+                    .collect::<Vec<_>>(),
+            });
+            write_json(output, &json).await
+        }
+        OutputFormat::Graph => {
+            let mut chart = format!("{} average downloads per weekday\n", crate_name);
+            for (name, average) in names.iter().zip(&averages) {
+                let bar = "█".repeat((average / max * BAR_WIDTH).round() as usize);
+                chart.push_str(&format!("{} {} {}\n", name, bar, average.round()));
+            }
+            write_output(output, &chart).await
+        }
+        format => {
+            let mut headers = vec![
+                "Weekday".to_string(),
+                "Average".to_string(),
+                "Share".to_string(),
+            ];
+            if format == OutputFormat::Table {
+                headers.push(String::new());
+            }
+            let rows = names
+                .iter()
+                .zip(&averages)
+                .map(|(name, average)| {
+                    let mut row = vec![
+                        name.to_string(),
+                        average.round().to_string(),
+                        format!("{:.1}%", average / sum * 100.0),
+                    ];
+                    if format == OutputFormat::Table {
+                        row.push("█".repeat((average / max * BAR_WIDTH).round() as usize));
+                    }
+                    row
+                })
+                .collect::<Vec<_>>();
+            print_rows(output, headers, rows).await
+        }
+    }
+}
+
+fn compact_number(count: u64) -> String {
+    let (value, suffix) = match count {
+        0..=999 => return count.to_string(),
+        1_000..=999_949 => (count as f64 / 1_000.0, "k"),
+        _ => (count as f64 / 1_000_000.0, "M"),
+    };
+    let value = format!("{:.1}", value);
+    format!("{}{}", value.trim_end_matches(".0"), suffix)
+}
+
+async fn handle_list_command(
+    args: &ListArgs,
+    output: &OutputArgs,
+    list_name: String,
+) -> Result<()> {
+    let list = watch_list(&list_name)?;
+    let client = new_client()?;
+    let crates = get_crates(&client, &list.crates)
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+
+    report_crates(
+        &client,
+        &args.report,
+        output,
+        crates,
+        ("list", json!(list_name)),
+        &format!("{} list", list_name),
+    )
+    .await
+}
+
+async fn handle_stdin_command(args: &StdinArgs, output: &OutputArgs) -> Result<()> {
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .await
+        .context("can not read stdin")?;
+    let crate_names = parse_crate_names(&input)?;
+    if crate_names.is_empty() {
+        return Err(Error::Usage("no crate names given on stdin".to_string()));
+    }
+    let client = new_client()?;
+    let mut crates = Vec::new();
+    for result in get_crates(&client, &crate_names).await {
+        match result {
+            Ok(api_crate) => crates.push(api_crate),
+            Err(Error::CrateNotFound(crate_name)) => {
+                tracing::warn!("{} is not on crates.io, skipping it", crate_name)
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    report_crates(
+        &client,
+        &args.report,
+        output,
+        crates,
+        ("source", json!("stdin")),
+        "stdin",
+    )
+    .await
+}
+
+async fn handle_project_command(args: &ProjectArgs, output: &OutputArgs) -> Result<()> {
+    let crate_names = manifest::workspace_crates(&args.path)?;
+    if crate_names.is_empty() {
+        return Err(Error::Usage(format!(
+            "no publishable crates found in {}",
+            args.path.display()
+        )));
+    }
+    let client = new_client()?;
+    let mut crates = Vec::new();
+    for result in get_crates(&client, &crate_names).await {
+        match result {
+            Ok(api_crate) => crates.push(api_crate),
+            Err(Error::CrateNotFound(crate_name)) => {
+                tracing::warn!("{} is not published on crates.io yet", crate_name)
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    report_crates(
+        &client,
+        &args.report,
+        output,
+        crates,
+        ("project", json!(args.path)),
+        &args.path.display().to_string(),
+    )
+    .await
+}
+
+async fn handle_deps_command(args: &DepsArgs, output: &OutputArgs) -> Result<()> {
+    let mut dependencies = manifest::dependencies(&args.path)?
+        .into_iter()
+        .map(|dependency| (dependency.name, dependency.kinds.join(", ")))
+        .collect::<Vec<_>>();
+    let locked = manifest::locked_versions(&args.path)?;
+    if args.all {
+        for name in locked.keys() {
+            if !dependencies
+                .iter()
+                .any(|(dependency, _)| dependency == name)
+            {
+                dependencies.push((name.clone(), "transitive".to_string()));
+            }
+        }
+    }
+    if dependencies.is_empty() {
+        return Err(Error::Usage(format!(
+            "no crates.io dependencies found in {}",
+            args.path.display()
+        )));
+    }
+    let client = new_client()?;
+
+    let pb = new_spinner();
+    pb.set_message("Fetching dependency infos...");
+    let fetched = stream::iter(dependencies)
+        .map(|(crate_name, kinds)| {
+            let client = client.clone();
+            let inner_pb = pb.clone();
+            async move {
+                inner_pb.set_message(format!("Fetching {} info...", crate_name));
+                let api_crate = client
+                    .get_crate(&crate_name)
+                    .await
+                    .map_err(crate_not_found(&crate_name))?;
+                let dependents = client
+                    .crate_reverse_dependency_count(&crate_name)
+                    .await
+                    .context(format!("can not get dependents of crate {}", crate_name))?;
+                Ok::<_, Error>((kinds, api_crate, dependents))
+            }
+        })
+        .buffered(config::concurrency())
+        .collect::<Vec<_>>()
+        .await;
+    pb.finish_and_clear();
+    let fetched = fetched.into_iter().collect::<Result<Vec<_>>>()?;
+    let advisories = match args.advisories {
+        true => {
+            crate_advisories(
+                fetched
+                    .iter()
+                    .map(|(_, api_crate, _)| api_crate.crate_data.name.clone())
+                    .collect(),
+            )
+            .await?
+        }
+        false => vec![Vec::new(); fetched.len()],
+    };
+    let repos = match args.unmaintained {
+        Some(_) => {
+            maintenance_repos(
+                &fetched
+                    .iter()
+                    .map(|(_, api_crate, _)| api_crate.crate_data.clone())
+                    .collect::<Vec<_>>(),
+            )
+            .await
+        }
+        None => vec![None; fetched.len()],
+    };
+    let mut fetched = fetched
+        .into_iter()
+        .zip(advisories)
+        .zip(repos)
+        .map(|(((kinds, api_crate, dependents), advisories), repo)| {
+            // unlocked dependencies are checked at the latest version
+            let versions = locked
+                .get(&api_crate.crate_data.name)
+                .cloned()
+                .unwrap_or_else(|| vec![default_version(&api_crate.crate_data)]);
+            let open = open_advisories(&advisories, &versions)
+                .into_iter()
+                .cloned()
+                .collect::<Vec<_>>();
+            let unmaintained = args.unmaintained.map_or(Vec::new(), |years| {
+                unmaintained_reasons(&api_crate, &repo, years)
+            });
+            (kinds, api_crate, dependents, open, unmaintained)
+        })
+        .collect::<Vec<_>>();
+    select_rows(
+        &mut fetched,
+        &args.rows,
+        |(_, api_crate, _, _, _)| api_crate.crate_data.name.clone(),
+        |(_, api_crate, _, _, _), key| match key {
+            SortKey::Daily => api_crate.crate_data.recent_downloads.unwrap_or(0),
+            _ => api_crate.crate_data.downloads,
+        },
+    );
+
+    let last_release = |api_crate: &CrateResponse| {
+        api_crate
+            .versions
+            .iter()
+            .map(|version| version.created_at.date_naive())
+            .max()
+    };
+    let locked_version = |crate_name: &str| {
+        locked
+            .get(crate_name)
+            .map(|versions| versions.join(", "))
+            .unwrap_or_default()
+    };
+
+    if output.output == OutputFormat::Json {
+        let json = json!({
+            "project": args.path,
+            "dependencies": fetched
+                .iter()
+                .map(|(kinds, api_crate, dependents, open, unmaintained)| {
+                    let mut entry = json!({
+                        "name": api_crate.crate_data.name,
+                        "locked": locked.get(&api_crate.crate_data.name),
+                        "kind": kinds,
+                        "downloads": api_crate.crate_data.downloads,
+                        "last_release": last_release(api_crate),
+                        "dependents": dependents,
+                    });
+                    if args.advisories {
+                        entry["advisories"] = json!(open);
+                    }
+                    if args.unmaintained.is_some() {
+                        entry["unmaintained"] = json!(unmaintained);
+                    }
+                    entry
+                })
+                .collect::<Vec<_>>(),
+        });
+        return write_json(output, &json).await;
+    }
+    if let (Some(years), OutputFormat::Table) = (args.unmaintained, output.output) {
+        print_unmaintained_count(
+            fetched
+                .iter()
+                .filter(|(_, _, _, _, unmaintained)| !unmaintained.is_empty())
+                .count(),
+            fetched.len(),
+            years,
+        );
+    }
+
+    let rows = fetched
+        .iter()
+        .map(|(kinds, api_crate, dependents, open, unmaintained)| {
+            let mut row = vec![
+                api_crate.crate_data.name.clone(),
+                locked_version(&api_crate.crate_data.name),
+                kinds.clone(),
+                api_crate.crate_data.downloads.to_string(),
+                last_release(api_crate).map_or(String::new(), |date| date.to_string()),
+                dependents.to_string(),
+            ];
+            if args.advisories {
+                row.push(advisory_ids(&open.iter().collect::<Vec<_>>()));
+            }
+            if args.unmaintained.is_some() {
+                row.push(unmaintained_summary(unmaintained));
+            }
+            row
+        })
+        .collect::<Vec<_>>();
+    let mut headers = vec![
+        "Crate Name".to_string(),
+        "Locked".to_string(),
+        "Kind".to_string(),
+        "Download Count".to_string(),
+        "Last Release".to_string(),
+        "Dependents".to_string(),
+    ];
+    if args.advisories {
+        headers.push("Advisories".to_string());
+    }
+    if args.unmaintained.is_some() {
+        headers.push("Unmaintained".to_string());
+    }
+    print_rows(output, headers, rows).await
+}
+
+async fn get_crates(client: &api::Client, crate_names: &[String]) -> Vec<Result<Crate>> {
+    let pb = new_spinner();
+    pb.set_message("Fetching crates infos...");
+    let fetched = stream::iter(crate_names.to_vec())
+        .map(|crate_name| {
+            let client = client.clone();
+            let inner_pb = pb.clone();
+            async move {
+                inner_pb.set_message(format!("Fetching {} info...", crate_name));
+                client
+                    .get_crate(&crate_name)
+                    .await
+                    .map(|response| response.crate_data)
+                    .map_err(crate_not_found(&crate_name))
+            }
+        })
+        .buffered(config::concurrency())
+        .collect::<Vec<_>>()
+        .await;
+    pb.finish_and_clear();
+    fetched
+}
+
+async fn report_crates(
+    client: &api::Client,
+    args: &ReportArgs,
+    output: &OutputArgs,
+    mut crates: Vec<Crate>,
+    subject: (&str, serde_json::Value),
+    title: &str,
+) -> Result<()> {
+    let crate_n_day_downloads: Arc<Mutex<HashMap<String, HashMap<NaiveDate, u64>>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+
+    let mut days = window_days(&args.window, 1)?;
+    if args.window.latest_only {
+        days.drain(..days.len().saturating_sub(1));
+    }
+    let previous_days = match days.first() {
+        Some(first) if args.trend => (1..=days.len() as u64)
+            .rev()
+            .map(|offset| *first - Days::new(offset))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    // ndjson lines are written as soon as each crate is fetched, so rows can not be sorted
+    let stream_writer = match output.output {
+        OutputFormat::Ndjson => {
+            if args.rows.sort.is_some()
+                || args.rows.top.is_some()
+                || args.docs
+                || args.github
+                || args.advisories
+                || args.porcelain
+            {
+                return Err(Error::Usage(
+                    "-o ndjson streams crates as they are fetched, without --sort, --top, --docs, --github, --advisories and --porcelain"
+                        .to_string(),
+                ));
+            }
+            if let Some(min_downloads) = args.rows.min_downloads {
+                crates.retain(|c| c.downloads >= min_downloads);
+            }
+            Some(Arc::new(Mutex::new(open_output(output).await?)))
+        }
+        _ => None,
+    };
+
+    let streamed = Arc::new(Mutex::new(Vec::new()));
+    let failed = Arc::new(Mutex::new(BTreeMap::new()));
+
+    let pb = new_spinner();
+    pb.set_message("Fetching crates infos...");
+    let download_futures = stream::iter(crates.clone())
+        .map(|crate_info| {
+            let client = client.clone();
+            let n_daily_downloads = crate_n_day_downloads.clone();
+            let inner_pb = pb.clone();
+            let days_clone = [previous_days.as_slice(), days.as_slice()].concat();
+            let previous_count = previous_days.len();
+            let stream_writer = stream_writer.clone();
+            let streamed = streamed.clone();
+            let failed = failed.clone();
+            let subject = (subject.0.to_string(), subject.1.clone());
+            let trend = args.trend;
+            async move {
+                let fetched =
+                    get_crate_downloads_multi(&client, &crate_info.name, &days_clone).await;
+                if let Some(writer) = stream_writer {
+                    let mut entry = match &fetched {
+                        Ok(download_count) => {
+                            let (previous, current) = days_clone.split_at(previous_count);
+                            let count =
+                                |day: &NaiveDate| download_count.get(day).copied().unwrap_or(0);
+                            let mut entry = json!({
+                                "crate": crate_info,
+                                "downloads": current
+                                    .iter()
+                                    .map(|day| json!({"date": day.format("%Y-%m-%d").to_string(), "downloads": count(day)}))
+                                    .collect::<Vec<_>>(),
+                            });
+                            if trend {
+                                entry["previous_window_downloads"] =
+                                    json!(previous.iter().map(count).sum::<u64>());
+                            }
+                            entry
+                        }
+                        Err(err) => json!({"crate": crate_info, "error": err.to_string()}),
+                    };
+                    entry[subject.0] = subject.1;
+                    let mut writer = writer.lock().await;
+                    let _ = writer.write_all(format!("{}\n", entry).as_bytes()).await;
+                    let _ = writer.flush().await;
+                    streamed.lock().await.push(entry);
+                }
+                match fetched {
+                    Ok(download_count) => {
+                        n_daily_downloads
+                            .lock()
+                            .await
+                            .insert(crate_info.name.clone(), download_count);
+                    }
+                    Err(err) => {
+                        failed
+                            .lock()
+                            .await
+                            .insert(crate_info.name.clone(), err.to_string());
+                    }
+                }
+                inner_pb.set_message(format!("Fetching {} info...", crate_info.name));
+                inner_pb.tick();
+            }
+        })
+        .buffer_unordered(config::concurrency());
+    let interrupted = interrupt::catch(download_futures.collect::<Vec<_>>())
+        .await
+        .is_none();
+    let failed = std::mem::take(&mut *failed.lock().await);
+    // crates not fetched before Ctrl-C are left out of the report
+    let skipped = match interrupted {
+        true => {
+            pb.finish_with_message("Interrupted, showing the crates fetched so far");
+            let fetched = crate_n_day_downloads.lock().await;
+            let (kept, skipped): (Vec<_>, Vec<_>) = crates
+                .into_iter()
+                .partition(|c| fetched.contains_key(&c.name) || failed.contains_key(&c.name));
+            crates = kept;
+            skipped.into_iter().map(|c| c.name).collect::<Vec<_>>()
+        }
+        false => {
+            pb.finish_with_message("Finished gathering crate info!");
+            Vec::new()
+        }
+    };
+    let finish = |skipped: Vec<String>| {
+        print_fetch_failures(
+            &failed,
+            output.output == OutputFormat::Table && !args.porcelain,
+        );
+        if !skipped.is_empty() {
+            return Err(Error::Interrupted(skipped));
+        }
+        if args.strict && !failed.is_empty() {
+            return Err(Error::FetchFailed(failed.keys().cloned().collect()));
+        }
+        Ok(())
+    };
+    if stream_writer.is_some() {
+        let streamed = std::mem::take(&mut *streamed.lock().await);
+        append_record(output, json!(streamed)).await?;
+        return finish(skipped);
+    }
+
+    {
+        let daily_downloads = crate_n_day_downloads.lock().await;
+        let day_count = |crate_name: &str, day: &NaiveDate| {
+            daily_downloads
+                .get(crate_name)
+                .and_then(|downloads| downloads.get(day))
+                .copied()
+                .unwrap_or(0)
+        };
+        select_rows(
+            &mut crates,
+            &args.rows,
+            |c| c.name.clone(),
+            |c, key| match key {
+                SortKey::Daily => days.last().map_or(0, |day| day_count(&c.name, day)),
+                SortKey::Total => days.iter().map(|day| day_count(&c.name, day)).sum(),
+                _ => c.downloads,
+            },
+        );
+    }
+    crate_n_day_downloads
+        .lock()
+        .await
+        .retain(|crate_name, _| crates.iter().any(|c| &c.name == crate_name));
+
+    let owner_changes = match args.owners.track_owners && !interrupted {
+        true => {
+            let crate_names = crates.iter().map(|c| c.name.clone()).collect::<Vec<_>>();
+            Some(track_owners(client, &crate_names, &args.owners.state_file, output).await?)
+        }
+        false => None,
+    };
+    let docs = match args.docs && !interrupted {
+        true => {
+            let builds = docs_builds(
+                crates
+                    .iter()
+                    .map(|c| (c.name.clone(), default_version(c)))
+                    .collect(),
+            )
+            .await?;
+            Some(
+                crates
+                    .iter()
+                    .map(|c| c.name.clone())
+                    .zip(builds)
+                    .collect::<HashMap<_, _>>(),
+            )
+        }
+        false => None,
+    };
+    let repos = match args.github && !interrupted {
+        true => Some(
+            crates
+                .iter()
+                .map(|c| c.name.clone())
+                .zip(github_repos(&crates).await?)
+                .collect::<HashMap<_, _>>(),
+        ),
+        false => None,
+    };
+    let advisories = match args.advisories && !interrupted {
+        true => {
+            let crate_names = crates.iter().map(|c| c.name.clone()).collect();
+            Some(
+                crates
+                    .iter()
+                    .zip(crate_advisories(crate_names).await?)
+                    .map(|(c, advisories)| {
+                        let open = open_advisories(&advisories, &[default_version(c)]);
+                        (
+                            c.name.clone(),
+                            open.into_iter().cloned().collect::<Vec<_>>(),
+                        )
+                    })
+                    .collect::<HashMap<_, _>>(),
+            )
+        }
+        false => None,
+    };
+
+    if output.chart_file.is_some() {
+        let daily_downloads = crate_n_day_downloads.lock().await;
+        let counts = crates
+            .iter()
+            .filter(|c| !failed.contains_key(&c.name))
+            .map(|c| {
+                let counts = days
+                    .iter()
+                    .map(|day| {
+                        daily_downloads
+                            .get(&c.name)
+                            .and_then(|downloads| downloads.get(day))
+                            .copied()
+                            .unwrap_or(0) as f64
+                    })
+                    .collect::<Vec<_>>();
+                (c.name.as_str(), counts)
+            })
+            .collect::<Vec<_>>();
+        let series = counts
+            .iter()
+            .map(|(crate_name, counts)| (*crate_name, counts.as_slice()))
+            .collect::<Vec<_>>();
+        write_chart(output, &days, &series, &format!("{} downloads", title)).await?;
+    }
+
+    if args.porcelain {
+        let daily_downloads = crate_n_day_downloads.lock().await;
+        for c in &crates {
+            for day in &days {
+                let count = match failed.contains_key(&c.name) {
+                    true => "ERR".to_string(),
+                    false => daily_downloads
+                        .get(&c.name)
+                        .and_then(|downloads| downloads.get(day))
+                        .unwrap_or(&0)
+                        .to_string(),
+                };
+                println!("{}\t{}\t{}", c.name, day.format("%Y-%m-%d"), count);
+            }
+        }
+    } else if output.output == OutputFormat::Json {
+        let daily_downloads = crate_n_day_downloads.lock().await;
+        let day_counts = |crate_name: &str| {
+            days.iter()
+                .map(|day| {
+                    let count = daily_downloads
+                        .get(crate_name)
+                        .and_then(|downloads| downloads.get(day))
+                        .unwrap_or(&0);
+                    json!({"date": day.format("%Y-%m-%d").to_string(), "downloads": count})
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut json = json!({
+            "crates": crates
+                .iter()
+                .map(|c| {
+                if let Some(err) = failed.get(&c.name) {
+                    return json!({"crate": c, "downloads": null, "error": err});
+                }
+                let mut entry = json!({"crate": c, "downloads": day_counts(&c.name)});
+                if let Some(docs) = &docs {
+                    entry["docs"] = json!(docs[&c.name]);
+                }
+                if let Some(repos) = &repos {
+                    entry["github"] = json!(repos[&c.name]);
+                }
+                if let Some(advisories) = &advisories {
+                    entry["advisories"] = json!(advisories[&c.name]);
+                }
+                if args.trend {
+                    entry["previous_window_downloads"] = json!(previous_days
+                        .iter()
+                        .map(|day| daily_downloads
+                            .get(&c.name)
+                            .and_then(|downloads| downloads.get(day))
+                            .unwrap_or(&0))
+                        .sum::<u64>());
+                }
+                entry
+            })
+                .collect::<Vec<_>>(),
+            "daily_totals": days
+                .iter()
+                .map(|day| {
+                    let count = daily_downloads
+                        .values()
+                        .map(|downloads| downloads.get(day).unwrap_or(&0))
+                        .sum::<u64>();
+                    json!({"date": day.format("%Y-%m-%d").to_string(), "downloads": count})
+                })
+                .collect::<Vec<_>>(),
+            "total": crates.iter().map(|c| c.downloads).sum::<u64>(),
+        });
+        json[subject.0] = subject.1;
+        if let Some(owner_changes) = owner_changes {
+            json["owner_changes"] = owner_changes;
+        }
+        if interrupted {
+            json["skipped"] = json!(skipped);
+        }
+        write_json(output, &json).await?;
+    } else if output.output == OutputFormat::Influx {
+        let daily_downloads = crate_n_day_downloads.lock().await;
+        let lines = crates
+            .iter()
+            .filter(|c| !failed.contains_key(&c.name))
+            .map(|c| {
+                let daily = days
+                    .iter()
+                    .map(|day| {
+                        let count = daily_downloads
+                            .get(&c.name)
+                            .and_then(|downloads| downloads.get(day))
+                            .copied()
+                            .unwrap_or(0);
+                        (*day, count)
+                    })
+                    .collect::<Vec<_>>();
+                metrics::influx_lines(&c.name, None, &daily)
+            })
+            .collect::<String>();
+        write_output(output, &lines).await?;
+    } else if output.output == OutputFormat::Html {
+        let daily_downloads = crate_n_day_downloads.lock().await;
+        let fetched = crates
+            .iter()
+            .filter(|c| !failed.contains_key(&c.name))
+            .cloned()
+            .collect::<Vec<_>>();
+        let html = html::crates_report(
+            &format!("{} downloads", title),
+            &fetched,
+            &daily_downloads,
+            &days,
+        );
+        write_html(output, &html).await?;
+    } else if output.output == OutputFormat::Graph && days.is_empty() {
+        println!("No downloads found");
+    } else if output.output == OutputFormat::Graph {
+        let daily_downloads = crate_n_day_downloads.lock().await;
+        let daily_totals = days
+            .iter()
+            .map(|day| {
+                daily_downloads
+                    .values()
+                    .map(|downloads| downloads.get(day).unwrap_or(&0))
+                    .sum::<u64>() as f64
+            })
+            .collect::<Vec<_>>();
+        println!(
+            "{}",
+            plot_smoothed(
+                daily_totals,
+                args.smooth,
+                &[],
+                Config::default()
+                    .with_offset(10)
+                    .with_height(10)
+                    .with_caption(format!(
+                        "{} daily downloads across {} crates",
+                        title,
+                        crates.len()
+                    ))
+            )
+        )
+    } else {
+        print_crates_table(
+            &crates,
+            &crate_n_day_downloads.lock().await.clone(),
+            &days,
+            &previous_days,
+            &extra_columns(docs.as_ref(), repos.as_ref(), advisories.as_ref()),
+            &failed,
+            output,
+        )
+        .await?;
+    }
+    finish(skipped)
+}
+
+async fn handle_crate_command(
+    args: &CrateArgs,
+    output: &OutputArgs,
+    crate_name: String,
+) -> Result<()> {
+    if args.forecast.is_some() && (args.group_by.is_some() || args.by_version) {
+        return Err(Error::Usage(
+            "--forecast works with daily downloads, without --group-by and --by-version"
+                .to_string(),
+        ));
+    }
+    let client = new_client()?;
+
+    let api_crate = client
+        .get_crate(&crate_name)
+        .await
+        .map_err(crate_not_found(&crate_name))?;
+    let downloads = client
+        .crate_downloads(&crate_name)
+        .await
+        .context(format!("can not get downloads of crate {}", crate_name))?;
+
+    let owner_changes = match args.owners.track_owners {
+        true => Some(
+            track_owners(
+                &client,
+                std::slice::from_ref(&crate_name),
+                &args.owners.state_file,
+                output,
+            )
+            .await?,
+        ),
+        false => None,
+    };
+
+    let mut version_downloads = daily_downloads(&downloads)
+        .into_iter()
+        .map(|(date, count)| (date, count as f64))
+        .collect::<Vec<_>>();
+    if args.info
+        && matches!(
+            output.output,
+            OutputFormat::Table | OutputFormat::Graph | OutputFormat::Csv | OutputFormat::Md
+        )
+    {
+        print_crate_info(&api_crate, output);
+    }
+    let ranks = match args.rank {
+        true => Some(
+            source::Provider::new(config::source(), &client)?
+                .rankings(&api_crate)
+                .await?,
+        ),
+        false => None,
+    };
+    if let Some(ranks) = ranks
+        .as_ref()
+        .filter(|_| output.output != OutputFormat::Json)
+    {
+        let positions = ranks
+            .iter()
+            .map(|(category, rank)| {
+                let scope = category
+                    .as_ref()
+                    .map_or("overall".to_string(), |c| format!("in {}", c));
+                match rank {
+                    Some(rank) => format!("#{} {}", rank, scope),
+                    None => format!("below #{} {}", source::RANK_PAGES * 100, scope),
+                }
+            })
+            .collect::<Vec<_>>();
+        println!("Rank: {}", positions.join(", "));
+    }
+    let docs = match args.docs {
+        true => {
+            let version = default_version(&api_crate.crate_data);
+            let builds = docs_builds(vec![(crate_name.clone(), version.clone())]).await?;
+            Some((version, builds.into_iter().next().flatten()))
+        }
+        false => None,
+    };
+    if let Some((version, build)) = docs
+        .as_ref()
+        .filter(|_| output.output != OutputFormat::Json)
+    {
+        println!("Docs: {} {}", version, docs_summary(build));
+    }
+    let repo = match args.github {
+        true => github_repos(std::slice::from_ref(&api_crate.crate_data))
+            .await?
+            .pop()
+            .flatten(),
+        false => None,
+    };
+    if args.github && output.output != OutputFormat::Json {
+        println!("GitHub: {}", github_summary(&repo));
+    }
+    let advisories = match args.advisories {
+        true => crate_advisories(vec![crate_name.clone()])
+            .await?
+            .pop()
+            .unwrap_or_default(),
+        false => Vec::new(),
+    };
+    let open = open_advisories(&advisories, &[default_version(&api_crate.crate_data)]);
+    if args.advisories && output.output != OutputFormat::Json {
+        match open.is_empty() {
+            true => println!("Advisories: none affecting the latest version"),
+            false => println!(
+                "Advisories: {}",
+                open.iter()
+                    .map(|advisory| format!("{} ({})", advisory.id, advisory.kind))
+                    .join(", ")
+            ),
+        }
+    }
+    // the callout would break parseable output
+    if args.peak_day
+        && matches!(
+            output.output,
+            OutputFormat::Table | OutputFormat::Graph | OutputFormat::Md
+        )
+    {
+        print_peak_day(&version_downloads);
+    }
+    let history = version_downloads.clone();
+    apply_window(&mut version_downloads, &args.window);
+    let forecast = match (args.forecast, version_downloads.last()) {
+        (Some(days), Some((end, _))) => {
+            let start = history.first().map_or(*end, |vd| vd.0);
+            // the weekly season and the trend are fitted over calendar days
+            let known = fill_days(&history, start, *end)
+                .into_iter()
+                .map(|vd| vd.1)
+                .collect::<Vec<_>>();
+            forecast::extend(&known, days as usize)
+                .into_iter()
+                .zip(end.iter_days().skip(1))
+                .map(|(count, date)| (date, count.round()))
+                .collect::<Vec<_>>()
+        }
+        _ => Vec::new(),
+    };
+    let total = if args.window.is_bounded() {
+        version_downloads.iter().map(|vd| vd.1).sum::<f64>() as u64
+    } else {
+        api_crate.crate_data.downloads
+    };
+    if !args.by_version {
+        let dates = version_downloads.iter().map(|vd| vd.0).collect::<Vec<_>>();
+        let counts = version_downloads.iter().map(|vd| vd.1).collect::<Vec<_>>();
+        let title = format!("{} downloads", crate_name);
+        write_chart(output, &dates, &[(&crate_name, &counts)], &title).await?;
+    }
+    if args.by_version {
+        let mut dates = version_downloads.iter().map(|vd| vd.0).collect::<Vec<_>>();
+        if args.window.latest_only {
+            dates.drain(..dates.len().saturating_sub(1));
+        }
+        return print_version_breakdown(
+            &api_crate,
+            &downloads,
+            &dates,
+            args.window.is_bounded() || args.window.latest_only,
+            args.group_by == Some(Period::Major),
+            output,
+        )
+        .await;
+    }
+    if args.window.latest_only {
+        let latest = version_downloads
+            .iter()
+            .max_by_key(|vd| vd.0)
+            .map(|vd| (format!("{}", vd.0), vd.1));
+        match latest {
+            Some(latest) if args.porcelain => println!("{}", latest.1),
+            Some(latest) => print_latest_table(&crate_name, &latest, output).await?,
+            None => println!("No downloads found"),
+        }
+        return Ok(());
+    }
+    let mut download_rows = match args.group_by {
+        Some(period) => group_downloads(&version_downloads, period),
+        None => version_downloads
+            .iter()
+            .map(|t| (format!("{}", t.0), t.1))
+            .collect::<Vec<(String, f64)>>(),
+    };
+    let dc = download_rows.iter().map(|vd| vd.1).collect::<Vec<_>>();
+    let anomaly_points = match args.flag_anomalies {
+        Some(sigma) => {
+            // quiet days belong in the trailing baseline as zeros
+            let days = match (history.first(), version_downloads.last()) {
+                (Some(first), Some(last)) => fill_days(&history, first.0, last.0),
+                _ => Vec::new(),
+            };
+            let series = match args.group_by {
+                Some(period) => group_downloads(&days, period),
+                None => days.iter().map(|t| (t.0.to_string(), t.1)).collect(),
+            };
+            let counts = series.iter().map(|row| row.1).collect::<Vec<_>>();
+            find_anomalies(&counts, sigma)
+                .into_iter()
+                .filter_map(|(i, score)| {
+                    let position = download_rows.iter().position(|row| row.0 == series[i].0);
+                    position.map(|position| (position, score))
+                })
+                .collect()
+        }
+        None => Vec::new(),
+    };
+    let anomalies = anomaly_points
+        .iter()
+        .map(|(i, score)| (download_rows[*i].0.clone(), *score))
+        .collect::<HashMap<_, _>>();
+    if matches!(
+        output.output,
+        OutputFormat::Table | OutputFormat::Csv | OutputFormat::Md
+    ) {
+        select_rows(
+            &mut download_rows,
+            &args.rows,
+            |row| row.0.clone(),
+            |row, _| row.1 as u64,
+        );
+    }
+    let predicted = forecast.iter().map(|vd| vd.1).collect::<Vec<_>>();
+
+    if output.output == OutputFormat::Svg {
+        let labels = download_rows.iter().map(|row| &row.0).collect::<Vec<_>>();
+        let svg = chart::render(&labels, &[(&crate_name, &dc)], &crate_name);
+        if output.out_file.is_none() && std::io::stdout().is_terminal() {
+            return Err(Error::Usage(
+                "svg output needs --out-file or a redirected stdout".to_string(),
+            ));
+        }
+        write_output(output, &svg).await?;
+    } else if output.output == OutputFormat::Json {
+        let mut json = json!({
+            "crate": api_crate.crate_data,
+            "downloads": download_rows
+                .iter()
+                .map(|(date, count)| json!({"date": date, "downloads": *count as u64}))
+                .collect::<Vec<_>>(),
+            "window_total": dc.iter().sum::<f64>() as u64,
+            "total": api_crate.crate_data.downloads,
+        });
+        if let Some(ranks) = &ranks {
+            json["rank"] = json!({
+                "overall": ranks
+                    .iter()
+                    .find(|(category, _)| category.is_none())
+                    .and_then(|(_, rank)| *rank),
+                "categories": ranks
+                    .iter()
+                    .filter_map(|(category, rank)| Some((category.clone()?, json!(rank))))
+                    .collect::<serde_json::Map<_, _>>(),
+            });
+        }
+        if let Some((version, build)) = &docs {
+            json["docs"] = json!({"version": version, "build": build});
+        }
+        if args.github {
+            json["github"] = json!(repo);
+        }
+        if args.advisories {
+            json["advisories"] = json!(open);
+        }
+        if let Some(owner_changes) = owner_changes {
+            json["owner_changes"] = owner_changes;
+        }
+        if args.info {
+            json["license"] = json!(crate_license(&api_crate));
+            json["categories"] = json!(api_crate
+                .categories
+                .iter()
+                .map(|category| &category.category)
+                .collect::<Vec<_>>());
+            json["keywords"] = json!(api_crate
+                .keywords
+                .iter()
+                .map(|keyword| &keyword.keyword)
+                .collect::<Vec<_>>());
+        }
+        if args.flag_anomalies.is_some() {
+            json["anomalies"] = anomaly_points
+                .iter()
+                .map(|(i, score)| {
+                    json!({
+                        "date": download_rows[*i].0,
+                        "downloads": download_rows[*i].1 as u64,
+                        "sigma": score,
+                    })
+                })
+                .collect();
+        }
+        if !forecast.is_empty() {
+            json["forecast"] = forecast
+                .iter()
+                .map(|(date, count)| json!({"date": date, "downloads": *count as u64}))
+                .collect();
+        }
+        write_json(output, &json).await?;
+    } else if output.output == OutputFormat::Influx {
+        let daily = version_downloads
+            .iter()
+            .map(|(date, count)| (*date, *count as u64))
+            .collect::<Vec<_>>();
+        write_output(output, &metrics::influx_lines(&crate_name, None, &daily)).await?;
+    } else if output.output == OutputFormat::Html {
+        let html = html::downloads_report(
+            &format!("{} downloads", crate_name),
+            Some(&api_crate.crate_data),
+            &version_downloads,
+        );
+        write_html(output, &html).await?;
+    } else if output.output == OutputFormat::Graph && dc.is_empty() {
+        println!("No downloads found");
+    } else if output.output == OutputFormat::Graph {
+        let graph = plot_smoothed(
+            dc,
+            args.smooth,
+            &predicted,
+            Config::default()
+                .with_offset(10)
+                .with_height(10)
+                .with_caption(
+                    match (version_downloads.first(), version_downloads.last()) {
+                        (Some(first), Some(last)) if args.window.is_bounded() => format!(
+                            "{} downloads from {} to {} {}",
+                            &crate_name, first.0, last.0, total
+                        ),
+                        _ => format!("{} total downloads {}", &crate_name, total),
+                    },
+                ),
+        );
+        let mut releases = api_crate
+            .versions
+            .iter()
+            .filter(|version| !version.yanked)
+            .filter_map(|version| {
+                let date = version.created_at.date_naive();
+                let label = match args.group_by {
+                    Some(period) => bucket_label(date, period),
+                    None => date.to_string(),
+                };
+                download_rows
+                    .iter()
+                    .position(|row| row.0 == label)
+                    .map(|i| (i, version.num.clone()))
+            })
+            .collect::<Vec<_>>();
+        if !forecast.is_empty() {
+            releases.push((download_rows.len(), "forecast".to_string()));
+        }
+        releases.extend(anomaly_points.iter().map(|(i, _)| (*i, "!".to_string())));
+        releases.sort();
+        println!("{}", annotate_releases(&graph, &releases));
+    } else {
+        download_rows.extend(
+            forecast
+                .iter()
+                .map(|(date, count)| (format!("{} (forecast)", date), *count)),
+        );
+        print_downloads_table(
+            &download_rows,
+            total,
+            args.flag_anomalies.map(|_| &anomalies),
+            output,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+async fn handle_dependencies_command(
+    args: &CrateArgs,
+    output: &OutputArgs,
+    crate_name: String,
+) -> Result<()> {
+    let client = new_client()?;
+
+    let api_crate = client
+        .get_crate(&crate_name)
+        .await
+        .map_err(crate_not_found(&crate_name))?;
+    let version = api_crate.crate_data.max_version.clone();
+    let dependencies = client
+        .crate_dependencies(&crate_name, &version)
+        .await
+        .context(format!(
+            "can not get dependencies of crate {} {}",
+            crate_name, version
+        ))?;
+    let dependency_names = dependencies
+        .iter()
+        .map(|dependency| dependency.crate_id.clone())
+        .unique()
+        .collect::<Vec<_>>();
+    let pb = new_spinner();
+    pb.set_message("Fetching crates infos...");
+    let fetched = stream::iter(dependency_names)
+        .map(|crate_name| {
+            let client = client.clone();
+            async move {
+                client
+                    .get_crate(&crate_name)
+                    .await
+                    .map_err(crate_not_found(&crate_name))
+            }
+        })
+        .buffered(config::concurrency())
+        .collect::<Vec<_>>()
+        .await;
+    pb.finish_and_clear();
+    let fetched = fetched.into_iter().collect::<Result<Vec<_>>>()?;
+    let downloads = fetched
+        .iter()
+        .map(|c| (c.crate_data.name.clone(), c.crate_data.downloads))
+        .collect::<HashMap<_, _>>();
+    let download_count = |crate_name: &str| downloads.get(crate_name).copied().unwrap_or(0);
+    let unmaintained = match args.unmaintained {
+        Some(years) => {
+            let repos = maintenance_repos(
+                &fetched
+                    .iter()
+                    .map(|c| c.crate_data.clone())
+                    .collect::<Vec<_>>(),
+            )
+            .await;
+            fetched
+                .iter()
+                .zip(repos)
+                .map(|(c, repo)| {
+                    (
+                        c.crate_data.name.clone(),
+                        unmaintained_reasons(c, &repo, years),
+                    )
+                })
+                .collect::<HashMap<_, _>>()
+        }
+        None => HashMap::new(),
+    };
+
+    let mut dependencies = dependencies;
+    select_rows(
+        &mut dependencies,
+        &args.rows,
+        |dependency| dependency.crate_id.clone(),
+        |dependency, _| download_count(&dependency.crate_id),
+    );
+
+    if output.output == OutputFormat::Json {
+        let json = json!({
+            "crate": crate_name,
+            "version": version,
+            "dependencies": dependencies
+                .iter()
+                .map(|dependency| {
+                    let mut entry = json!({
+                        "name": dependency.crate_id,
+                        "req": dependency.req,
+                        "kind": dependency.kind,
+                        "optional": dependency.optional,
+                        "target": dependency.target,
+                        "downloads": download_count(&dependency.crate_id),
+                    });
+                    if let Some(reasons) = unmaintained.get(&dependency.crate_id) {
+                        entry["unmaintained"] = json!(reasons);
+                    }
+                    entry
+                })
+                .collect::<Vec<_>>(),
+        });
+        return write_json(output, &json).await;
+    }
+    if let (Some(years), OutputFormat::Table) = (args.unmaintained, output.output) {
+        print_unmaintained_count(
+            unmaintained
+                .values()
+                .filter(|reasons| !reasons.is_empty())
+                .count(),
+            unmaintained.len(),
+            years,
+        );
+    }
+
+    let rows = dependencies
+        .iter()
+        .map(|dependency| {
+            let mut row = vec![
+                dependency.crate_id.clone(),
+                dependency.req.clone(),
+                dependency.kind.clone(),
+                dependency.optional.to_string(),
+                download_count(&dependency.crate_id).to_string(),
+            ];
+            if let Some(reasons) = unmaintained.get(&dependency.crate_id) {
+                row.push(unmaintained_summary(reasons));
+            }
+            row
+        })
+        .collect::<Vec<_>>();
+    let mut headers = vec![
+        format!("{} {} Dependency", crate_name, version),
+        "Req".to_string(),
+        "Kind".to_string(),
+        "Optional".to_string(),
+        "Download Count".to_string(),
+    ];
+    if args.unmaintained.is_some() {
+        headers.push("Unmaintained".to_string());
+    }
+    print_rows(output, headers, rows).await
+}
+
+async fn handle_crate_comparison(
+    args: &CrateArgs,
+    output: &OutputArgs,
+    crate_names: &[String],
+) -> Result<()> {
+    if args.by_version {
+        return Err(Error::Usage(
+            "--by-version works with a single crate".to_string(),
+        ));
+    }
+    if args.forecast.is_some() {
+        return Err(Error::Usage(
+            "--forecast works with a single crate".to_string(),
+        ));
+    }
+    let client = new_client()?;
+
+    let pb = new_spinner();
+    pb.set_message("Fetching crates infos...");
+    let fetched = stream::iter(crate_names.to_vec())
+        .map(|crate_name| {
+            let client = client.clone();
+            let inner_pb = pb.clone();
+            async move {
+                inner_pb.set_message(format!("Fetching {} info...", crate_name));
+                let api_crate = client
+                    .get_crate(&crate_name)
+                    .await
+                    .map_err(crate_not_found(&crate_name))?;
+                let downloads = client
+                    .crate_downloads(&crate_name)
+                    .await
+                    .context(format!("can not get downloads of crate {}", crate_name))?;
+                inner_pb.tick();
+                Ok::<_, Error>((crate_name, api_crate, downloads))
+            }
+        })
+        .buffered(config::concurrency())
+        .collect::<Vec<_>>()
+        .await;
+    pb.finish_with_message("Finished gathering crate info!");
+
+    let mut series = Vec::new();
+    let mut influx = String::new();
+    let mut chart_series = Vec::new();
+    for result in fetched {
+        let (crate_name, api_crate, downloads) = result?;
+        let mut daily = daily_downloads(&downloads)
+            .into_iter()
+            .map(|(date, count)| (date, count as f64))
+            .collect::<Vec<_>>();
+        if args.peak_day
+            && matches!(
+                output.output,
+                OutputFormat::Table | OutputFormat::Graph | OutputFormat::Md
+            )
+        {
+            print!("{}: ", crate_name);
+            print_peak_day(&daily);
+        }
+        apply_window(&mut daily, &args.window);
+        if output.output == OutputFormat::Influx {
+            let points = daily
+                .iter()
+                .map(|(date, count)| (*date, *count as u64))
+                .collect::<Vec<_>>();
+            influx.push_str(&metrics::influx_lines(&crate_name, None, &points));
+        }
+        if output.chart_file.is_some() {
+            chart_series.push((
+                crate_name.clone(),
+                daily.iter().copied().collect::<BTreeMap<_, _>>(),
+            ));
+        }
+        let total = if args.window.is_bounded() {
+            daily.iter().map(|vd| vd.1).sum::<f64>() as u64
+        } else {
+            api_crate.crate_data.downloads
+        };
+        let rows = match args.group_by {
+            Some(period) => group_downloads(&daily, period),
+            None => daily
+                .iter()
+                .map(|(date, count)| (date.to_string(), *count))
+                .collect(),
+        };
+        series.push((
+            crate_name,
+            rows.into_iter().collect::<BTreeMap<_, _>>(),
+            total,
+        ));
+    }
+
+    let owner_changes = match args.owners.track_owners {
+        true => Some(track_owners(&client, crate_names, &args.owners.state_file, output).await?),
+        false => None,
+    };
+    if output.chart_file.is_some() {
+        let dates = chart_series
+            .iter()
+            .flat_map(|(_, daily)| daily.keys().copied())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let counts = chart_series
+            .iter()
+            .map(|(crate_name, daily)| {
+                let counts = dates
+                    .iter()
+                    .map(|date| daily.get(date).copied().unwrap_or(0.0))
+                    .collect::<Vec<_>>();
+                (crate_name.as_str(), counts)
+            })
+            .collect::<Vec<_>>();
+        let series = counts
+            .iter()
+            .map(|(crate_name, counts)| (*crate_name, counts.as_slice()))
+            .collect::<Vec<_>>();
+        let title = format!("downloads of {}", crate_names.join(", "));
+        write_chart(output, &dates, &series, &title).await?;
+    }
+
+    let mut labels = series
+        .iter()
+        .flat_map(|(_, rows, _)| rows.keys().cloned())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    if args.window.latest_only {
+        labels.drain(..labels.len().saturating_sub(1));
+    }
+    let value =
+        |rows: &BTreeMap<String, f64>, label: &String| rows.get(label).copied().unwrap_or(0.0);
+
+    if args.porcelain {
+        for label in &labels {
+            for (crate_name, rows, _) in &series {
+                println!("{}\t{}\t{}", crate_name, label, value(rows, label));
+            }
+        }
+        return Ok(());
+    }
+
+    match output.output {
+        OutputFormat::Json => {
+            let mut json = json!({
+                "crates": series
+                    .iter()
+                    .map(|(crate_name, rows, total)| {
+                        json!({
+                            "crate": crate_name,
+                            "downloads": labels
+                                .iter()
+                                .map(|label| json!({"date": label, "downloads": value(rows, label) as u64}))
+                                .collect::<Vec<_>>(),
+                            "total": total,
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            });
+            if let Some(owner_changes) = owner_changes {
+                json["owner_changes"] = owner_changes;
+            }
+            write_json(output, &json).await
+        }
+        OutputFormat::Graph if labels.is_empty() => {
+            println!("No downloads found");
+            Ok(())
+        }
+        OutputFormat::Graph => {
+            let all_series = series
+                .iter()
+                .map(|(_, rows, _)| {
+                    let counts = labels
+                        .iter()
+                        .map(|label| value(rows, label))
+                        .collect::<Vec<_>>();
+                    match args.smooth {
+                        Some(window) => moving_average(&counts, window),
+                        None => counts,
+                    }
+                })
+                .collect::<Vec<Vec<f64>>>();
+            println!(
+                "{}",
+                plot_many(
+                    all_series,
+                    Config::default()
+                        .with_offset(10)
+                        .with_height(10)
+                        .with_caption(format!("downloads of {}", crate_names.join(", ")))
+                )
+            );
+            Ok(())
+        }
+        OutputFormat::Svg | OutputFormat::Html => Err(Error::Usage(
+            "svg and html output work with a single crate".to_string(),
+        )),
+        OutputFormat::Influx => write_output(output, &influx).await,
+        _ => {
+            let mut headers = vec!["Date".to_string()];
+            headers.extend(series.iter().map(|(crate_name, _, _)| crate_name.clone()));
+            select_rows(
+                &mut labels,
+                &args.rows,
+                |label| label.clone(),
+                |label, _| {
+                    series
+                        .iter()
+                        .map(|(_, rows, _)| value(rows, label) as u64)
+                        .sum()
+                },
+            );
+            let mut rows = labels
+                .iter()
+                .map(|label| {
+                    let mut row = vec![label.clone()];
+                    row.extend(
+                        series
+                            .iter()
+                            .map(|(_, rows, _)| value(rows, label).to_string()),
+                    );
+                    row
+                })
+                .collect::<Vec<_>>();
+            let mut total_row = vec!["Total".to_string()];
+            total_row.extend(series.iter().map(|(_, _, total)| total.to_string()));
+            rows.push(total_row);
+            print_rows(output, headers, rows).await
+        }
+    }
+}
+
+async fn handle_snapshot_command(
+    args: &SnapshotArgs,
+    output: &OutputArgs,
+    mut crate_names: Vec<String>,
+) -> Result<()> {
+    let client = new_client()?;
+    if let Some(user_name) = &args.user {
+        let user = client
+            .user(user_name)
+            .await
+            .map_err(user_not_found(user_name))?;
+        crate_names.extend(
+            get_user_crates(&client, user.id, user_name)
+                .await?
+                .into_iter()
+                .map(|c| c.name),
+        );
+    }
+    if crate_names.is_empty() {
+        return Err(Error::Usage(
+            "snapshot needs crate names or --user".to_string(),
+        ));
+    }
+    let mut store = Store::open(args.store.db.as_deref())?;
+
+    let pb = new_spinner();
+    pb.set_message("Fetching crates infos...");
+    let fetched = stream::iter(crate_names)
+        .map(|crate_name| {
+            let client = client.clone();
+            let inner_pb = pb.clone();
+            async move {
+                inner_pb.set_message(format!("Fetching {} info...", crate_name));
+                let api_crate = client
+                    .get_crate(&crate_name)
+                    .await
+                    .map_err(crate_not_found(&crate_name))?;
+                let dependents = client
+                    .crate_reverse_dependency_count(&crate_name)
+                    .await
+                    .context(format!("can not get dependents of crate {}", crate_name))?;
+                let downloads = client
+                    .crate_downloads(&crate_name)
+                    .await
+                    .context(format!("can not get downloads of crate {}", crate_name))?;
+                inner_pb.tick();
+                Ok::<_, Error>((crate_name, api_crate, dependents, downloads))
+            }
+        })
+        .buffered(config::concurrency())
+        .collect::<Vec<_>>()
+        .await;
+    pb.finish_with_message("Finished gathering crate info!");
+
+    let taken_at = Utc::now();
+    let mut rows = Vec::new();
+    let mut snapshots = Vec::new();
+    for result in fetched {
+        let (crate_name, api_crate, dependents, downloads) = result?;
+        let snapshot = Snapshot {
+            taken_at,
+            downloads: api_crate.crate_data.downloads,
+            recent_downloads: api_crate.crate_data.recent_downloads,
+            dependents,
+            versions: api_crate.versions.len() as u64,
+        };
+        let versions = api_crate
+            .versions
+            .iter()
+            .map(|v| (v.num.clone(), v.downloads))
+            .collect::<Vec<_>>();
+        let daily = daily_downloads(&downloads);
+        store.insert_snapshot(&crate_name, &snapshot, &versions, &daily)?;
+        snapshots.push(json!({
+            "crate": crate_name,
+            "downloads": snapshot.downloads,
+            "versions": snapshot.versions,
+            "dependents": snapshot.dependents,
+            "days": daily.len(),
+            "taken_at": taken_at,
+        }));
+        rows.push(vec![
+            crate_name,
+            snapshot.downloads.to_string(),
+            snapshot.versions.to_string(),
+            snapshot.dependents.to_string(),
+            daily.len().to_string(),
+        ]);
+    }
+    if output.output == OutputFormat::Json {
+        return write_json(output, &json!(snapshots)).await;
+    }
+    print_rows(
+        output,
+        vec![
+            "Crate Name".to_string(),
+            "Download Count".to_string(),
+            "Versions".to_string(),
+            "Dependents".to_string(),
+            "Days".to_string(),
+        ],
+        rows,
+    )
+    .await
+}
+
+async fn handle_diff_command(
+    args: &DiffArgs,
+    output: &OutputArgs,
+    crate_name: String,
+) -> Result<()> {
+    let store = Store::open(args.store.db.as_deref())?;
+    let no_snapshot = || {
+        Error::Usage(format!(
+            "no snapshots of {} found, run crabst snapshot first",
+            crate_name
+        ))
+    };
+    let from = store
+        .snapshot_near(&crate_name, args.since)?
+        .ok_or_else(no_snapshot)?;
+    let to = store
+        .snapshot_near(&crate_name, args.until.unwrap_or_else(Utc::now))?
+        .ok_or_else(no_snapshot)?;
+
+    let metrics = [
+        ("Downloads", from.downloads, to.downloads),
+        ("Dependents", from.dependents, to.dependents),
+        ("Versions", from.versions, to.versions),
+    ];
+    let change = |old: u64, new: u64| new as i64 - old as i64;
+    let change_percent =
+        |old: u64, new: u64| (old > 0).then(|| (new as f64 - old as f64) / old as f64 * 100.0);
+
+    if output.output == OutputFormat::Json {
+        let json = json!({
+            "crate": crate_name,
+            "from": from.taken_at,
+            "to": to.taken_at,
+            "changes": metrics
+                .iter()
+                .map(|(metric, old, new)| {
+                    json!({
+                        "metric": metric.to_lowercase(),
+                        "from": old,
+                        "to": new,
+                        "change": change(*old, *new),
+                        "change_percent": change_percent(*old, *new),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        });
+        return write_json(output, &json).await;
+    }
+
+    let rows = metrics
+        .iter()
+        .map(|(metric, old, new)| {
+            vec![
+                metric.to_string(),
+                old.to_string(),
+                new.to_string(),
+                format!("{:+}", change(*old, *new)),
+                change_percent(*old, *new)
+                    .map_or("n/a".to_string(), |percent| format!("{:+.2}%", percent)),
+            ]
+        })
+        .collect::<Vec<_>>();
+    print_rows(
+        output,
+        vec![
+            crate_name.clone(),
+            from.taken_at.format("%Y-%m-%d %H:%M").to_string(),
+            to.taken_at.format("%Y-%m-%d %H:%M").to_string(),
+            "Change".to_string(),
+            "Change %".to_string(),
+        ],
+        rows,
+    )
+    .await
+}
+
+async fn handle_history_command(
+    args: &HistoryArgs,
+    output: &OutputArgs,
+    crate_name: String,
+) -> Result<()> {
+    if args.group_by == Some(Period::Major) {
+        return Err(Error::Usage(
+            "history can not be grouped by major version".to_string(),
+        ));
+    }
+    let mut store = Store::open(args.store.db.as_deref())?;
+    let client = new_client()?;
+    match client.crate_downloads(&crate_name).await {
+        Ok(downloads) => store.upsert_daily(&crate_name, &daily_downloads(&downloads))?,
+        Err(err) => tracing::warn!(
+            "can not fetch recent downloads of {}, showing local data only: {}",
+            crate_name,
+            err
+        ),
+    }
+    let stored = store.daily_downloads(&crate_name)?;
+    let (Some(first), Some(last)) = (stored.keys().next(), stored.keys().next_back()) else {
+        return Err(Error::Usage(format!(
+            "no download history of {} found, run crabst snapshot first",
+            crate_name
+        )));
+    };
+
+    let mut history = first
+        .iter_days()
+        .take_while(|date| date <= last)
+        .map(|date| (date, stored.get(&date).copied().unwrap_or(0) as f64))
+        .collect::<Vec<_>>();
+    apply_window(&mut history, &args.window);
+    {
+        let dates = history.iter().map(|vd| vd.0).collect::<Vec<_>>();
+        let counts = history.iter().map(|vd| vd.1).collect::<Vec<_>>();
+        let title = format!("{} download history", crate_name);
+        write_chart(output, &dates, &[(&crate_name, &counts)], &title).await?;
+    }
+    let rows = match args.group_by {
+        Some(period) => group_downloads(&history, period),
+        None => history
+            .iter()
+            .map(|(date, count)| (date.to_string(), *count))
+            .collect(),
+    };
+    let counts = rows.iter().map(|row| row.1).collect::<Vec<_>>();
+    let total = counts.iter().sum::<f64>() as u64;
+
+    match output.output {
+        OutputFormat::Json => {
+            let json = json!({
+                "crate": crate_name,
+                "downloads": rows
+                    .iter()
+                    .map(|(date, count)| json!({"date": date, "downloads": *count as u64}))
+                    .collect::<Vec<_>>(),
+                "total": total,
+            });
+            write_json(output, &json).await
+        }
+        OutputFormat::Graph if counts.is_empty() => {
+            println!("No downloads found");
+            Ok(())
+        }
+        OutputFormat::Graph => {
+            let mut config = Config::default().with_offset(10).with_height(10);
+            if counts.len() > args.width as usize {
+                config = config.with_width(args.width);
+            }
+            let caption = match (history.first(), history.last()) {
+                (Some(first), Some(last)) => format!(
+                    "{} downloads from {} to {} {}",
+                    crate_name, first.0, last.0, total
+                ),
+                _ => format!("{} downloads {}", crate_name, total),
+            };
+            println!(
+                "{}",
+                plot_smoothed(counts, args.smooth, &[], config.with_caption(caption))
+            );
+            Ok(())
+        }
+        OutputFormat::Svg => {
+            let dates = history.iter().map(|vd| vd.0).collect::<Vec<_>>();
+            let counts = history.iter().map(|vd| vd.1).collect::<Vec<_>>();
+            if output.out_file.is_none() && std::io::stdout().is_terminal() {
+                return Err(Error::Usage(
+                    "svg output needs --out-file or a redirected stdout".to_string(),
+                ));
+            }
+            write_output(
+                output,
+                &chart::render(&dates, &[(&crate_name, &counts)], &crate_name),
+            )
+            .await
+        }
+        OutputFormat::Influx => {
+            let daily = history
+                .iter()
+                .map(|(date, count)| (*date, *count as u64))
+                .collect::<Vec<_>>();
+            write_output(output, &metrics::influx_lines(&crate_name, None, &daily)).await
+        }
+        OutputFormat::Html => {
+            let title = format!("{} download history", crate_name);
+            write_html(output, &html::downloads_report(&title, None, &history)).await
+        }
+        _ => print_downloads_table(&rows, total, None, output).await,
+    }
+}
+
+async fn handle_import_dump_command(args: &ImportDumpArgs) -> Result<()> {
+    let mut store = Store::open(args.store.db.as_deref())?;
+    let work_dir = std::env::temp_dir().join(format!("crabst-dump-{}", std::process::id()));
+
+    let pb = new_spinner();
+    pb.enable_steady_tick(Duration::from_millis(500));
+    let result = async {
+        let mut source = PathBuf::from(&args.source);
+        if args.source.starts_with("http://") || args.source.starts_with("https://") {
+            tokio::fs::create_dir_all(&work_dir)
+                .await
+                .context(format!("can not create {}", work_dir.display()))?;
+            source = work_dir.join("db-dump.tar.gz");
+            pb.set_message("Downloading dump...");
+            dump::download(&args.source, &source, &pb).await?;
+        }
+        let tables = if source.is_dir() {
+            dump::find_tables(&source)?
+        } else {
+            pb.set_message("Unpacking dump...");
+            std::fs::create_dir_all(&work_dir)
+                .context(format!("can not create {}", work_dir.display()))?;
+            dump::unpack(&source, &work_dir)?;
+            work_dir.clone()
+        };
+        pb.set_message("Importing version downloads...");
+        dump::import(&tables, &mut store, &args.crates)
+    }
+    .await;
+    let _ = std::fs::remove_dir_all(&work_dir);
+    let imported = result?;
+    pb.finish_with_message(format!("Imported {} daily download counts", imported));
+    Ok(())
+}
+
+async fn handle_tui_command(args: &TuiArgs) -> Result<()> {
+    if !std::io::stdout().is_terminal() {
+        return Err(Error::Usage(
+            "tui needs an interactive terminal".to_string(),
+        ));
+    }
+    let client = new_client()?;
+    tui::run(&client, &args.name, Duration::from_secs(args.refresh * 60)).await
+}
+
+async fn write_output(output: &OutputArgs, content: &str) -> Result<()> {
+    match &output.out_file {
+        Some(out_file) => tokio::fs::write(out_file, content)
+            .await
+            .context(format!("can not write {}", out_file.display())),
+        None => {
+            let mut stdout = io::stdout();
+            let _ = stdout.write_all(content.as_bytes()).await;
+            let _ = stdout.flush().await;
+            Ok(())
+        }
+    }
+}
+
+// a line per printed result with the time and arguments of the run, for cron jobs
+async fn append_record(output: &OutputArgs, result: serde_json::Value) -> Result<()> {
+    let Some(append) = &output.append else {
+        return Ok(());
+    };
+    let record = json!({
+        "timestamp": Utc::now(),
+        "command": std::env::args().skip(1).collect::<Vec<_>>(),
+        "result": result,
+    });
+    let context = || format!("can not append to {}", append.display());
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(append)
+        .await
+        .context(context())?;
+    file.write_all(format!("{}\n", record).as_bytes())
+        .await
+        .context(context())
+}
+
+async fn open_output(output: &OutputArgs) -> Result<Box<dyn AsyncWrite + Unpin + Send>> {
+    Ok(match &output.out_file {
+        Some(out_file) => Box::new(
+            tokio::fs::File::create(out_file)
+                .await
+                .context(format!("can not write {}", out_file.display()))?,
+        ),
+        None => Box::new(io::stdout()),
+    })
+}
+
+async fn write_chart(
+    output: &OutputArgs,
+    dates: &[NaiveDate],
+    series: &[(&str, &[f64])],
+    title: &str,
+) -> Result<()> {
+    let Some(chart_file) = &output.chart_file else {
+        return Ok(());
+    };
+    tokio::fs::write(chart_file, chart::render(dates, series, title))
+        .await
+        .context(format!("can not write {}", chart_file.display()))
+}
+
+async fn write_html(output: &OutputArgs, html: &str) -> Result<()> {
+    if output.out_file.is_none() && std::io::stdout().is_terminal() {
+        return Err(Error::Usage(
+            "html output needs --out-file or a redirected stdout".to_string(),
+        ));
+    }
+    write_output(output, html).await
+}
+
+async fn write_json(output: &OutputArgs, json: &serde_json::Value) -> Result<()> {
+    append_record(output, json.clone()).await?;
+    let mut content =
+        serde_json::to_string_pretty(json).context("can not serialize json output")?;
+    content.push('\n');
+    write_output(output, &content).await
+}
+
+fn plot_smoothed(
+    counts: Vec<f64>,
+    smooth: Option<usize>,
+    forecast: &[f64],
+    config: Config,
+) -> String {
+    let mut series = Vec::new();
+    if let Some(window) = smooth {
+        series.push(moving_average(&counts, window));
+    }
+    if let Some(last) = counts.last().filter(|_| !forecast.is_empty()) {
+        let mut predicted = vec![f64::NAN; counts.len() - 1];
+        predicted.push(*last);
+        predicted.extend(forecast);
+        series.push(predicted);
+    }
+    series.insert(0, counts);
+    plot_many(series, config)
+}
+
+fn annotate_releases(graph: &str, releases: &[(usize, String)]) -> String {
+    const LABEL_LINES: usize = 3;
+    let mut lines = graph.lines().map(str::to_string).collect::<Vec<_>>();
+    let axis = lines
+        .first()
+        .and_then(|line| line.chars().position(|c| c == '┤' || c == '┼'));
+    let (Some(axis), false) = (axis, releases.is_empty()) else {
+        return graph.to_string();
+    };
+    let caption = lines.pop().unwrap_or_default();
+
+    let mut markers = String::new();
+    let mut labels: Vec<String> = vec![String::new(); LABEL_LINES];
+    for (index, version) in releases {
+        let column = axis + 1 + index;
+        if markers.chars().count() <= column {
+            markers.push_str(&" ".repeat(column - markers.chars().count()));
+            markers.push('^');
+        }
+        let free = labels
+            .iter_mut()
+            .find(|line| line.is_empty() || line.chars().count() < column);
+        if let Some(line) = free {
+            line.push_str(&" ".repeat(column - line.chars().count()));
+            line.push_str(version);
+        }
+    }
+    lines.push(markers);
+    lines.extend(labels.into_iter().filter(|line| !line.is_empty()));
+    lines.push(caption);
+    lines.join("\n")
+}
+
+fn crate_license(api_crate: &CrateResponse) -> Option<String> {
+    let latest = &api_crate.crate_data.max_version;
+    api_crate
+        .versions
+        .iter()
+        .find(|version| &version.num == latest)
+        .or(api_crate.versions.first())
+        .and_then(|version| version.license.clone())
+}
+
+fn print_crate_info(api_crate: &CrateResponse, output: &OutputArgs) {
+    let data = &api_crate.crate_data;
+    let optional = |value: &Option<String>| value.clone().unwrap_or("-".to_string());
+    let joined = |values: Vec<&String>| match values.is_empty() {
+        true => "-".to_string(),
+        false => values
+            .into_iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", "),
+    };
+    let rows = [
+        ("Crate", data.name.clone()),
+        (
+            "Description",
+            optional(&data.description).trim().to_string(),
+        ),
+        ("License", optional(&crate_license(api_crate))),
+        ("Repository", optional(&data.repository)),
+        ("Documentation", optional(&data.documentation)),
+        ("Homepage", optional(&data.homepage)),
+        (
+            "Categories",
+            joined(api_crate.categories.iter().map(|c| &c.category).collect()),
+        ),
+        (
+            "Keywords",
+            joined(api_crate.keywords.iter().map(|k| &k.keyword).collect()),
+        ),
+        ("Created", data.created_at.format("%Y-%m-%d").to_string()),
+        ("Updated", data.updated_at.format("%Y-%m-%d").to_string()),
+        ("Latest Version", data.max_version.clone()),
+        (
+            "Recent Downloads",
+            data.recent_downloads
+                .map_or("-".to_string(), |count| count.to_string()),
+        ),
+    ]
+    .into_iter()
+    .map(|(field, value)| vec![field.to_string(), value])
+    .collect::<Vec<_>>();
+    let headers = ["Field", "Value"]
+        .iter()
+        .map(|header| format_header(header, output.header_case))
+        .collect::<Vec<_>>();
+    let content = match output.output {
+        OutputFormat::Csv => render_csv(&headers, &rows),
+        OutputFormat::Md => render_markdown(&headers, &rows, Align::Left),
+        _ => render_table(&headers, &rows, Align::Left),
+    };
+    println!("{}", content.trim_end());
+}
+
+fn print_peak_day(downloads: &[(NaiveDate, f64)]) {
+    let peak = downloads
+        .iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)));
+    if let Some((date, count)) = peak {
+        let callout = format!("★ Peak day: {} with {} downloads", date, count);
+        if std::io::stdout().is_terminal() {
+            println!("\x1b[1;33m{}\x1b[0m", callout);
+        } else {
+            println!("{}", callout);
+        }
+    }
+}
+
+async fn print_rows(
+    output: &OutputArgs,
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+) -> Result<()> {
+    append_record(output, json!(row_objects(&headers, &rows))).await?;
+    let headers = headers
+        .iter()
+        .map(|header| format_header(header, output.header_case))
+        .collect::<Vec<_>>();
+    let content = match output.output {
+        OutputFormat::Csv => render_csv(&headers, &rows),
+        OutputFormat::Md => render_markdown(&headers, &rows, output.align),
+        OutputFormat::Ndjson => render_ndjson(&headers, &rows),
+        OutputFormat::Json => {
+            serde_json::to_string_pretty(&row_objects(&headers, &rows))
+                .context("can not serialize json output")?
+                + "\n"
+        }
+        _ => render_table(&headers, &rows, output.align),
+    };
+    write_output(output, &content).await
+}
+
+async fn print_downloads_table(
+    downloads: &[(String, f64)],
+    total: u64,
+    anomalies: Option<&HashMap<String, f64>>,
+    output: &OutputArgs,
+) -> Result<()> {
+    let mut headers = vec!["Date".to_string(), "Download Count".to_string()];
+    let mut rows = downloads
+        .iter()
+        .map(|c| vec![c.0.clone(), c.1.to_string()])
+        .collect::<Vec<_>>();
+    rows.push(vec!["Total".to_string(), total.to_string()]);
+    let Some(anomalies) = anomalies else {
+        return print_rows(output, headers, rows).await;
+    };
+
+    headers.push("Anomaly".to_string());
+    for row in &mut rows {
+        let score = anomalies.get(&row[0]);
+        row.push(score.map_or(String::new(), |score| format!("{:+.1}σ", score)));
+    }
+    if output.output != OutputFormat::Table {
+        return print_rows(output, headers, rows).await;
+    }
+    append_record(output, json!(row_objects(&headers, &rows))).await?;
+    let headers = headers
+        .iter()
+        .map(|header| format_header(header, output.header_case))
+        .collect::<Vec<_>>();
+    let content = render_highlighted_table(&headers, &rows, output.align, |row| {
+        anomalies.contains_key(&row[0])
+    });
+    write_output(output, &content).await
+}
+
+async fn print_latest_table(
+    crate_name: &str,
+    latest: &(String, f64),
+    output: &OutputArgs,
+) -> Result<()> {
+    if output.output == OutputFormat::Json {
+        let json = json!({
+            "crate": crate_name,
+            "date": latest.0,
+            "downloads": latest.1 as u64,
+        });
+        return write_json(output, &json).await;
+    }
+    print_rows(
+        output,
+        vec![latest.0.clone()],
+        vec![vec![latest.1.to_string()]],
+    )
+    .await
+}
+
+async fn print_crates_table(
+    crates: &[Crate],
+    daily_downloads: &HashMap<String, HashMap<NaiveDate, u64>>,
+    days: &[NaiveDate],
+    previous_days: &[NaiveDate],
+    extra_columns: &[(&str, HashMap<String, String>)],
+    failed: &BTreeMap<String, String>,
+    output: &OutputArgs,
+) -> Result<()> {
+    let mut header_vec = vec!["Crate Name".to_owned(), "Download Count".to_owned()];
+    for (header, _) in extra_columns {
+        header_vec.push(header.to_string());
+    }
+    for date in days {
+        header_vec.push(date.format("%Y-%m-%d").to_string())
+    }
+    if !previous_days.is_empty() {
+        header_vec.push("Trend".to_owned());
+    }
+    let window_sum = |downloads: &HashMap<NaiveDate, u64>, window: &[NaiveDate]| {
+        window
+            .iter()
+            .map(|day| downloads.get(day).unwrap_or(&0))
+            .sum::<u64>()
+    };
+
+    let mut default_zero_hash = HashMap::new();
+    for day in days {
+        default_zero_hash.insert(*day, 0);
+    }
+
+    let mut rows = crates
+        .iter()
+        .map(|c| {
+            let mut row = vec![c.name.clone(), c.downloads.to_string()];
+            for (_, cells) in extra_columns {
+                row.push(cells.get(&c.name).cloned().unwrap_or("-".to_string()));
+            }
+            if failed.contains_key(&c.name) {
+                let columns = days.len() + usize::from(!previous_days.is_empty());
+                row.extend(std::iter::repeat_n("ERR".to_string(), columns));
+                return row;
+            }
+            for day in days {
+                row.push(
+                    daily_downloads
+                        .get(&c.name)
+                        .unwrap_or(&default_zero_hash)
+                        .get(day)
+                        .unwrap_or(&0)
+                        .to_string(),
+                )
+            }
+            if !previous_days.is_empty() {
+                let downloads = daily_downloads.get(&c.name).unwrap_or(&default_zero_hash);
+                row.push(trend(
+                    window_sum(downloads, days),
+                    window_sum(downloads, previous_days),
+                ));
+            }
+            row
+        })
+        .collect::<Vec<_>>();
+
+    let mut total_row = vec![
+        "Total".to_string(),
+        crates
+            .iter()
+            .fold(0, |init, c| init + c.downloads)
+            .to_string(),
+    ];
+    total_row.extend(extra_columns.iter().map(|_| String::new()));
+    for day in days {
+        total_row.push(
+            daily_downloads
+                .values()
+                .map(|download_maps| download_maps.get(day).unwrap_or(&0))
+                .sum::<u64>()
+                .to_string(),
+        );
+    }
+    if !previous_days.is_empty() {
+        let total = |window| {
+            daily_downloads
+                .values()
+                .map(|downloads| window_sum(downloads, window))
+                .sum()
+        };
+        total_row.push(trend(total(days), total(previous_days)));
+    }
+    rows.push(total_row);
+
+    print_rows(output, header_vec, rows).await
+}
+
+// footnote to the ERR cells of tables, totals leave these crates out
+fn print_fetch_failures(failed: &BTreeMap<String, String>, footnote: bool) {
+    for (crate_name, err) in failed {
+        match footnote {
+            true => println!("ERR: can not fetch downloads of {}: {}", crate_name, err),
+            false => tracing::warn!("can not fetch downloads of {}: {}", crate_name, err),
+        }
+    }
+}
+
+fn select_rows<T>(
+    items: &mut Vec<T>,
+    rows: &RowArgs,
+    name: impl Fn(&T) -> String,
+    count: impl Fn(&T, SortKey) -> u64,
+) {
+    if let Some(min_downloads) = rows.min_downloads {
+        items.retain(|item| count(item, SortKey::Downloads) >= min_downloads);
+    }
+    match rows.sort {
+        Some(SortKey::Name) => items.sort_by_cached_key(|item| name(item)),
+        Some(key) => items.sort_by_cached_key(|item| count(item, key)),
+        None => {}
+    }
+    if rows.desc {
+        items.reverse();
+    }
+    if let Some(top) = rows.top {
+        items.truncate(top);
+    }
+}
+
+fn apply_window(downloads: &mut Vec<(NaiveDate, f64)>, window: &WindowArgs) {
+    let today = Utc::now().date_naive();
+    let mut end = window.to.unwrap_or(today);
+    if window.exclude_today {
+        end = end.min(today - Days::new(1));
+    }
+    if !window.is_bounded() {
+        end = downloads.last().map_or(end, |vd| end.min(vd.0));
+    }
+    let last_start = window
+        .last
+        .map(|last_n_day| end - Days::new(u64::from(last_n_day) - 1));
+    let start = [window.from, last_start].into_iter().flatten().max();
+    *downloads = match start.or_else(|| downloads.first().map(|vd| vd.0)) {
+        Some(start) if downloads.iter().any(|vd| (start..=end).contains(&vd.0)) => {
+            fill_days(downloads, start, end)
+        }
+        _ => Vec::new(),
+    };
+}
+
+// crates.io has no entry for days without downloads
+fn fill_days(
+    downloads: &[(NaiveDate, f64)],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Vec<(NaiveDate, f64)> {
+    let counts = downloads.iter().copied().collect::<HashMap<_, _>>();
+    start
+        .iter_days()
+        .take_while(|day| *day <= end)
+        .map(|day| (day, counts.get(&day).copied().unwrap_or(0.0)))
+        .collect()
+}
+
+fn window_days(window: &WindowArgs, default_last: u32) -> Result<Vec<NaiveDate>> {
+    let today = Utc::now().date_naive();
+    let end = match window.to {
+        Some(to) => to,
+        None if window.exclude_today => today - Days::new(1),
+        None => today,
+    };
+    let start = match window.from {
+        Some(from) => from,
+        None => end - Days::new(u64::from(window.last.unwrap_or(default_last).max(1)) - 1),
+    };
+    if start > end {
+        return Err(Error::Usage(format!(
+            "--from {} is after the end of the period {}",
+            start, end
+        )));
+    }
+    let mut days = start
+        .iter_days()
+        .take_while(|day| *day <= end)
+        .collect::<Vec<_>>();
+    if let Some(last_n_day) = window.last.filter(|_| window.from.is_some()) {
+        days.drain(..days.len().saturating_sub(last_n_day as usize));
+    }
+    Ok(days)
+}
+
+struct VersionDownloadsRow {
+    version: String,
+    published: Option<NaiveDate>,
+    daily: Vec<u64>,
+    all_time: Option<u64>,
+}
+
+fn group_by_major(breakdown: Vec<VersionDownloadsRow>) -> Vec<VersionDownloadsRow> {
+    let mut majors: BTreeMap<Option<u64>, VersionDownloadsRow> = BTreeMap::new();
+    for row in breakdown {
+        let major = semver::Version::parse(&row.version).map(|version| version.major);
+        let grouped = majors.entry(major).or_insert_with(|| VersionDownloadsRow {
+            version: major.map_or("other".to_string(), |major| format!("{}.x", major)),
+            published: None,
+            daily: vec![0; row.daily.len()],
+            all_time: Some(0),
+        });
+        grouped.published = grouped.published.into_iter().chain(row.published).min();
+        for (total, count) in grouped.daily.iter_mut().zip(&row.daily) {
+            *total += count;
+        }
+        grouped.all_time = grouped.all_time.zip(row.all_time).map(|(a, b)| a + b);
+    }
+    majors.into_values().rev().collect()
+}
+
+async fn print_version_breakdown(
+    api_crate: &CrateResponse,
+    downloads: &CrateDownloads,
+    dates: &[NaiveDate],
+    show_days: bool,
+    by_major: bool,
+    output: &OutputArgs,
+) -> Result<()> {
+    let mut per_version: HashMap<u64, BTreeMap<NaiveDate, u64>> = HashMap::new();
+    for vd in &downloads.version_downloads {
+        if dates.contains(&vd.date) {
+            *per_version
+                .entry(vd.version)
+                .or_default()
+                .entry(vd.date)
+                .or_insert(0) += vd.downloads;
+        }
+    }
+    let mut other_versions: BTreeMap<NaiveDate, u64> = BTreeMap::new();
+    for extra in &downloads.meta.extra_downloads {
+        if dates.contains(&extra.date) {
+            *other_versions.entry(extra.date).or_insert(0) += extra.downloads;
+        }
+    }
+
+    let day_cells = |daily: &BTreeMap<NaiveDate, u64>| {
+        dates
+            .iter()
+            .map(|date| daily.get(date).copied().unwrap_or(0))
+            .collect::<Vec<_>>()
+    };
+    let mut breakdown = api_crate
+        .versions
+        .iter()
+        .filter_map(|version| {
+            per_version
+                .get(&version.id)
+                .map(|daily| VersionDownloadsRow {
+                    version: version.num.clone(),
+                    published: Some(version.created_at.date_naive()),
+                    daily: day_cells(daily),
+                    all_time: Some(version.downloads),
+                })
+        })
+        .collect::<Vec<_>>();
+    if by_major {
+        breakdown = group_by_major(breakdown);
+    }
+    if !other_versions.is_empty() {
+        breakdown.push(VersionDownloadsRow {
+            version: "Other versions".to_string(),
+            published: None,
+            daily: day_cells(&other_versions),
+            all_time: None,
+        });
+    }
+
+    if output.chart_file.is_some() {
+        let counts = breakdown
+            .iter()
+            .map(|row| {
+                row.daily
+                    .iter()
+                    .map(|count| *count as f64)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let series = breakdown
+            .iter()
+            .zip(&counts)
+            .map(|(row, counts)| (row.version.as_str(), counts.as_slice()))
+            .collect::<Vec<_>>();
+        let title = format!("{} downloads by version", api_crate.crate_data.name);
+        write_chart(output, dates, &series, &title).await?;
+    }
+
+    if output.output == OutputFormat::Json {
+        let json = json!({
+            "crate": api_crate.crate_data.name,
+            "versions": breakdown
+                .iter()
+                .map(|row| {
+                    json!({
+                        "version": row.version,
+                        "published": row.published,
+                        "downloads": row.daily.iter().sum::<u64>(),
+                        "all_time_downloads": row.all_time,
+                        "daily": dates
+                            .iter()
+                            .zip(&row.daily)
+                            .map(|(date, count)| json!({"date": date, "downloads": count}))
+                            .collect::<Vec<_>>(),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        });
+        return write_json(output, &json).await;
+    }
+    if output.output == OutputFormat::Influx {
+        let lines = breakdown
+            .iter()
+            .map(|row| {
+                let daily = dates.iter().copied().zip(row.daily.iter().copied());
+                let version = row.all_time.map_or("other", |_| row.version.as_str());
+                metrics::influx_lines(
+                    &api_crate.crate_data.name,
+                    Some(version),
+                    &daily.collect::<Vec<_>>(),
+                )
+            })
+            .collect::<String>();
+        return write_output(output, &lines).await;
+    }
+    if matches!(
+        output.output,
+        OutputFormat::Graph | OutputFormat::Svg | OutputFormat::Html
+    ) {
+        return Err(Error::Usage(
+            "--by-version supports table, csv, md, json and influx output".to_string(),
+        ));
+    }
+
+    let mut headers = vec![
+        "Version".to_string(),
+        "Published".to_string(),
+        "Downloads".to_string(),
+        "All Time".to_string(),
+    ];
+    if show_days {
+        headers.extend(dates.iter().map(|date| date.format("%Y-%m-%d").to_string()));
+    }
+    let mut rows = breakdown
+        .iter()
+        .map(|version_row| {
+            let mut row = vec![
+                version_row.version.clone(),
+                version_row
+                    .published
+                    .map(|date| date.to_string())
+                    .unwrap_or_default(),
+                version_row.daily.iter().sum::<u64>().to_string(),
+                version_row
+                    .all_time
+                    .map(|count| count.to_string())
+                    .unwrap_or_default(),
+            ];
+            if show_days {
+                row.extend(version_row.daily.iter().map(|count| count.to_string()));
+            }
+            row
+        })
+        .collect::<Vec<_>>();
+    let mut total_row = vec![
+        "Total".to_string(),
+        String::new(),
+        breakdown
+            .iter()
+            .map(|row| row.daily.iter().sum::<u64>())
+            .sum::<u64>()
+            .to_string(),
+        api_crate.crate_data.downloads.to_string(),
+    ];
+    if show_days {
+        total_row.extend((0..dates.len()).map(|i| {
+            breakdown
+                .iter()
+                .map(|row| row.daily[i])
+                .sum::<u64>()
+                .to_string()
+        }));
+    }
+    rows.push(total_row);
+    print_rows(output, headers, rows).await
+}
+
+async fn print_crate_dependents(
+    dependents: &ReverseDependencies,
+    output: &OutputArgs,
+) -> Result<()> {
+    let mut rows = dependents
+        .dependencies
+        .iter()
+        .map(|rd| {
+            vec![
+                rd.crate_version.crate_name.clone(),
+                rd.dependency.downloads.to_string(),
+                rd.dependency.req.clone(),
+                rd.dependency.kind.clone(),
+                if rd.dependency.optional { "yes" } else { "no" }.to_string(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    rows.push(vec![
+        format!("Total ({} dependents)", dependents.dependencies.len()),
+        dependents
+            .dependencies
+            .iter()
+            .map(|rd| rd.dependency.downloads)
+            .sum::<u64>()
+            .to_string(),
+        String::new(),
+        String::new(),
+        String::new(),
+    ]);
+    let headers = [
+        "Crate Name",
+        "Download Count",
+        "Requirement",
+        "Kind",
+        "Optional",
+    ]
+    .iter()
+    .map(|header| header.to_string())
+    .collect();
+    print_rows(output, headers, rows).await
+}
+
+async fn track_owners(
+    client: &api::Client,
+    crate_names: &[String],
+    state_file: &Path,
+    output: &OutputArgs,
+) -> Result<serde_json::Value> {
+    let mut known_owners: BTreeMap<String, BTreeSet<String>> =
+        match tokio::fs::read_to_string(state_file).await {
+            Ok(content) => serde_json::from_str(&content)
+                .context(format!("can not parse {}", state_file.display()))?,
+            Err(_) => BTreeMap::new(),
+        };
+
+    let current_owners = stream::iter(crate_names.to_vec())
+        .map(|crate_name| {
+            let client = client.clone();
+            async move {
+                let owners = client.crate_owners(&crate_name).await;
+                (crate_name, owners)
+            }
+        })
+        .buffer_unordered(config::concurrency())
+        .collect::<Vec<_>>()
+        .await;
+
+    let mut changes = Vec::new();
+    for (crate_name, owners) in current_owners {
+        let owners = match owners {
+            Ok(owners) => owners
+                .into_iter()
+                .map(|owner| owner.login)
+                .collect::<BTreeSet<_>>(),
+            Err(err) => {
+                tracing::warn!("can not get owners of {}: {}", crate_name, err);
+                continue;
+            }
+        };
+        if let Some(previous) = known_owners.get(&crate_name) {
+            for added in owners.difference(previous) {
+                changes.push((crate_name.clone(), "added", added.clone()));
+            }
+            for removed in previous.difference(&owners) {
+                changes.push((crate_name.clone(), "removed", removed.clone()));
+            }
+        }
+        known_owners.insert(crate_name, owners);
+    }
+    changes.sort();
+
+    // json output carries the changes in its own document
+    match output.output {
+        OutputFormat::Json => {}
+        OutputFormat::Table | OutputFormat::Graph | OutputFormat::Md if changes.is_empty() => {
+            println!("No owner changes since last run")
+        }
+        _ => print_owner_changes(&changes, output).await?,
+    }
+
+    let content =
+        serde_json::to_string_pretty(&known_owners).context("can not serialize owners")?;
+    tokio::fs::write(state_file, content)
+        .await
+        .context(format!("can not write {}", state_file.display()))?;
+    Ok(changes
+        .iter()
+        .map(|(crate_name, change, owner)| {
+            json!({"crate": crate_name, "change": change, "owner": owner})
+        })
+        .collect())
+}
+
+async fn print_owner_changes(
+    changes: &[(String, &str, String)],
+    output: &OutputArgs,
+) -> Result<()> {
+    let rows = changes
+        .iter()
+        .map(|(crate_name, change, owner)| {
+            vec![crate_name.clone(), change.to_string(), owner.clone()]
+        })
+        .collect::<Vec<_>>();
+    print_rows(
+        output,
+        vec![
+            "Crate Name".to_string(),
+            "Change".to_string(),
+            "Owner".to_string(),
+        ],
+        rows,
+    )
+    .await
+}
+
+fn bucket_label(date: NaiveDate, period: Period) -> String {
+    match period {
+        Period::Day | Period::Major => date.to_string(),
+        Period::Week => format!("{}-W{:02}", date.iso_week().year(), date.iso_week().week()),
+        Period::Month => date.format("%Y-%m").to_string(),
+        Period::Quarter => format!("{}-Q{}", date.year(), date.month0() / 3 + 1),
+    }
+}
+
+fn group_downloads(downloads: &[(NaiveDate, f64)], period: Period) -> Vec<(String, f64)> {
+    let mut grouped: Vec<(String, f64)> = Vec::new();
+    for (date, count) in downloads.iter().sorted_by_key(|d| d.0) {
+        let label = bucket_label(*date, period);
+        match grouped.last_mut() {
+            Some(last) if last.0 == label => last.1 += count,
+            _ => grouped.push((label, *count)),
+        }
+    }
+    grouped
+}
 
-    let mut stdout = io::stdout();
-    let _ = stdout.write_all(table.to_string().as_bytes()).await;
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
 }
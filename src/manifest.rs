@@ -0,0 +1,258 @@
+use crate::error::{Context, Error, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+pub struct Dependency {
+    pub name: String,
+    pub kinds: Vec<&'static str>,
+}
+
+pub fn workspace_crates(path: &Path) -> Result<Vec<String>> {
+    let (manifests, workspace) = workspace_manifests(path)?;
+    let mut crate_names = Vec::new();
+    for manifest in &manifests {
+        if let Some(name) = publishable_name(manifest, workspace.as_ref()) {
+            if !crate_names.contains(&name) {
+                crate_names.push(name);
+            }
+        }
+    }
+    Ok(crate_names)
+}
+
+pub fn dependencies(path: &Path) -> Result<Vec<Dependency>> {
+    let (manifests, workspace) = workspace_manifests(path)?;
+    let members = manifests
+        .iter()
+        .filter_map(|manifest| manifest.get("package")?.get("name")?.as_str())
+        .collect::<Vec<_>>();
+    let workspace_dependencies = workspace
+        .as_ref()
+        .and_then(|workspace| workspace.get("dependencies"))
+        .and_then(Value::as_table);
+
+    let mut dependencies: Vec<Dependency> = Vec::new();
+    for manifest in &manifests {
+        let targets = manifest
+            .get("target")
+            .and_then(Value::as_table)
+            .into_iter()
+            .flat_map(|targets| targets.values().filter_map(Value::as_table));
+        for table in std::iter::once(manifest).chain(targets) {
+            for (key, kind) in [
+                ("dependencies", "normal"),
+                ("dev-dependencies", "dev"),
+                ("build-dependencies", "build"),
+            ] {
+                let Some(entries) = table.get(key).and_then(Value::as_table) else {
+                    continue;
+                };
+                for (dependency_key, spec) in entries {
+                    let spec = match spec.get("workspace") {
+                        Some(Value::Boolean(true)) => workspace_dependencies
+                            .and_then(|dependencies| dependencies.get(dependency_key))
+                            .unwrap_or(spec),
+                        _ => spec,
+                    };
+                    let Some(name) = registry_name(dependency_key, spec) else {
+                        continue;
+                    };
+                    if members.contains(&name.as_str()) {
+                        continue;
+                    }
+                    match dependencies
+                        .iter_mut()
+                        .find(|dependency| dependency.name == name)
+                    {
+                        Some(dependency) if !dependency.kinds.contains(&kind) => {
+                            dependency.kinds.push(kind)
+                        }
+                        Some(_) => {}
+                        None => dependencies.push(Dependency {
+                            name,
+                            kinds: vec![kind],
+                        }),
+                    }
+                }
+            }
+        }
+    }
+    dependencies.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(dependencies)
+}
+
+pub fn locked_versions(path: &Path) -> Result<BTreeMap<String, Vec<String>>> {
+    let lock_path = manifest_dir(path).join("Cargo.lock");
+    let content = match std::fs::read_to_string(&lock_path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(err) => return Err(err).context(format!("can not read {}", lock_path.display())),
+    };
+    let lock = content
+        .parse::<Table>()
+        .context(format!("can not parse {}", lock_path.display()))?;
+    let mut versions: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for package in lock
+        .get("package")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let from_registry = package
+            .get("source")
+            .and_then(Value::as_str)
+            .is_some_and(|source| source.starts_with("registry+") || source.starts_with("sparse+"));
+        let (Some(name), Some(version)) = (
+            package.get("name").and_then(Value::as_str),
+            package.get("version").and_then(Value::as_str),
+        ) else {
+            continue;
+        };
+        if from_registry {
+            versions
+                .entry(name.to_string())
+                .or_default()
+                .push(version.to_string());
+        }
+    }
+    Ok(versions)
+}
+
+fn workspace_manifests(path: &Path) -> Result<(Vec<Table>, Option<Table>)> {
+    let root = manifest_dir(path);
+    let manifest = read_manifest(&root)?;
+    let Some(workspace) = manifest.get("workspace").and_then(Value::as_table).cloned() else {
+        return Ok((vec![manifest], None));
+    };
+    let excluded = string_list(workspace.get("exclude"))
+        .iter()
+        .map(|exclude| root.join(exclude))
+        .collect::<Vec<_>>();
+    let mut manifests = vec![manifest];
+    for pattern in string_list(workspace.get("members")) {
+        for member in expand_member(&root, &pattern)? {
+            if !excluded.contains(&member) && member != root {
+                manifests.push(read_manifest(&member)?);
+            }
+        }
+    }
+    Ok((manifests, Some(workspace)))
+}
+
+fn registry_name(key: &str, spec: &Value) -> Option<String> {
+    match spec {
+        Value::String(_) => Some(key.to_string()),
+        Value::Table(spec) if spec.contains_key("path") || spec.contains_key("git") => None,
+        Value::Table(spec) if spec.contains_key("registry") => None,
+        Value::Table(spec) => Some(
+            spec.get("package")
+                .and_then(Value::as_str)
+                .unwrap_or(key)
+                .to_string(),
+        ),
+        _ => None,
+    }
+}
+
+fn manifest_dir(path: &Path) -> PathBuf {
+    if path.file_name() == Some("Cargo.toml".as_ref()) {
+        path.parent().unwrap_or(Path::new(".")).to_path_buf()
+    } else {
+        path.to_path_buf()
+    }
+}
+
+fn read_manifest(dir: &Path) -> Result<Table> {
+    let path = dir.join("Cargo.toml");
+    let content =
+        std::fs::read_to_string(&path).context(format!("can not read {}", path.display()))?;
+    content
+        .parse::<Table>()
+        .context(format!("can not parse {}", path.display()))
+}
+
+fn publishable_name(manifest: &Table, workspace: Option<&Table>) -> Option<String> {
+    let package = manifest.get("package")?.as_table()?;
+    let mut publish = package.get("publish");
+    if publish
+        .and_then(Value::as_table)
+        .is_some_and(|publish| publish.get("workspace") == Some(&Value::Boolean(true)))
+    {
+        publish = workspace
+            .and_then(|workspace| workspace.get("package"))
+            .and_then(|package| package.get("publish"));
+    }
+    let publishable = match publish {
+        Some(Value::Boolean(publish)) => *publish,
+        Some(Value::Array(registries)) => !registries.is_empty(),
+        _ => true,
+    };
+    publishable
+        .then(|| package.get("name")?.as_str().map(str::to_string))
+        .flatten()
+}
+
+fn string_list(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| value.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn expand_member(root: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+    let mut dirs = vec![root.to_path_buf()];
+    for component in Path::new(pattern).components() {
+        let component = component.as_os_str().to_string_lossy();
+        if !component.contains('*') {
+            dirs = dirs.into_iter().map(|dir| dir.join(&*component)).collect();
+            continue;
+        }
+        let mut matched = Vec::new();
+        for dir in dirs {
+            let entries =
+                std::fs::read_dir(&dir).context(format!("can not read {}", dir.display()))?;
+            for entry in entries {
+                let path = entry
+                    .context(format!("can not read {}", dir.display()))?
+                    .path();
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                if path.is_dir() && wildcard_match(&component, &name) {
+                    matched.push(path);
+                }
+            }
+        }
+        matched.sort();
+        dirs = matched;
+    }
+    dirs.retain(|dir| dir.join("Cargo.toml").is_file());
+    if dirs.is_empty() && !pattern.contains('*') {
+        return Err(Error::Usage(format!(
+            "workspace member {} has no Cargo.toml",
+            pattern
+        )));
+    }
+    Ok(dirs)
+}
+
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => {
+            let Some(name) = name.strip_prefix(prefix) else {
+                return false;
+            };
+            (0..=name.len())
+                .filter(|&i| name.is_char_boundary(i))
+                .any(|i| wildcard_match(rest, &name[i..]))
+        }
+    }
+}
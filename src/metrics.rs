@@ -0,0 +1,312 @@
+use crate::api::{self, Client};
+use crate::config;
+use crate::error::{crate_not_found, Context, Error, Result};
+use chrono::{NaiveDate, Utc};
+use crabst_lib::stats::daily_downloads;
+use futures::{stream, StreamExt};
+use reqwest::Url;
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+use tokio::sync::RwLock;
+
+pub struct CrateMetrics {
+    pub name: String,
+    pub downloads: u64,
+    pub recent_downloads: u64,
+    pub daily: Option<(NaiveDate, u64)>,
+    pub dependents: u64,
+}
+
+pub async fn collect(client: &Client, crate_names: &[String]) -> Result<Vec<CrateMetrics>> {
+    stream::iter(crate_names.to_vec())
+        .map(|crate_name| async move {
+            let api_crate = client
+                .get_crate(&crate_name)
+                .await
+                .map_err(crate_not_found(&crate_name))?
+                .crate_data;
+            let downloads = client
+                .crate_downloads(&crate_name)
+                .await
+                .context(format!("can not get downloads of {}", crate_name))?;
+            let dependents = client
+                .crate_reverse_dependency_count(&crate_name)
+                .await
+                .context(format!("can not get dependents of {}", crate_name))?;
+            Ok(CrateMetrics {
+                name: crate_name,
+                downloads: api_crate.downloads,
+                recent_downloads: api_crate.recent_downloads.unwrap_or(0),
+                daily: daily_downloads(&downloads).into_iter().next_back(),
+                dependents,
+            })
+        })
+        .buffered(config::concurrency())
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect()
+}
+
+struct Family {
+    name: &'static str,
+    kind: &'static str,
+    help: &'static str,
+    value: fn(&CrateMetrics) -> Option<u64>,
+}
+
+const FAMILIES: [Family; 4] = [
+    Family {
+        name: "crabst_crate_downloads_total",
+        kind: "counter",
+        help: "all time downloads of the crate",
+        value: |m| Some(m.downloads),
+    },
+    Family {
+        name: "crabst_crate_recent_downloads",
+        kind: "gauge",
+        help: "downloads of the crate in the last 90 days",
+        value: |m| Some(m.recent_downloads),
+    },
+    Family {
+        name: "crabst_crate_daily_downloads",
+        kind: "gauge",
+        help: "downloads of the crate on the latest reported day",
+        value: |m| m.daily.map(|(_, count)| count),
+    },
+    Family {
+        name: "crabst_crate_dependents",
+        kind: "gauge",
+        help: "number of crates depending on the crate",
+        value: |m| Some(m.dependents),
+    },
+];
+
+pub fn render_prometheus(metrics: &[CrateMetrics], up: bool) -> String {
+    let mut out = String::new();
+    for Family {
+        name,
+        kind,
+        help,
+        value,
+    } in FAMILIES
+    {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} {}", name, kind);
+        for crate_metrics in metrics {
+            if let Some(value) = value(crate_metrics) {
+                let _ = writeln!(
+                    out,
+                    "{}{{crate=\"{}\"}} {}",
+                    name,
+                    escape_label(&crate_metrics.name),
+                    value
+                );
+            }
+        }
+    }
+    let _ = writeln!(out, "# HELP crabst_up whether the last refresh succeeded");
+    let _ = writeln!(out, "# TYPE crabst_up gauge");
+    let _ = writeln!(out, "crabst_up {}", u8::from(up));
+    let _ = writeln!(
+        out,
+        "# HELP crabst_last_refresh_timestamp_seconds unix time of the last refresh"
+    );
+    let _ = writeln!(out, "# TYPE crabst_last_refresh_timestamp_seconds gauge");
+    let _ = writeln!(
+        out,
+        "crabst_last_refresh_timestamp_seconds {}",
+        Utc::now().timestamp()
+    );
+    out
+}
+
+pub async fn serve(addr: &str, crate_names: Vec<String>, interval: Duration) -> Result<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .context(format!("can not listen on {}", addr))?;
+    tracing::warn!("serving metrics on http://{}/metrics", addr);
+
+    let client = api::client()?;
+    let page = Arc::new(RwLock::new(String::new()));
+    let refresh_page = page.clone();
+    tokio::spawn(async move {
+        let mut last = Vec::new();
+        loop {
+            let up = match collect(&client, &crate_names).await {
+                Ok(metrics) => {
+                    last = metrics;
+                    true
+                }
+                Err(err) => {
+                    tracing::error!("{}", err);
+                    false
+                }
+            };
+            *refresh_page.write().await = render_prometheus(&last, up);
+            tokio::time::sleep(interval).await;
+        }
+    });
+
+    loop {
+        let (stream, _) = listener
+            .accept()
+            .await
+            .context(format!("can not accept connections on {}", addr))?;
+        let page = page.clone();
+        tokio::spawn(async move {
+            let _ = respond(stream, &page).await;
+        });
+    }
+}
+
+async fn respond(mut stream: TcpStream, page: &RwLock<String>) -> std::io::Result<()> {
+    let mut buffer = [0; 1024];
+    let read = stream.read(&mut buffer).await?;
+    let request = String::from_utf8_lossy(&buffer[..read]);
+    let path = request.split_whitespace().nth(1).unwrap_or("/");
+    let (status, body) = match path {
+        "/metrics" => ("200 OK", page.read().await.clone()),
+        _ => ("404 Not Found", "see /metrics\n".to_string()),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+pub enum PushTarget {
+    Statsd(String),
+    Graphite(String),
+    Pushgateway(Url),
+}
+
+impl PushTarget {
+    pub fn parse(target: &str) -> Result<PushTarget> {
+        let invalid = || Error::Usage(format!("invalid push target {}", target));
+        let mut url = Url::parse(target).map_err(|_| invalid())?;
+        let host = url.host_str().ok_or_else(invalid)?.to_string();
+        match url.scheme() {
+            "statsd" => Ok(PushTarget::Statsd(format!(
+                "{}:{}",
+                host,
+                url.port().unwrap_or(8125)
+            ))),
+            "graphite" => Ok(PushTarget::Graphite(format!(
+                "{}:{}",
+                host,
+                url.port().unwrap_or(2003)
+            ))),
+            "http" | "https" => {
+                if !url.path().contains("/metrics/job/") {
+                    url.path_segments_mut()
+                        .map_err(|_| invalid())?
+                        .pop_if_empty()
+                        .extend(["metrics", "job", "crabst"]);
+                }
+                Ok(PushTarget::Pushgateway(url))
+            }
+            scheme => Err(Error::Usage(format!(
+                "unknown push target scheme {}, use statsd, graphite, http or https",
+                scheme
+            ))),
+        }
+    }
+}
+
+pub async fn push(target: &PushTarget, metrics: &[CrateMetrics]) -> Result<()> {
+    match target {
+        PushTarget::Statsd(addr) => {
+            let socket = UdpSocket::bind("0.0.0.0:0")
+                .await
+                .context("can not open udp socket")?;
+            for (name, value) in flat_metrics(metrics) {
+                socket
+                    .send_to(format!("{}:{}|g", name, value).as_bytes(), addr)
+                    .await
+                    .context(format!("can not push to statsd at {}", addr))?;
+            }
+            Ok(())
+        }
+        PushTarget::Graphite(addr) => {
+            let timestamp = Utc::now().timestamp();
+            let mut lines = String::new();
+            for (name, value) in flat_metrics(metrics) {
+                let _ = writeln!(lines, "{} {} {}", name, value, timestamp);
+            }
+            let mut stream = TcpStream::connect(addr)
+                .await
+                .context(format!("can not connect to graphite at {}", addr))?;
+            stream
+                .write_all(lines.as_bytes())
+                .await
+                .context(format!("can not push to graphite at {}", addr))?;
+            stream
+                .shutdown()
+                .await
+                .context(format!("can not push to graphite at {}", addr))
+        }
+        PushTarget::Pushgateway(url) => {
+            api::http_client()?
+                .put(url.clone())
+                .body(render_prometheus(metrics, true))
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .context(format!("can not push to {}", url))?;
+            Ok(())
+        }
+    }
+}
+
+fn flat_metrics(metrics: &[CrateMetrics]) -> Vec<(String, u64)> {
+    metrics
+        .iter()
+        .flat_map(|crate_metrics| {
+            FAMILIES.iter().filter_map(|family| {
+                let name = family.name.trim_start_matches("crabst_crate_");
+                (family.value)(crate_metrics)
+                    .map(|value| (format!("crabst.{}.{}", crate_metrics.name, name), value))
+            })
+        })
+        .collect()
+}
+
+pub fn influx_lines(crate_name: &str, version: Option<&str>, daily: &[(NaiveDate, u64)]) -> String {
+    let mut tags = format!("crate={}", escape_tag(crate_name));
+    if let Some(version) = version {
+        let _ = write!(tags, ",version={}", escape_tag(version));
+    }
+    let mut out = String::new();
+    for (date, count) in daily {
+        let timestamp = date.and_time(chrono::NaiveTime::MIN).and_utc().timestamp() * 1_000_000_000;
+        let _ = writeln!(
+            out,
+            "crabst_downloads,{} downloads={}i {}",
+            tags, count, timestamp
+        );
+    }
+    out
+}
+
+fn escape_tag(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace('=', "\\=")
+        .replace(' ', "\\ ")
+}
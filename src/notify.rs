@@ -0,0 +1,184 @@
+use crate::api::Client;
+use crate::config;
+use crate::config::AlertRule;
+use crate::error::{crate_not_found, Context, Result};
+use chrono::Utc;
+use crabst_lib::stats::daily_downloads;
+use futures::{stream, StreamExt};
+use itertools::Itertools;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::Url;
+use serde_json::json;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+pub struct Alert {
+    pub crate_name: String,
+    pub rule: &'static str,
+    pub threshold: u64,
+    pub value: u64,
+    pub text: String,
+}
+
+impl Alert {
+    fn key(&self) -> String {
+        format!("{}:{}:{}", self.crate_name, self.rule, self.threshold)
+    }
+}
+
+struct CrateNumbers {
+    name: String,
+    downloads: u64,
+    days: Vec<(chrono::NaiveDate, u64)>,
+}
+
+pub async fn run(
+    client: &Client,
+    rules: &[AlertRule],
+    webhook: &Url,
+    state_file: &Path,
+) -> Result<Vec<Alert>> {
+    let mut known: BTreeSet<String> = match tokio::fs::read_to_string(state_file).await {
+        Ok(content) => serde_json::from_str(&content)
+            .context(format!("can not parse {}", state_file.display()))?,
+        Err(_) => BTreeSet::new(),
+    };
+
+    let alerts = evaluate(client, rules).await?;
+    let mut sent = Vec::new();
+    let mut result = Ok(());
+    for alert in alerts.iter().filter(|alert| !known.contains(&alert.key())) {
+        if let Err(err) = send(webhook, alert).await {
+            result = Err(err);
+            break;
+        }
+        sent.push(alert.key());
+    }
+    known = alerts
+        .iter()
+        .map(Alert::key)
+        .filter(|key| known.contains(key) || sent.contains(key))
+        .collect();
+
+    let content = serde_json::to_string_pretty(&known).context("can not serialize alerts")?;
+    tokio::fs::write(state_file, content)
+        .await
+        .context(format!("can not write {}", state_file.display()))?;
+    result?;
+    Ok(alerts
+        .into_iter()
+        .filter(|alert| sent.contains(&alert.key()))
+        .collect())
+}
+
+async fn evaluate(client: &Client, rules: &[AlertRule]) -> Result<Vec<Alert>> {
+    let crate_names = rules
+        .iter()
+        .map(|rule| rule.crate_name.clone())
+        .unique()
+        .collect::<Vec<_>>();
+    let numbers = stream::iter(crate_names)
+        .map(|crate_name| async move {
+            let api_crate = client
+                .get_crate(&crate_name)
+                .await
+                .map_err(crate_not_found(&crate_name))?
+                .crate_data;
+            let downloads = client
+                .crate_downloads(&crate_name)
+                .await
+                .context(format!("can not get downloads of {}", crate_name))?;
+            let today = Utc::now().date_naive();
+            Ok(CrateNumbers {
+                name: crate_name,
+                downloads: api_crate.downloads,
+                days: daily_downloads(&downloads)
+                    .into_iter()
+                    .filter(|(date, _)| *date < today)
+                    .collect(),
+            })
+        })
+        .buffered(config::concurrency())
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut alerts = Vec::new();
+    for rule in rules {
+        let Some(numbers) = numbers.iter().find(|n| n.name == rule.crate_name) else {
+            continue;
+        };
+        let alert = |rule_name, threshold, value, text| Alert {
+            crate_name: numbers.name.clone(),
+            rule: rule_name,
+            threshold,
+            value,
+            text,
+        };
+        if let Some(threshold) = rule.total_above {
+            if numbers.downloads >= threshold {
+                alerts.push(alert(
+                    "total_above",
+                    threshold,
+                    numbers.downloads,
+                    format!(
+                        "{} crossed {} total downloads, now at {}",
+                        numbers.name, threshold, numbers.downloads
+                    ),
+                ));
+            }
+        }
+        let latest = numbers.days.last();
+        if let (Some(threshold), Some((date, count))) = (rule.daily_above, latest) {
+            if *count >= threshold {
+                alerts.push(alert(
+                    "daily_above",
+                    threshold,
+                    *count,
+                    format!(
+                        "{} had {} downloads on {}, above {}",
+                        numbers.name, count, date, threshold
+                    ),
+                ));
+            }
+        }
+        let previous = numbers.days.iter().rev().nth(1);
+        if let (Some(threshold), Some((date, count)), Some((_, before))) =
+            (rule.daily_drop_percent, latest, previous)
+        {
+            let drop = before.saturating_sub(*count) * 100 / (*before).max(1);
+            if *before > 0 && drop >= threshold {
+                alerts.push(alert(
+                    "daily_drop_percent",
+                    threshold,
+                    drop,
+                    format!(
+                        "{} daily downloads dropped {}% from {} to {} on {}",
+                        numbers.name, drop, before, count, date
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(alerts)
+}
+
+async fn send(webhook: &Url, alert: &Alert) -> Result<()> {
+    let payload = json!({
+        "crate": alert.crate_name,
+        "rule": alert.rule,
+        "threshold": alert.threshold,
+        "value": alert.value,
+        "text": alert.text,
+    });
+    crate::api::http_client()?
+        .post(webhook.clone())
+        .header(CONTENT_TYPE, "application/json")
+        .body(payload.to_string())
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .context(format!("can not post alert to {}", webhook))?;
+    Ok(())
+}
@@ -0,0 +1,295 @@
+use crate::error::{Error, Result};
+use chrono::{DateTime, Datelike, NaiveDateTime, TimeZone, Timelike};
+
+const SEARCH_DAYS: usize = 4 * 366;
+
+pub struct Schedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Schedule {
+    pub fn parse(expr: &str) -> Result<Schedule> {
+        let expanded = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let invalid = || {
+            Error::Usage(format!(
+                "invalid schedule `{}`, expected minute hour day month weekday",
+                expr
+            ))
+        };
+        let fields = expanded.split_whitespace().collect::<Vec<_>>();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(invalid());
+        };
+        let mut weekday_bits = field(weekdays, 0, 7).ok_or_else(invalid)?;
+        if weekday_bits & (1 << 7) != 0 {
+            weekday_bits |= 1;
+        }
+        Ok(Schedule {
+            minutes: field(minutes, 0, 59).ok_or_else(invalid)?,
+            hours: field(hours, 0, 23).ok_or_else(invalid)?,
+            days: field(days, 1, 31).ok_or_else(invalid)?,
+            months: field(months, 1, 12).ok_or_else(invalid)?,
+            weekdays: weekday_bits,
+            any_day: days == "*",
+            any_weekday: weekdays == "*",
+        })
+    }
+
+    pub fn next_after<Tz: TimeZone>(&self, after: DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let start = after
+            .naive_local()
+            .with_second(0)?
+            .with_nanosecond(0)?
+            .checked_add_signed(chrono::Duration::minutes(1))?;
+        for date in start.date().iter_days().take(SEARCH_DAYS) {
+            if !self.matches_day(date) {
+                continue;
+            }
+            for hour in (0..24).filter(|hour| bit(self.hours, *hour)) {
+                for minute in (0..60).filter(|minute| bit(self.minutes, *minute)) {
+                    let time: NaiveDateTime = date.and_hms_opt(hour, minute, 0)?;
+                    if time < start {
+                        continue;
+                    }
+                    // times skipped by a daylight saving change do not exist
+                    if let Some(at) = after.timezone().from_local_datetime(&time).earliest() {
+                        return Some(at);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn matches_day(&self, date: chrono::NaiveDate) -> bool {
+        if !bit(self.months, date.month()) {
+            return false;
+        }
+        let day = bit(self.days, date.day());
+        let weekday = bit(self.weekdays, date.weekday().num_days_from_sunday());
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        }
+    }
+}
+
+fn bit(bits: u64, value: u32) -> bool {
+    bits & (1 << value) != 0
+}
+
+fn field(text: &str, min: u32, max: u32) -> Option<u64> {
+    let mut bits = 0;
+    for part in text.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|step| *step > 0)?),
+            None => (part, 1),
+        };
+        let (first, last) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((first, last)) => (first.parse().ok()?, last.parse().ok()?),
+            None if part.contains('/') => (range.parse().ok()?, max),
+            None => (range.parse().ok()?, range.parse().ok()?),
+        };
+        if first < min || last > max || first > last {
+            return None;
+        }
+        for value in (first..=last).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Some(bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, LocalResult, NaiveDate};
+
+    // utc+1 that skips from 02:00 to 03:00 on 2024-03-31, like central europe
+    #[derive(Clone, Copy, Debug)]
+    struct Spring;
+
+    fn change() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 3, 31)
+            .unwrap()
+            .and_hms_opt(2, 0, 0)
+            .unwrap()
+    }
+
+    fn hours(offset: i32) -> FixedOffset {
+        FixedOffset::east_opt(offset * 3600).unwrap()
+    }
+
+    impl TimeZone for Spring {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Spring {
+            Spring
+        }
+
+        fn offset_from_local_date(&self, _: &NaiveDate) -> LocalResult<FixedOffset> {
+            LocalResult::Single(hours(1))
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> LocalResult<FixedOffset> {
+            match *local {
+                local if local < change() => LocalResult::Single(hours(1)),
+                local if local < change() + chrono::Duration::hours(1) => LocalResult::None,
+                _ => LocalResult::Single(hours(2)),
+            }
+        }
+
+        fn offset_from_utc_date(&self, _: &NaiveDate) -> FixedOffset {
+            hours(1)
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            match *utc < change() - chrono::Duration::hours(1) {
+                true => hours(1),
+                false => hours(2),
+            }
+        }
+    }
+
+    fn next(expr: &str, after: &str) -> Option<String> {
+        let after = NaiveDateTime::parse_from_str(after, "%Y-%m-%d %H:%M").unwrap();
+        let after = Spring.from_local_datetime(&after).unwrap();
+        let next = Schedule::parse(expr).unwrap().next_after(after)?;
+        Some(next.naive_local().format("%Y-%m-%d %H:%M").to_string())
+    }
+
+    #[test]
+    fn steps() {
+        assert_eq!(
+            next("*/15 * * * *", "2024-03-01 10:07").unwrap(),
+            "2024-03-01 10:15"
+        );
+        assert_eq!(
+            next("*/15 * * * *", "2024-03-01 10:45").unwrap(),
+            "2024-03-01 11:00"
+        );
+        assert_eq!(
+            next("5/10 * * * *", "2024-03-01 10:07").unwrap(),
+            "2024-03-01 10:15"
+        );
+        assert_eq!(
+            next("5/10 * * * *", "2024-03-01 10:55").unwrap(),
+            "2024-03-01 11:05"
+        );
+    }
+
+    #[test]
+    fn ranges_and_lists() {
+        // 2024-03-01 is a friday
+        let workdays = "0 9-17 * * 1-5";
+        assert_eq!(
+            next(workdays, "2024-03-01 16:30").unwrap(),
+            "2024-03-01 17:00"
+        );
+        assert_eq!(
+            next(workdays, "2024-03-01 17:00").unwrap(),
+            "2024-03-04 09:00"
+        );
+        assert_eq!(
+            next("0,30 8 * * *", "2024-03-01 08:10").unwrap(),
+            "2024-03-01 08:30"
+        );
+    }
+
+    #[test]
+    fn weekday_seven_is_sunday() {
+        assert_eq!(
+            next("0 0 * * 7", "2024-03-01 12:00").unwrap(),
+            "2024-03-03 00:00"
+        );
+        assert_eq!(
+            next("0 0 * * 0", "2024-03-01 12:00").unwrap(),
+            "2024-03-03 00:00"
+        );
+    }
+
+    #[test]
+    fn day_and_weekday_match_either() {
+        // the 13th or any friday
+        assert_eq!(
+            next("0 0 13 * 5", "2024-03-01 00:00").unwrap(),
+            "2024-03-08 00:00"
+        );
+        assert_eq!(
+            next("0 0 13 * 5", "2024-03-08 00:00").unwrap(),
+            "2024-03-13 00:00"
+        );
+        // only one of them restricted narrows to that one
+        assert_eq!(
+            next("0 0 13 * *", "2024-03-01 00:00").unwrap(),
+            "2024-03-13 00:00"
+        );
+    }
+
+    #[test]
+    fn shortcuts() {
+        assert_eq!(
+            next("@weekly", "2024-03-01 12:00").unwrap(),
+            "2024-03-03 00:00"
+        );
+        assert_eq!(
+            next("@monthly", "2024-03-01 12:00").unwrap(),
+            "2024-04-01 00:00"
+        );
+        assert_eq!(
+            next("@hourly", "2024-03-01 12:00").unwrap(),
+            "2024-03-01 13:00"
+        );
+    }
+
+    #[test]
+    fn invalid_fields() {
+        for expr in [
+            "",
+            "* * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "x * * * *",
+            "@yearly",
+        ] {
+            assert!(Schedule::parse(expr).is_err(), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn skips_times_lost_to_daylight_saving() {
+        assert_eq!(
+            next("30 2 * * *", "2024-03-30 03:00").unwrap(),
+            "2024-04-01 02:30"
+        );
+        assert_eq!(
+            next("30 * * * *", "2024-03-31 01:45").unwrap(),
+            "2024-03-31 03:30"
+        );
+    }
+
+    #[test]
+    fn impossible_dates_never_run() {
+        assert_eq!(next("0 0 31 2 *", "2024-03-01 00:00"), None);
+    }
+}
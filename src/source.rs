@@ -0,0 +1,122 @@
+use crate::api;
+use crate::cli::DataSource;
+use crate::error::{Context, Result};
+use crate::librs;
+use crates_io_api::CrateResponse;
+use std::collections::HashMap;
+
+pub const RANK_PAGES: usize = 10;
+
+/// Positions of a crate, overall when the scope is none and `None` when it is
+/// not among the crates searched.
+pub type Ranking = (Option<String>, Option<usize>);
+
+/// Where rankings and related crates come from.
+pub enum Provider {
+    CratesIo(api::Client),
+    LibRs(librs::Client),
+}
+
+impl Provider {
+    pub fn new(source: DataSource, client: &api::Client) -> Result<Provider> {
+        Ok(match source {
+            DataSource::CratesIo => Provider::CratesIo(client.clone()),
+            DataSource::LibRs => Provider::LibRs(librs::Client::new()?),
+        })
+    }
+
+    pub async fn rankings(&self, api_crate: &CrateResponse) -> Result<Vec<Ranking>> {
+        match self {
+            Provider::CratesIo(client) => crates_io_rankings(client, api_crate).await,
+            Provider::LibRs(client) => Ok(client
+                .rankings(&api_crate.crate_data.name)
+                .await?
+                .into_iter()
+                .map(|(category, position)| (Some(category), Some(position)))
+                .collect()),
+        }
+    }
+
+    pub async fn related(&self, api_crate: &CrateResponse) -> Result<Vec<String>> {
+        match self {
+            Provider::CratesIo(client) => crates_io_related(client, api_crate).await,
+            Provider::LibRs(client) => client.related(&api_crate.crate_data.name).await,
+        }
+    }
+}
+
+// crates.io only sorts, so the crate is looked up in the first pages of each leaderboard
+async fn crates_io_rankings(
+    client: &api::Client,
+    api_crate: &CrateResponse,
+) -> Result<Vec<Ranking>> {
+    let scopes = std::iter::once(None).chain(
+        api_crate
+            .categories
+            .iter()
+            .map(|category| Some(category.slug.clone())),
+    );
+    let mut ranks = Vec::new();
+    for scope in scopes {
+        let mut rank = None;
+        for page_number in 1..=RANK_PAGES {
+            let mut query = vec![
+                ("sort", "downloads".to_string()),
+                ("page", page_number.to_string()),
+                ("per_page", "100".to_string()),
+            ];
+            if let Some(category) = &scope {
+                query.push(("category", category.clone()));
+            }
+            let page = client
+                .crates_page(&query)
+                .await
+                .context("can not get the downloads leaderboard")?;
+            let position = page
+                .crates
+                .iter()
+                .position(|found| found.name == api_crate.crate_data.name);
+            if let Some(position) = position {
+                rank = Some((page_number - 1) * 100 + position + 1);
+            }
+            if rank.is_some() || page.crates.len() < 100 {
+                break;
+            }
+        }
+        ranks.push((scope, rank));
+    }
+    Ok(ranks)
+}
+
+// crates sharing the most keywords and categories, then the most downloaded
+async fn crates_io_related(client: &api::Client, api_crate: &CrateResponse) -> Result<Vec<String>> {
+    let filters = api_crate
+        .keywords
+        .iter()
+        .map(|keyword| ("keyword", keyword.keyword.clone()))
+        .chain(
+            api_crate
+                .categories
+                .iter()
+                .map(|category| ("category", category.slug.clone())),
+        );
+    let mut shared: HashMap<String, (usize, u64)> = HashMap::new();
+    for (filter, value) in filters {
+        let page = client
+            .crates_page(&[
+                (filter, value.clone()),
+                ("sort", "downloads".to_string()),
+                ("per_page", "50".to_string()),
+            ])
+            .await
+            .context(format!("can not get crates of {} {}", filter, value))?;
+        for found in page.crates {
+            if found.name != api_crate.crate_data.name {
+                shared.entry(found.name).or_insert((0, found.downloads)).0 += 1;
+            }
+        }
+    }
+    let mut related = shared.into_iter().collect::<Vec<_>>();
+    related.sort_by_key(|(_, shared)| std::cmp::Reverse(*shared));
+    Ok(related.into_iter().map(|(name, _)| name).collect())
+}
@@ -0,0 +1,282 @@
+use crate::error::{Context, Error, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS snapshots (
+    id INTEGER PRIMARY KEY,
+    crate TEXT NOT NULL,
+    taken_at TEXT NOT NULL,
+    downloads INTEGER NOT NULL,
+    recent_downloads INTEGER,
+    dependents INTEGER NOT NULL,
+    versions INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS snapshots_crate ON snapshots (crate, taken_at);
+CREATE TABLE IF NOT EXISTS version_snapshots (
+    snapshot_id INTEGER NOT NULL REFERENCES snapshots (id),
+    version TEXT NOT NULL,
+    downloads INTEGER NOT NULL,
+    PRIMARY KEY (snapshot_id, version)
+);
+CREATE TABLE IF NOT EXISTS daily_downloads (
+    crate TEXT NOT NULL,
+    date TEXT NOT NULL,
+    downloads INTEGER NOT NULL,
+    PRIMARY KEY (crate, date)
+);
+CREATE TABLE IF NOT EXISTS dependent_snapshots (
+    crate TEXT NOT NULL,
+    taken_at TEXT NOT NULL,
+    dependent TEXT NOT NULL,
+    PRIMARY KEY (crate, taken_at, dependent)
+);
+";
+
+pub struct Snapshot {
+    pub taken_at: DateTime<Utc>,
+    pub downloads: u64,
+    pub recent_downloads: Option<u64>,
+    pub dependents: u64,
+    pub versions: u64,
+}
+
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    pub fn open(path: Option<&Path>) -> Result<Store> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => default_path()?,
+        };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .context(format!("can not create {}", parent.display()))?;
+        }
+        let conn = Connection::open(&path).context(format!("can not open {}", path.display()))?;
+        conn.execute_batch(SCHEMA)
+            .context("can not create snapshot tables")?;
+        Ok(Store { conn })
+    }
+
+    pub fn insert_snapshot(
+        &mut self,
+        crate_name: &str,
+        snapshot: &Snapshot,
+        versions: &[(String, u64)],
+        daily: &BTreeMap<NaiveDate, u64>,
+    ) -> Result<()> {
+        let tx = self
+            .conn
+            .transaction()
+            .context("can not start snapshot transaction")?;
+        tx.execute(
+            "INSERT INTO snapshots (crate, taken_at, downloads, recent_downloads, dependents, versions)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                crate_name,
+                snapshot.taken_at,
+                snapshot.downloads,
+                snapshot.recent_downloads,
+                snapshot.dependents,
+                snapshot.versions
+            ],
+        )
+        .context(format!("can not store snapshot of {}", crate_name))?;
+        let snapshot_id = tx.last_insert_rowid();
+        for (version, downloads) in versions {
+            tx.execute(
+                "INSERT INTO version_snapshots (snapshot_id, version, downloads) VALUES (?1, ?2, ?3)",
+                params![snapshot_id, version, downloads],
+            )
+            .context(format!("can not store version {} of {}", version, crate_name))?;
+        }
+        upsert_daily(&tx, crate_name, daily)?;
+        tx.commit()
+            .context(format!("can not store snapshot of {}", crate_name))
+    }
+
+    pub fn snapshot_near(&self, crate_name: &str, at: DateTime<Utc>) -> Result<Option<Snapshot>> {
+        let before = self.query_snapshot(
+            "SELECT taken_at, downloads, recent_downloads, dependents, versions FROM snapshots
+             WHERE crate = ?1 AND taken_at <= ?2 ORDER BY taken_at DESC LIMIT 1",
+            crate_name,
+            at,
+        )?;
+        match before {
+            Some(snapshot) => Ok(Some(snapshot)),
+            None => self.query_snapshot(
+                "SELECT taken_at, downloads, recent_downloads, dependents, versions FROM snapshots
+                 WHERE crate = ?1 AND taken_at > ?2 ORDER BY taken_at ASC LIMIT 1",
+                crate_name,
+                at,
+            ),
+        }
+    }
+
+    pub fn latest_dependents(
+        &self,
+        crate_name: &str,
+    ) -> Result<Option<(DateTime<Utc>, BTreeSet<String>)>> {
+        let taken_at: Option<DateTime<Utc>> = self
+            .conn
+            .query_row(
+                "SELECT MAX(taken_at) FROM dependent_snapshots WHERE crate = ?1",
+                params![crate_name],
+                |row| row.get(0),
+            )
+            .context(format!("can not read dependents of {}", crate_name))?;
+        let Some(taken_at) = taken_at else {
+            return Ok(None);
+        };
+        let mut statement = self
+            .conn
+            .prepare("SELECT dependent FROM dependent_snapshots WHERE crate = ?1 AND taken_at = ?2")
+            .context("can not read dependents")?;
+        let rows = statement
+            .query_map(params![crate_name, taken_at], |row| row.get(0))
+            .context(format!("can not read dependents of {}", crate_name))?;
+        let dependents = rows
+            .collect::<rusqlite::Result<BTreeSet<String>>>()
+            .context(format!("can not read dependents of {}", crate_name))?;
+        Ok(Some((taken_at, dependents)))
+    }
+
+    pub fn insert_dependents(
+        &mut self,
+        crate_name: &str,
+        taken_at: DateTime<Utc>,
+        dependents: &BTreeSet<String>,
+    ) -> Result<()> {
+        let tx = self
+            .conn
+            .transaction()
+            .context("can not start dependents transaction")?;
+        for dependent in dependents {
+            tx.execute(
+                "INSERT OR IGNORE INTO dependent_snapshots (crate, taken_at, dependent)
+                 VALUES (?1, ?2, ?3)",
+                params![crate_name, taken_at, dependent],
+            )
+            .context(format!("can not store dependents of {}", crate_name))?;
+        }
+        tx.commit()
+            .context(format!("can not store dependents of {}", crate_name))
+    }
+
+    pub fn upsert_daily(
+        &mut self,
+        crate_name: &str,
+        daily: &BTreeMap<NaiveDate, u64>,
+    ) -> Result<()> {
+        let tx = self
+            .conn
+            .transaction()
+            .context("can not start daily downloads transaction")?;
+        upsert_daily(&tx, crate_name, daily)?;
+        tx.commit()
+            .context(format!("can not store daily downloads of {}", crate_name))
+    }
+
+    pub fn daily_downloads(&self, crate_name: &str) -> Result<BTreeMap<NaiveDate, u64>> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT date, downloads FROM daily_downloads WHERE crate = ?1 ORDER BY date")
+            .context("can not read daily downloads")?;
+        let rows = statement
+            .query_map(params![crate_name], |row| Ok((row.get(0)?, row.get(1)?)))
+            .context(format!("can not read daily downloads of {}", crate_name))?;
+        rows.collect::<rusqlite::Result<BTreeMap<_, _>>>()
+            .context(format!("can not read daily downloads of {}", crate_name))
+    }
+
+    pub fn import_version_downloads(
+        &mut self,
+        rows: impl Iterator<Item = Result<(String, NaiveDate, u64)>>,
+    ) -> Result<usize> {
+        let tx = self
+            .conn
+            .transaction()
+            .context("can not start import transaction")?;
+        tx.execute_batch(
+            "CREATE TEMP TABLE import_downloads (
+                crate TEXT NOT NULL,
+                date TEXT NOT NULL,
+                downloads INTEGER NOT NULL
+            )",
+        )
+        .context("can not create import table")?;
+        {
+            let mut insert = tx
+                .prepare(
+                    "INSERT INTO import_downloads (crate, date, downloads) VALUES (?1, ?2, ?3)",
+                )
+                .context("can not prepare import")?;
+            for row in rows {
+                let (crate_name, date, downloads) = row?;
+                insert
+                    .execute(params![crate_name, date, downloads])
+                    .context(format!("can not import downloads of {}", crate_name))?;
+            }
+        }
+        let imported = tx
+            .execute(
+                "INSERT INTO daily_downloads (crate, date, downloads)
+                 SELECT crate, date, SUM(downloads) FROM import_downloads WHERE true
+                 GROUP BY crate, date
+                 ON CONFLICT (crate, date) DO UPDATE SET downloads = excluded.downloads",
+                [],
+            )
+            .context("can not merge imported downloads")?;
+        tx.execute_batch("DROP TABLE import_downloads")
+            .context("can not drop import table")?;
+        tx.commit().context("can not store imported downloads")?;
+        Ok(imported)
+    }
+
+    fn query_snapshot(
+        &self,
+        sql: &str,
+        crate_name: &str,
+        at: DateTime<Utc>,
+    ) -> Result<Option<Snapshot>> {
+        self.conn
+            .query_row(sql, params![crate_name, at], |row| {
+                Ok(Snapshot {
+                    taken_at: row.get(0)?,
+                    downloads: row.get(1)?,
+                    recent_downloads: row.get(2)?,
+                    dependents: row.get(3)?,
+                    versions: row.get(4)?,
+                })
+            })
+            .optional()
+            .context(format!("can not read snapshots of {}", crate_name))
+    }
+}
+
+fn upsert_daily(
+    conn: &Connection,
+    crate_name: &str,
+    daily: &BTreeMap<NaiveDate, u64>,
+) -> Result<()> {
+    for (date, downloads) in daily {
+        conn.execute(
+            "INSERT INTO daily_downloads (crate, date, downloads) VALUES (?1, ?2, ?3)
+             ON CONFLICT (crate, date) DO UPDATE SET downloads = excluded.downloads",
+            params![crate_name, date, downloads],
+        )
+        .context(format!("can not store daily downloads of {}", crate_name))?;
+    }
+    Ok(())
+}
+
+fn default_path() -> Result<PathBuf> {
+    dirs::data_dir()
+        .map(|dir| dir.join("crabst").join("history.db"))
+        .ok_or_else(|| Error::Usage("can not find a data directory, pass --db".to_string()))
+}
@@ -0,0 +1,194 @@
+use crate::api::Client;
+use crate::error::{crate_not_found, Context, Error, Result};
+use crate::semver::{Version, VersionReq};
+use crate::{config, default_version};
+use crates_io_api::CrateResponse;
+use futures::{stream, StreamExt};
+use itertools::Itertools;
+use std::collections::{BTreeMap, HashMap};
+
+/// A crate version in a resolved dependency tree.
+pub struct Node {
+    pub name: String,
+    pub version: String,
+    pub license: Option<String>,
+    pub depth: u32,
+    pub parents: Vec<Edge>,
+}
+
+/// The requirement a crate version puts on one of its dependencies.
+pub struct Edge {
+    pub name: String,
+    pub version: String,
+    pub req: String,
+}
+
+/// Resolves the normal and build dependencies of the latest version of a crate,
+/// picking the newest version matching each requirement the way cargo would
+/// without a lock file. Optional dependencies are skipped as they depend on
+/// features.
+pub async fn resolve(client: &Client, root: CrateResponse) -> Result<Vec<Node>> {
+    let root_version = default_version(&root.crate_data);
+    let mut crates = HashMap::new();
+    let mut nodes = BTreeMap::new();
+    nodes.insert(
+        (root.crate_data.name.clone(), root_version.clone()),
+        Node {
+            name: root.crate_data.name.clone(),
+            license: license(&root, &root_version),
+            version: root_version.clone(),
+            depth: 0,
+            parents: Vec::new(),
+        },
+    );
+    let mut level = vec![(root.crate_data.name.clone(), root_version)];
+    crates.insert(root.crate_data.name.clone(), root);
+    let mut depth = 0;
+    while !level.is_empty() {
+        depth += 1;
+        let dependencies = stream::iter(level)
+            .map(|(name, version)| {
+                let client = client.clone();
+                async move {
+                    let dependencies =
+                        client
+                            .crate_dependencies(&name, &version)
+                            .await
+                            .context(format!(
+                                "can not get dependencies of crate {} {}",
+                                name, version
+                            ))?;
+                    Ok::<_, Error>((name, version, dependencies))
+                }
+            })
+            .buffered(config::concurrency())
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+        let edges = dependencies
+            .into_iter()
+            .flat_map(|(name, version, dependencies)| {
+                dependencies
+                    .into_iter()
+                    .filter(|dependency| dependency.kind != "dev" && !dependency.optional)
+                    .map(move |dependency| {
+                        let edge = Edge {
+                            name: name.clone(),
+                            version: version.clone(),
+                            req: dependency.req,
+                        };
+                        (dependency.crate_id, edge)
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        let missing = edges
+            .iter()
+            .map(|(dependency, _)| dependency.clone())
+            .filter(|dependency| !crates.contains_key(dependency))
+            .collect::<std::collections::BTreeSet<_>>();
+        let fetched = stream::iter(missing)
+            .map(|name| {
+                let client = client.clone();
+                async move {
+                    let api_crate = client
+                        .get_crate(&name)
+                        .await
+                        .map_err(crate_not_found(&name))?;
+                    Ok::<_, Error>((name, api_crate))
+                }
+            })
+            .buffered(config::concurrency())
+            .collect::<Vec<_>>()
+            .await;
+        for result in fetched {
+            let (name, api_crate) = result?;
+            crates.insert(name, api_crate);
+        }
+
+        level = Vec::new();
+        for (dependency, edge) in edges {
+            let api_crate = &crates[&dependency];
+            let Some(version) = newest_matching(api_crate, &edge.req) else {
+                tracing::warn!(
+                    "no version of {} matches {} required by {} {}",
+                    dependency,
+                    edge.req,
+                    edge.name,
+                    edge.version
+                );
+                continue;
+            };
+            let node = nodes
+                .entry((dependency.clone(), version.clone()))
+                .or_insert_with(|| {
+                    level.push((dependency.clone(), version.clone()));
+                    Node {
+                        name: dependency.clone(),
+                        license: license(api_crate, &version),
+                        version,
+                        depth,
+                        parents: Vec::new(),
+                    }
+                });
+            node.parents.push(edge);
+        }
+    }
+    let mut nodes = nodes.into_values().collect::<Vec<_>>();
+    nodes.sort_by(|a, b| (a.depth, &a.name).cmp(&(b.depth, &b.name)));
+    Ok(nodes)
+}
+
+fn newest_matching(api_crate: &CrateResponse, req: &str) -> Option<String> {
+    let req = VersionReq::parse(req)?;
+    api_crate
+        .versions
+        .iter()
+        .filter(|version| !version.yanked)
+        .filter_map(|version| Version::parse(&version.num).map(|parsed| (parsed, &version.num)))
+        .filter(|(parsed, _)| req.matches(parsed))
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .map(|(_, num)| num.clone())
+}
+
+fn license(api_crate: &CrateResponse, version: &str) -> Option<String> {
+    api_crate
+        .versions
+        .iter()
+        .find(|found| found.num == version)
+        .and_then(|found| found.license.clone())
+}
+
+/// Crates resolved to more than one semver incompatible version, with every
+/// version of each, like `cargo tree --duplicates`.
+pub fn duplicates(nodes: &[Node]) -> Vec<(&str, Vec<&Node>)> {
+    let mut versions: BTreeMap<&str, Vec<&Node>> = BTreeMap::new();
+    for node in nodes {
+        versions.entry(&node.name).or_default().push(node);
+    }
+    versions
+        .into_iter()
+        .filter(|(_, nodes)| {
+            nodes
+                .iter()
+                .map(|node| Version::parse(&node.version).map(|version| compatibility(&version)))
+                .unique()
+                .count()
+                > 1
+        })
+        .map(|(name, mut nodes)| {
+            nodes.sort_by(|a, b| Version::parse(&b.version).cmp(&Version::parse(&a.version)));
+            (name, nodes)
+        })
+        .collect()
+}
+
+// cargo unifies versions sharing their leftmost non-zero component
+fn compatibility(version: &Version) -> (u64, u64, u64) {
+    match (version.major, version.minor) {
+        (0, 0) => (0, 0, version.patch),
+        (0, minor) => (0, minor, 0),
+        (major, _) => (major, 0, 0),
+    }
+}
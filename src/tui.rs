@@ -0,0 +1,387 @@
+use crate::api::Client;
+use crate::error::{user_not_found, Context, Result};
+use crate::get_user_crates;
+use chrono::NaiveDate;
+use crabst_lib::stats::daily_downloads;
+use crates_io_api::Crate;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::symbols::Marker;
+use ratatui::text::Line;
+use ratatui::widgets::{
+    Axis, Block, Cell, Chart, Dataset, GraphType, Paragraph, Row, Table, TableState,
+};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Name,
+    Downloads,
+    Recent,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Pane {
+    Crates,
+    Dependents,
+}
+
+struct CrateDetails {
+    downloads: Vec<(NaiveDate, u64)>,
+    dependents: Vec<(String, u64)>,
+    dependents_total: u64,
+}
+
+struct App {
+    user_name: String,
+    crates: Vec<Crate>,
+    sort: Column,
+    desc: bool,
+    focus: Pane,
+    crates_state: TableState,
+    dependents_state: TableState,
+    details: HashMap<String, CrateDetails>,
+    status: String,
+}
+
+pub async fn run(client: &Client, user_name: &str, refresh: Duration) -> Result<()> {
+    let mut app = App {
+        user_name: user_name.to_string(),
+        crates: Vec::new(),
+        sort: Column::Downloads,
+        desc: true,
+        focus: Pane::Crates,
+        crates_state: TableState::default().with_selected(Some(0)),
+        dependents_state: TableState::default(),
+        details: HashMap::new(),
+        status: String::new(),
+    };
+    app.load_crates(client).await?;
+
+    let mut terminal = ratatui::init();
+    let result = app.event_loop(&mut terminal, client, refresh).await;
+    ratatui::restore();
+    result
+}
+
+impl App {
+    async fn event_loop(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        client: &Client,
+        refresh: Duration,
+    ) -> Result<()> {
+        let mut last_refresh = Instant::now();
+        loop {
+            terminal
+                .draw(|frame| self.draw(frame))
+                .context("can not draw the dashboard")?;
+
+            if let Some(crate_name) = self.selected_crate() {
+                if !self.details.contains_key(&crate_name) {
+                    self.status = format!("Fetching {}...", crate_name);
+                    terminal
+                        .draw(|frame| self.draw(frame))
+                        .context("can not draw the dashboard")?;
+                    self.load_details(client, &crate_name).await;
+                    continue;
+                }
+            }
+
+            if last_refresh.elapsed() >= refresh {
+                self.refresh(client).await;
+                last_refresh = Instant::now();
+                continue;
+            }
+
+            if !event::poll(Duration::from_millis(250)).context("can not read terminal events")? {
+                continue;
+            }
+            let Event::Key(key) = event::read().context("can not read terminal events")? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Down | KeyCode::Char('j') => self.select(1),
+                KeyCode::Up | KeyCode::Char('k') => self.select(-1),
+                KeyCode::Tab => {
+                    self.focus = match self.focus {
+                        Pane::Crates => Pane::Dependents,
+                        Pane::Dependents => Pane::Crates,
+                    }
+                }
+                KeyCode::Char('s') => {
+                    self.sort = match self.sort {
+                        Column::Name => Column::Downloads,
+                        Column::Downloads => Column::Recent,
+                        Column::Recent => Column::Name,
+                    };
+                    self.sort_crates();
+                }
+                KeyCode::Char('d') => {
+                    self.desc = !self.desc;
+                    self.sort_crates();
+                }
+                KeyCode::Char('r') => {
+                    self.refresh(client).await;
+                    last_refresh = Instant::now();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    async fn load_crates(&mut self, client: &Client) -> Result<()> {
+        let user = client
+            .user(&self.user_name)
+            .await
+            .map_err(user_not_found(&self.user_name))?;
+        self.crates = get_user_crates(client, user.id, &self.user_name).await?;
+        self.sort_crates();
+        Ok(())
+    }
+
+    async fn load_details(&mut self, client: &Client, crate_name: &str) {
+        let downloads = client.crate_downloads(crate_name).await;
+        let dependents = client.crate_reverse_dependencies_page(crate_name, 1).await;
+        match (downloads, dependents) {
+            (Ok(downloads), Ok(dependents)) => {
+                let mut dependent_rows = dependents
+                    .dependencies
+                    .iter()
+                    .map(|rd| (rd.crate_version.crate_name.clone(), rd.dependency.downloads))
+                    .collect::<Vec<_>>();
+                dependent_rows.sort_by_key(|row| std::cmp::Reverse(row.1));
+                self.details.insert(
+                    crate_name.to_string(),
+                    CrateDetails {
+                        downloads: daily_downloads(&downloads).into_iter().collect(),
+                        dependents: dependent_rows,
+                        dependents_total: dependents.meta.total,
+                    },
+                );
+                self.status.clear();
+            }
+            (Err(err), _) | (_, Err(err)) => {
+                self.details.insert(
+                    crate_name.to_string(),
+                    CrateDetails {
+                        downloads: Vec::new(),
+                        dependents: Vec::new(),
+                        dependents_total: 0,
+                    },
+                );
+                self.status = format!("can not fetch {}: {}", crate_name, err);
+            }
+        }
+    }
+
+    async fn refresh(&mut self, client: &Client) {
+        let selected = self.selected_crate();
+        self.details.clear();
+        match self.load_crates(client).await {
+            Ok(()) => self.status.clear(),
+            Err(err) => self.status = err.to_string(),
+        }
+        let index = selected
+            .and_then(|name| self.crates.iter().position(|c| c.name == name))
+            .unwrap_or(0);
+        self.crates_state.select(Some(index));
+    }
+
+    fn sort_crates(&mut self) {
+        let selected = self.selected_crate();
+        match self.sort {
+            Column::Name => self.crates.sort_by(|a, b| a.name.cmp(&b.name)),
+            Column::Downloads => self.crates.sort_by_key(|c| c.downloads),
+            Column::Recent => self.crates.sort_by_key(|c| c.recent_downloads.unwrap_or(0)),
+        }
+        if self.desc {
+            self.crates.reverse();
+        }
+        let index = selected
+            .and_then(|name| self.crates.iter().position(|c| c.name == name))
+            .unwrap_or(0);
+        self.crates_state.select(Some(index));
+    }
+
+    fn selected_crate(&self) -> Option<String> {
+        self.crates_state
+            .selected()
+            .and_then(|index| self.crates.get(index))
+            .map(|c| c.name.clone())
+    }
+
+    fn select(&mut self, step: isize) {
+        let (state, len) = match self.focus {
+            Pane::Crates => (&mut self.crates_state, self.crates.len()),
+            Pane::Dependents => {
+                let len = self
+                    .selected_crate()
+                    .and_then(|name| self.details.get(&name))
+                    .map_or(0, |details| details.dependents.len());
+                (&mut self.dependents_state, len)
+            }
+        };
+        if len == 0 {
+            return;
+        }
+        let index = state.selected().unwrap_or(0) as isize + step;
+        state.select(Some(index.clamp(0, len as isize - 1) as usize));
+        if self.focus == Pane::Crates {
+            self.dependents_state.select(None);
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, footer] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(main);
+        let [graph, dependents] =
+            Layout::vertical([Constraint::Percentage(55), Constraint::Percentage(45)]).areas(right);
+
+        self.draw_crates(frame, left);
+        self.draw_graph(frame, graph);
+        self.draw_dependents(frame, dependents);
+
+        let help = "q quit  j/k move  tab switch pane  s sort  d reverse  r refresh";
+        let footer_text = if self.status.is_empty() {
+            help.to_string()
+        } else {
+            format!("{}  |  {}", self.status, help)
+        };
+        frame.render_widget(Paragraph::new(footer_text).dark_gray(), footer);
+    }
+
+    fn draw_crates(&mut self, frame: &mut Frame, area: Rect) {
+        let header_cell = |title: &str, column: Column| {
+            let marker = match (self.sort == column, self.desc) {
+                (true, true) => " ▼",
+                (true, false) => " ▲",
+                _ => "",
+            };
+            Cell::from(format!("{}{}", title, marker))
+        };
+        let header = Row::new(vec![
+            header_cell("Crate Name", Column::Name),
+            header_cell("Downloads", Column::Downloads),
+            header_cell("Recent", Column::Recent),
+        ])
+        .bold();
+        let rows = self.crates.iter().map(|c| {
+            Row::new(vec![
+                Cell::from(c.name.clone()),
+                Cell::from(Line::from(c.downloads.to_string()).right_aligned()),
+                Cell::from(Line::from(c.recent_downloads.unwrap_or(0).to_string()).right_aligned()),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Fill(1),
+                Constraint::Length(12),
+                Constraint::Length(10),
+            ],
+        )
+        .header(header)
+        .block(
+            Block::bordered()
+                .title(format!(
+                    " {} crates ({}) ",
+                    self.user_name,
+                    self.crates.len()
+                ))
+                .border_style(self.border_style(Pane::Crates)),
+        )
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, area, &mut self.crates_state);
+    }
+
+    fn draw_graph(&self, frame: &mut Frame, area: Rect) {
+        let crate_name = self.selected_crate().unwrap_or_default();
+        let block = Block::bordered().title(format!(" {} daily downloads ", crate_name));
+        let Some(details) = self.details.get(&crate_name) else {
+            frame.render_widget(Paragraph::new("Loading...").block(block), area);
+            return;
+        };
+        let points = details
+            .downloads
+            .iter()
+            .enumerate()
+            .map(|(i, (_, count))| (i as f64, *count as f64))
+            .collect::<Vec<_>>();
+        let max = points.iter().map(|p| p.1).fold(0.0, f64::max).max(1.0);
+        let date_label = |index: usize| {
+            details
+                .downloads
+                .get(index)
+                .map_or(String::new(), |(date, _)| date.to_string())
+        };
+        let chart = Chart::new(vec![Dataset::default()
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Cyan))
+            .data(&points)])
+        .block(block)
+        .x_axis(
+            Axis::default()
+                .bounds([0.0, points.len().saturating_sub(1) as f64])
+                .labels([date_label(0), date_label(points.len().saturating_sub(1))]),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([0.0, max])
+                .labels(["0".to_string(), format!("{}", max as u64)]),
+        );
+        frame.render_widget(chart, area);
+    }
+
+    fn draw_dependents(&mut self, frame: &mut Frame, area: Rect) {
+        let crate_name = self.selected_crate().unwrap_or_default();
+        let (rows, title) = match self.details.get(&crate_name) {
+            Some(details) => (
+                details
+                    .dependents
+                    .iter()
+                    .map(|(name, downloads)| {
+                        Row::new(vec![
+                            Cell::from(name.clone()),
+                            Cell::from(Line::from(downloads.to_string()).right_aligned()),
+                        ])
+                    })
+                    .collect::<Vec<_>>(),
+                format!(
+                    " dependents ({} of {}) ",
+                    details.dependents.len(),
+                    details.dependents_total
+                ),
+            ),
+            None => (Vec::new(), " dependents ".to_string()),
+        };
+        let table = Table::new(rows, [Constraint::Fill(1), Constraint::Length(12)])
+            .header(Row::new(vec!["Crate Name", "Downloads"]).bold())
+            .block(
+                Block::bordered()
+                    .title(title)
+                    .border_style(self.border_style(Pane::Dependents)),
+            )
+            .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(table, area, &mut self.dependents_state);
+    }
+
+    fn border_style(&self, pane: Pane) -> Style {
+        if self.focus == pane {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use std::collections::BTreeMap;
+
+const KEYBOARD: [&str; 4] = ["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+/// Common misspellings of a crate name, keyed by the crates.io normalized name
+/// and mapped to how they were made. The name itself is never included.
+pub fn variants(name: &str) -> BTreeMap<String, &'static str> {
+    let original = normalize(name);
+    let chars = original.chars().collect::<Vec<_>>();
+    let mut variants = BTreeMap::new();
+    let mut add = |variant: String, kind: &'static str| {
+        let variant = normalize(&variant);
+        if variant != original && valid(&variant) {
+            variants.entry(variant).or_insert(kind);
+        }
+    };
+    for i in 0..chars.len() {
+        let mut omitted = chars.clone();
+        omitted.remove(i);
+        add(omitted.iter().collect(), "omission");
+
+        let mut doubled = chars.clone();
+        doubled.insert(i, chars[i]);
+        add(doubled.iter().collect(), "repetition");
+
+        if i + 1 < chars.len() {
+            let mut swapped = chars.clone();
+            swapped.swap(i, i + 1);
+            add(swapped.iter().collect(), "transposition");
+
+            if chars[i] != '-' && chars[i + 1] != '-' {
+                let mut split = chars.clone();
+                split.insert(i + 1, '-');
+                add(split.iter().collect(), "hyphenation");
+            }
+        }
+
+        for neighbor in neighbors(chars[i]) {
+            let mut replaced = chars.clone();
+            replaced[i] = neighbor;
+            add(replaced.iter().collect(), "adjacent key");
+        }
+    }
+    add(original.replace('-', ""), "hyphenation");
+    for affixed in [
+        format!("{}-rs", original),
+        format!("{}rs", original),
+        format!("rust-{}", original),
+    ] {
+        add(affixed, "affix");
+    }
+    if let Some(stripped) = ["-rs", "rs"]
+        .iter()
+        .find_map(|suffix| original.strip_suffix(suffix))
+    {
+        add(stripped.to_string(), "affix");
+    }
+    variants
+}
+
+/// crates.io treats `-` and `_` as the same and ignores case.
+pub fn normalize(name: &str) -> String {
+    name.to_ascii_lowercase().replace('_', "-")
+}
+
+fn valid(name: &str) -> bool {
+    crate::valid_crate_name(name) && !name.ends_with('-') && !name.contains("--")
+}
+
+fn neighbors(c: char) -> Vec<char> {
+    let rows = KEYBOARD
+        .iter()
+        .map(|row| row.chars().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    for row in &rows {
+        if let Some(i) = row.iter().position(|key| *key == c) {
+            return [i.checked_sub(1), Some(i + 1)]
+                .into_iter()
+                .flatten()
+                .filter_map(|j| row.get(j).copied())
+                .collect();
+        }
+    }
+    Vec::new()
+}
@@ -0,0 +1,299 @@
+mod mock;
+
+use mock::{Registry, DAYS};
+use std::path::Path;
+
+// compares against tests/snapshots/<name>, UPDATE_SNAPSHOTS=1 rewrites them
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("snapshots")
+        .join(name);
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "missing snapshot {}, run with UPDATE_SNAPSHOTS=1 to create it",
+            name
+        )
+    });
+    assert_eq!(
+        expected, actual,
+        "output differs from {}, run with UPDATE_SNAPSHOTS=1 to accept it",
+        name
+    );
+}
+
+fn run(registry: &Registry, args: &[&str]) -> String {
+    let output = registry.run(args);
+    assert!(
+        output.status.success(),
+        "crabst {} failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn window() -> [&'static str; 4] {
+    ["--from", DAYS[0], "--to", DAYS[DAYS.len() - 1]]
+}
+
+#[test]
+fn crate_table() {
+    let registry = Registry::start();
+    let mut args = vec!["crate", "foo"];
+    args.extend(window());
+    assert_snapshot("crate.txt", &run(&registry, &args));
+}
+
+#[test]
+fn crate_json() {
+    let registry = Registry::start();
+    let mut args = vec!["-o", "json", "crate", "foo,bar"];
+    args.extend(window());
+    assert_snapshot("crate.json", &run(&registry, &args));
+}
+
+#[test]
+fn missing_crate_fails() {
+    let registry = Registry::start();
+    let output = registry.run(&["crate", "nope"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("nope"));
+}
+
+#[test]
+fn user_agent_is_sent() {
+    let registry = Registry::start();
+    let output = registry.run(&["crate", "foo", "--user-agent", "crabst (me@example.com)"]);
+    assert!(output.status.success());
+    let output = registry.run(&["crate", "foo", "--user-agent", "curl"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn user_table() {
+    let registry = Registry::start();
+    let mut args = vec!["user", "alice"];
+    args.extend(window());
+    assert_snapshot("user.txt", &run(&registry, &args));
+}
+
+#[test]
+fn user_json() {
+    let registry = Registry::start();
+    let mut args = vec!["-o", "json", "user", "alice"];
+    args.extend(window());
+    assert_snapshot("user.json", &run(&registry, &args));
+}
+
+#[test]
+fn user_crates_are_paginated() {
+    let registry = Registry::start();
+    let mut args = vec!["-o", "json", "user", "carol"];
+    args.extend(window());
+    let json: serde_json::Value = serde_json::from_str(&run(&registry, &args)).unwrap();
+    let crates = json["crates"].as_array().unwrap();
+    assert_eq!(crates.len(), 102);
+    assert_eq!(crates[101]["crate"]["name"], "crate-101");
+}
+
+#[test]
+fn peak_day_keeps_json_parseable() {
+    let registry = Registry::start();
+    for crates in ["foo", "foo,bar"] {
+        let mut args = vec!["-o", "json", "crate", crates, "--peak-day"];
+        args.extend(window());
+        let stdout = run(&registry, &args);
+        assert!(
+            serde_json::from_str::<serde_json::Value>(&stdout).is_ok(),
+            "{}",
+            stdout
+        );
+    }
+}
+
+#[test]
+fn owner_changes_keep_json_parseable() {
+    let registry = Registry::start();
+    let state =
+        std::env::temp_dir().join(format!("crabst-test-owners-{}.json", std::process::id()));
+    std::fs::write(&state, r#"{"foo": ["alice", "mallory"]}"#).unwrap();
+    let mut args = vec!["-o", "json", "crate", "foo", "--track-owners"];
+    args.extend(["--state-file", state.to_str().unwrap()]);
+    args.extend(window());
+    let changed: serde_json::Value = serde_json::from_str(&run(&registry, &args)).unwrap();
+    let unchanged: serde_json::Value = serde_json::from_str(&run(&registry, &args)).unwrap();
+    let _ = std::fs::remove_file(&state);
+    assert_eq!(
+        changed["owner_changes"],
+        serde_json::json!([
+            {"crate": "foo", "change": "added", "owner": "dave"},
+            {"crate": "foo", "change": "removed", "owner": "mallory"},
+        ])
+    );
+    assert_eq!(unchanged["owner_changes"], serde_json::json!([]));
+}
+
+#[test]
+fn failed_downloads_are_marked() {
+    let registry = Registry::start();
+    let mut args = vec!["user", "dave"];
+    args.extend(window());
+    let stdout = run(&registry, &args);
+    assert_snapshot(
+        "user_failed.txt",
+        &stdout.replace(registry.url(), "<registry>"),
+    );
+
+    args.push("--strict");
+    let output = registry.run(&args);
+    assert_eq!(output.status.code(), Some(4));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("can not fetch downloads of broken"));
+}
+
+#[test]
+fn deps_with_advisories_and_unmaintained() {
+    let registry = Registry::start();
+    let project = std::env::temp_dir().join(format!("crabst-test-deps-{}", std::process::id()));
+    std::fs::create_dir_all(&project).unwrap();
+    std::fs::write(
+        project.join("Cargo.toml"),
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\nbar = \"1\"\nfoo = \"1\"\n",
+    )
+    .unwrap();
+    let project = project.to_str().unwrap();
+    let args = ["deps", project, "--advisories", "--unmaintained", "1"];
+    assert_snapshot("deps.txt", &run(&registry, &args));
+}
+
+#[test]
+fn stdin_csv_with_quoted_commas() {
+    let registry = Registry::start();
+    let output = registry.run_with_stdin(
+        &["-o", "json", "--stdin"],
+        "description,name\n\"fast, small\",foo\n\"bar, but slower\",bar\n",
+    );
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let names = json["crates"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["crate"]["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["foo", "bar"]);
+}
+
+#[test]
+fn dependents_table() {
+    let registry = Registry::start();
+    assert_snapshot("dependents.txt", &run(&registry, &["dependents", "foo"]));
+}
+
+#[test]
+fn dependents_json() {
+    let registry = Registry::start();
+    assert_snapshot(
+        "dependents.json",
+        &run(&registry, &["-o", "json", "dependents", "foo"]),
+    );
+}
+
+#[test]
+fn bad_ages_are_usage_errors() {
+    let registry = Registry::start();
+    for age in ["3д", "99999999999999d", "d", "-30d"] {
+        let output = registry.run(&["diff", "foo", &format!("--since={}", age)]);
+        assert_eq!(output.status.code(), Some(2), "{}", age);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("invalid value"));
+    }
+}
+
+#[test]
+fn badge_counts_only_the_last_days() {
+    // the mock only has downloads from 2024, which are not in the last week
+    let registry = Registry::start();
+    let output = registry.run(&["badge", "foo"]);
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["message"], "0/week");
+}
+
+#[test]
+fn grouped_svg_labels_the_buckets() {
+    let registry = Registry::start();
+    let svg = run(
+        &registry,
+        &["crate", "foo", "-o", "svg", "--group-by", "month"],
+    );
+    assert!(svg.contains(">2024-03</text>"));
+    assert!(!svg.contains(">2024-03-01</text>"));
+    let polyline = svg.lines().find(|line| line.contains("<polyline")).unwrap();
+    assert_eq!(polyline.matches(',').count(), 1);
+}
+
+#[test]
+fn last_zero_is_a_usage_error() {
+    let registry = Registry::start();
+    let output = registry.run(&["-o", "graph", "crate", "foo", "--last", "0"]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn porcelain_needs_latest_only() {
+    let registry = Registry::start();
+    let output = registry.run(&["crate", "foo", "--porcelain"]);
+    assert_eq!(output.status.code(), Some(2));
+    let mut args = vec!["crate", "foo", "--porcelain", "--latest-only"];
+    args.extend(window());
+    assert_eq!(run(&registry, &args), "52\n");
+}
+
+#[test]
+fn latest_only_json_is_parseable() {
+    let registry = Registry::start();
+    let mut args = vec!["-o", "json", "crate", "foo", "--latest-only"];
+    args.extend(window());
+    let json: serde_json::Value = serde_json::from_str(&run(&registry, &args)).unwrap();
+    assert_eq!(json["crate"], "foo");
+    assert_eq!(json["date"], DAYS[DAYS.len() - 1]);
+    assert_eq!(json["downloads"], 52);
+}
+
+#[test]
+fn snapshot_json_is_parseable() {
+    let registry = Registry::start();
+    let db = std::env::temp_dir().join(format!("crabst-test-snapshot-{}.db", std::process::id()));
+    let args = [
+        "-o",
+        "json",
+        "snapshot",
+        "foo",
+        "--db",
+        db.to_str().unwrap(),
+    ];
+    let json: serde_json::Value = serde_json::from_str(&run(&registry, &args)).unwrap();
+    let _ = std::fs::remove_file(&db);
+    assert_eq!(json[0]["crate"], "foo");
+    assert_eq!(json[0]["days"], DAYS.len());
+    assert!(json[0]["taken_at"].is_string());
+}
+
+#[test]
+fn empty_graphs_say_so() {
+    // the mock only has downloads from 2024
+    let registry = Registry::start();
+    for args in [
+        &["crate", "foo"][..],
+        &["crate", "foo", "--group-by", "month"],
+        &["crate", "foo,bar"],
+    ] {
+        let mut args = args.to_vec();
+        args.extend(["-o", "graph", "--from", "2025-01-01", "--to", "2025-01-31"]);
+        assert_eq!(run(&registry, &args), "No downloads found\n", "{:?}", args);
+    }
+}
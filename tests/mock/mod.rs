@@ -0,0 +1,325 @@
+//! A crates.io stand-in serving canned json on a local port, enough for the
+//! crate, user, owners, dependents and deps commands, with the osv and github
+//! apis under `/osv` and `/gh`.
+
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+pub const DAYS: [&str; 3] = ["2024-03-01", "2024-03-02", "2024-03-03"];
+
+// downloads of each crate, alice owns the first two
+const CRATES: [(&str, u64); 3] = [("foo", 5000), ("bar", 1200), ("baz", 300)];
+// carol owns more crates than fit a page
+const CAROL_CRATES: usize = 102;
+// found on the registry but its downloads fail, dave owns it with foo
+const BROKEN: &str = "broken";
+
+pub struct Registry {
+    url: String,
+    config: PathBuf,
+}
+
+impl Registry {
+    pub fn start() -> Registry {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                std::thread::spawn(move || serve(stream));
+            }
+        });
+        let config = std::env::temp_dir().join(format!("crabst-test-{}.toml", port));
+        let server = format!("http://127.0.0.1:{}", port);
+        std::fs::write(
+            &config,
+            format!(
+                "request_interval_ms = 0\nosv_url = \"{0}/osv\"\ngithub_url = \"{0}/gh\"\n",
+                server
+            ),
+        )
+        .unwrap();
+        Registry {
+            url: format!("http://127.0.0.1:{}/api/v1", port),
+            config,
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Runs crabst against the registry without cache or user config.
+    pub fn run(&self, args: &[&str]) -> Output {
+        self.run_with_stdin(args, "")
+    }
+
+    pub fn run_with_stdin(&self, args: &[&str], stdin: &str) -> Output {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_crabst"))
+            .args(["--registry-url", &self.url, "--no-cache", "--retries", "0"])
+            .args(args)
+            .env("CRABST_CONFIG", &self.config)
+            .env_remove("CRABST_TOKEN")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(stdin.as_bytes())
+            .unwrap();
+        child.wait_with_output().unwrap()
+    }
+}
+
+fn serve(stream: TcpStream) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    let mut content_length = 0;
+    let mut user_agent = String::new();
+    let mut header = String::new();
+    while reader.read_line(&mut header).is_ok_and(|len| len > 2) {
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            } else if name.eq_ignore_ascii_case("user-agent") {
+                user_agent = value.trim().to_string();
+            }
+        }
+        header.clear();
+    }
+    let mut body = vec![0; content_length];
+    if reader.read_exact(&mut body).is_err() {
+        return;
+    }
+    let mut request = request_line.split(' ');
+    let method = request.next().unwrap_or_default();
+    let target = request.next().unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .collect::<Vec<_>>();
+    let param = |name: &str| {
+        query
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| *value)
+    };
+    let segments = path.trim_start_matches('/').split('/').collect::<Vec<_>>();
+    // crates.io and github refuse clients that do not identify themselves
+    if !user_agent.starts_with("crabst") {
+        let body = json!({"errors": [{"detail": "missing user agent"}]});
+        return respond(stream, "403 Forbidden", body);
+    }
+    let body = match (method, segments.as_slice()) {
+        ("GET", ["api", "v1", "crates"]) => {
+            param("user_id").and_then(|id| user_crates(id, param("page")))
+        }
+        ("GET", ["api", "v1", "crates", name]) => {
+            crate_info(name).map(|(api_crate, versions)| {
+                json!({"crate": api_crate, "versions": versions, "keywords": [], "categories": []})
+            })
+        }
+        ("GET", ["api", "v1", "crates", name, "downloads"]) => downloads(name),
+        ("GET", ["api", "v1", "crates", name, "reverse_dependencies"]) => dependents(name),
+        ("GET", ["api", "v1", "crates", name, "owners"]) => owners(name),
+        ("GET", ["api", "v1", "users", login]) => user(login).map(|user| json!({ "user": user })),
+        ("POST", ["osv", "query"]) => advisories(&body),
+        ("GET", ["gh", "repos", "x", name]) => repository(name),
+        ("GET", ["gh", "repos", "x", name, "commits"]) => commits(name),
+        _ => None,
+    };
+    let (status, body) = match body {
+        Some(body) => ("200 OK", body),
+        None => (
+            "404 Not Found",
+            json!({"errors": [{"detail": "Not Found"}]}),
+        ),
+    };
+    respond(stream, status, body);
+}
+
+fn respond(mut stream: TcpStream, status: &str, body: Value) {
+    let body = body.to_string();
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+}
+
+fn crate_downloads(name: &str) -> Option<u64> {
+    if name == BROKEN {
+        return Some(7);
+    }
+    if let Some((_, downloads)) = CRATES.iter().find(|(known, _)| *known == name) {
+        return Some(*downloads);
+    }
+    let index = name.strip_prefix("crate-")?.parse::<u64>().ok()?;
+    (index < CAROL_CRATES as u64).then_some(index * 10)
+}
+
+fn crate_info(name: &str) -> Option<(Value, Vec<Value>)> {
+    let downloads = crate_downloads(name)?;
+    let api_crate = json!({
+        "id": name, "name": name, "description": format!("{} crate", name),
+        "license": "MIT", "documentation": null, "homepage": null,
+        "repository": format!("https://github.com/x/{}", name),
+        "downloads": downloads, "recent_downloads": downloads / 10,
+        "categories": null, "keywords": null, "versions": [1],
+        "max_version": "1.0.0", "max_stable_version": "1.0.0",
+        "links": {
+            "owner_team": "", "owner_user": "", "owners": "",
+            "reverse_dependencies": "", "version_downloads": "", "versions": null
+        },
+        "created_at": "2024-01-01T00:00:00Z", "updated_at": "2024-02-01T00:00:00Z",
+        "exact_match": null
+    });
+    Some((api_crate, vec![version(name, 1, "1.0.0")]))
+}
+
+fn version(name: &str, id: u64, num: &str) -> Value {
+    json!({
+        "crate": name, "created_at": "2024-02-01T00:00:00Z",
+        "updated_at": "2024-02-01T00:00:00Z", "dl_path": "", "downloads": 100,
+        "features": {}, "id": id, "num": num, "yanked": false, "license": "MIT",
+        "readme_path": null, "links": {"dependencies": "", "version_downloads": ""},
+        "crate_size": 20000, "published_by": null, "rust_version": null
+    })
+}
+
+fn downloads(name: &str) -> Option<Value> {
+    if name == BROKEN {
+        return None;
+    }
+    let base = crate_downloads(name)? / 100;
+    let version_downloads = DAYS
+        .iter()
+        .enumerate()
+        .map(|(day, date)| json!({"date": date, "downloads": base + day as u64, "version": 1}))
+        .collect::<Vec<_>>();
+    Some(json!({
+        "version_downloads": version_downloads,
+        "meta": {"extra_downloads": [{"date": DAYS[0], "downloads": 1}]}
+    }))
+}
+
+fn dependents(name: &str) -> Option<Value> {
+    crate_downloads(name)?;
+    let others = CRATES
+        .iter()
+        .filter(|(other, _)| *other != name)
+        .collect::<Vec<_>>();
+    let dependencies = others
+        .iter()
+        .enumerate()
+        .map(|(i, (_, downloads))| {
+            json!({
+                "crate_id": name, "default_features": true, "downloads": downloads,
+                "features": [], "id": i, "kind": "normal", "optional": false,
+                "req": "^1", "target": null, "version_id": 100 + i
+            })
+        })
+        .collect::<Vec<_>>();
+    let versions = others
+        .iter()
+        .enumerate()
+        .map(|(i, (other, _))| version(other, 100 + i as u64, "0.1.0"))
+        .collect::<Vec<_>>();
+    Some(json!({
+        "dependencies": dependencies,
+        "versions": versions,
+        "meta": {"total": others.len()}
+    }))
+}
+
+fn owners(name: &str) -> Option<Value> {
+    crate_downloads(name)?;
+    let logins = match name {
+        "foo" => vec!["alice", "dave"],
+        "bar" => vec!["alice"],
+        BROKEN => vec!["dave"],
+        _ => Vec::new(),
+    };
+    let users = logins.into_iter().filter_map(user).collect::<Vec<_>>();
+    Some(json!({ "users": users }))
+}
+
+fn user(login: &str) -> Option<Value> {
+    let id = match login {
+        "alice" => 1,
+        "carol" => 3,
+        "dave" => 4,
+        _ => return None,
+    };
+    Some(json!({
+        "avatar": null, "email": null, "id": id, "kind": "user", "login": login,
+        "name": login, "url": format!("https://github.com/{}", login)
+    }))
+}
+
+fn user_crates(user_id: &str, page: Option<&str>) -> Option<Value> {
+    let names = match user_id {
+        "1" => vec!["bar".to_string(), "foo".to_string()],
+        "3" => (0..CAROL_CRATES)
+            .map(|index| format!("crate-{:03}", index))
+            .collect(),
+        "4" => vec![BROKEN.to_string(), "foo".to_string()],
+        _ => return None,
+    };
+    let page = page
+        .and_then(|page| page.parse::<usize>().ok())
+        .unwrap_or(1);
+    let crates = names
+        .iter()
+        .skip((page - 1) * 100)
+        .take(100)
+        .filter_map(|name| crate_info(name).map(|(api_crate, _)| api_crate))
+        .collect::<Vec<_>>();
+    Some(json!({"crates": crates, "meta": {"total": names.len()}}))
+}
+
+// foo 1.x has an advisory fixed in 1.2.0
+fn advisories(body: &[u8]) -> Option<Value> {
+    let query: Value = serde_json::from_slice(body).ok()?;
+    if query["package"]["name"] != "foo" {
+        return Some(json!({}));
+    }
+    Some(json!({"vulns": [{
+        "id": "RUSTSEC-2024-0001", "summary": "foo overflow", "aliases": [],
+        "published": "2024-02-15T00:00:00Z",
+        "references": [{"type": "ADVISORY", "url": "https://rustsec.org/advisories/RUSTSEC-2024-0001"}],
+        "affected": [{
+            "package": {"ecosystem": "crates.io", "name": "foo"},
+            "ranges": [{"type": "SEMVER", "events": [{"introduced": "0.0.0-0"}, {"fixed": "1.2.0"}]}]
+        }]
+    }]}))
+}
+
+// bar's repository is archived
+fn repository(name: &str) -> Option<Value> {
+    crate_downloads(name)?;
+    Some(json!({
+        "full_name": format!("x/{}", name), "stargazers_count": 10,
+        "open_issues_count": 1, "archived": name == "bar"
+    }))
+}
+
+fn commits(name: &str) -> Option<Value> {
+    crate_downloads(name)?;
+    Some(json!([{
+        "sha": "abc",
+        "commit": {"committer": {"name": "x", "email": "x@example.com", "date": "2024-02-01T00:00:00Z"}}
+    }]))
+}